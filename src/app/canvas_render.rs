@@ -0,0 +1,327 @@
+//! Contains the canvas render job, which computes the canvas on a background
+//! thread, so that the application stays responsive while it runs.
+
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use futures::executor::block_on;
+
+use crate::{
+    app::SlaveMessage,
+    frac_logic::{
+        buddhabrot::PREVIEW_SAMPLES_PER_PIXEL, complete_matrix, CacheView, DivergMatrix, Histogram,
+        RenderSettings, TileCache,
+    },
+    helpers::Vec2,
+    AppState,
+};
+
+/// The result of a canvas render, which depends on the rendering model.
+pub(crate) enum CanvasRenderResult {
+    /// The divergence matrix, and the fraction of its points filled by the subdivision.
+    Divergence(DivergMatrix, Option<f64>),
+    /// The histogram of the density modes, previewed with fewer samples than the captures.
+    Density(Histogram),
+}
+
+/// A canvas render running on a background thread. Dropping it cancels the render:
+/// the thread exits when it detects that the message pipe is closed.
+pub(crate) struct CanvasRender {
+    receiver: Receiver<SlaveMessage>,
+    /// The coarse previews rendered before the canvas in CPU mode, see `refinement`.
+    previews: Receiver<DivergMatrix>,
+    handle: Option<JoinHandle<Result<CanvasRenderResult, String>>>,
+    /// The number of lines of the canvas and its previews, to compute the progression.
+    lines: i32,
+    rendered_lines: i32,
+    /// Whether the render runs on the GPU.
+    pub(crate) gpu: bool,
+    /// The view used to store the result in the tile cache, if it is enabled.
+    pub(crate) cache_view: Option<CacheView>,
+    /// The number of tiles found in the tile cache and missing, if it was used.
+    pub(crate) cache_usage: Option<(usize, usize)>,
+    start: Instant,
+}
+
+impl CanvasRender {
+    /// Starts rendering the canvas with the given settings. In GPU mode, the render
+    /// shares the GPU objects of the settings instead of initializing new ones.
+    /// In CPU mode, `reuse` can provide the previous divergence matrix, and the move of the
+    /// canvas in cells since it was rendered, so that only the new points are computed.
+    /// Otherwise, the points found in the tile cache are not computed again.
+    pub(crate) fn start(
+        rs: &mut RenderSettings,
+        reuse: Option<(DivergMatrix, Vec2<i32>)>,
+        cache: &mut TileCache,
+    ) -> Result<Self, String> {
+        let size = Vec2::new(rs.canvas_size.x, rs.canvas_size.y);
+        let gpu = rs.wgpu_state.use_gpu && !rs.use_density();
+        let cpu = !gpu && !rs.use_density();
+        let reuse = reuse.filter(|(matrix, shift)| {
+            cpu && shift.x.abs() < size.x
+                && shift.y.abs() < size.y
+                && matrix.len() == size.y as usize
+                && matrix.iter().all(|row| row.len() == size.x as usize + 1)
+        });
+        if gpu {
+            // Reloading the shader here avoids doing it again for each render.
+            rs.update_gpu_precision(&size)?;
+        }
+        let mut rs_copy = rs.clone();
+        rs_copy.wgpu_state = rs.wgpu_state.share();
+
+        let cache_view = (cpu && cache.enabled()).then(|| rs.cache_view(&size));
+        let (known, cache_usage) = match (&cache_view, &reuse) {
+            (Some(view), None) => {
+                let (known, hits, misses) =
+                    cache.lookup(view, size.y as usize, size.x as usize + 1);
+                (Some(known), Some((hits, misses)))
+            }
+            _ => (None, None),
+        };
+        let cached = known.as_ref().and_then(complete_matrix);
+
+        // The previews are not needed when some of the points are reused.
+        let refinement = if !cpu || reuse.is_some() || cache_usage.is_some_and(|(hits, _)| hits > 0)
+        {
+            Vec::new()
+        } else {
+            rs.refinement.clone()
+        };
+        let lines = size.y
+            + refinement
+                .iter()
+                .map(|factor| (size.y + factor - 1) / factor)
+                .sum::<i32>();
+        let (sender, receiver) = mpsc::channel();
+        let (preview_sender, previews) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            for factor in refinement {
+                let preview = rs_copy.get_preview_diverg_matrix(&size, factor, Some(&sender));
+                if preview_sender.send(preview).is_err() {
+                    return Err("The canvas render was cancelled.".to_string());
+                }
+            }
+
+            if rs_copy.use_density() {
+                rs_copy
+                    .get_density_histogram(&size, PREVIEW_SAMPLES_PER_PIXEL, Some(&sender))
+                    .map(CanvasRenderResult::Density)
+            } else if gpu {
+                block_on(rs_copy.get_gpu_diverg_matrix_async(&size, Some(&sender)))
+                    .map(|matrix| CanvasRenderResult::Divergence(matrix, None))
+            } else if let Some(matrix) = cached {
+                Ok(CanvasRenderResult::Divergence(matrix, None))
+            } else if let Some((previous, shift)) = reuse {
+                let matrix =
+                    rs_copy.get_shifted_diverg_matrix(&size, &previous, &shift, Some(&sender));
+                Ok(CanvasRenderResult::Divergence(matrix, None))
+            } else {
+                let (matrix, fill) =
+                    rs_copy.get_canvas_diverg_matrix(&size, known.as_ref(), Some(&sender));
+                Ok(CanvasRenderResult::Divergence(matrix, fill))
+            }
+        });
+
+        Ok(Self {
+            receiver,
+            previews,
+            handle: Some(handle),
+            lines,
+            rendered_lines: 0,
+            gpu,
+            cache_view,
+            cache_usage,
+            start: Instant::now(),
+        })
+    }
+
+    /// Returns the finest preview rendered since the last call, if any.
+    pub(crate) fn preview(&self) -> Option<DivergMatrix> {
+        self.previews.try_iter().last()
+    }
+
+    /// Handles the messages sent by the render, and returns its result once it finished,
+    /// with the time it took.
+    pub(crate) fn poll(
+        &mut self,
+        state: &mut AppState,
+    ) -> Option<(Result<CanvasRenderResult, String>, Duration)> {
+        for message in self.receiver.try_iter() {
+            match message {
+                SlaveMessage::LineRender => self.rendered_lines += 1,
+                SlaveMessage::Warning(warn) => state.log_warn(warn),
+                SlaveMessage::LimitGPUChunkSize(size) => {
+                    state.render_settings.chunk_size_limit = Some(size)
+                }
+                // The status messages of the captures are not displayed for the canvas.
+                SlaveMessage::SetMessage(_) | SlaveMessage::ScrollLogs => {}
+                SlaveMessage::JobFinished => {}
+            }
+        }
+        state.canvas_render_progress =
+            Some(self.rendered_lines.min(self.lines) * 100 / self.lines.max(1));
+
+        if !self.handle.as_ref().is_some_and(JoinHandle::is_finished) {
+            return None;
+        }
+        state.canvas_render_progress = None;
+        let result = self
+            .handle
+            .take()
+            .unwrap()
+            .join()
+            .unwrap_or(Err("The canvas render panicked.".to_string()));
+        Some((result, self.start.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frac_logic::{CanvasCoords, Diverg};
+
+    /// Waits for the render to finish, and returns its result.
+    fn wait(render: &mut CanvasRender, state: &mut AppState) -> CanvasRenderResult {
+        loop {
+            if let Some((result, _)) = render.poll(state) {
+                return result.unwrap();
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_canvas_render() {
+        let mut state = AppState::default();
+        state.render_settings.canvas_size = CanvasCoords::new(40, 30);
+        state.render_settings.reset_cell_size();
+
+        let mut render =
+            CanvasRender::start(&mut state.render_settings, None, &mut state.tile_cache).unwrap();
+        assert!(!render.gpu);
+        let CanvasRenderResult::Divergence(matrix, fill) = wait(&mut render, &mut state) else {
+            panic!("Expected a divergence matrix");
+        };
+        assert_eq!(matrix.len(), 30);
+        assert!(fill.is_some());
+        assert_eq!(state.canvas_render_progress, None);
+        // The 1/8 and 1/2 previews are rendered first, and count in the progression.
+        assert_eq!(render.lines, 30 + 4 + 15);
+        assert_eq!(render.rendered_lines, render.lines);
+        let preview = render.preview().unwrap();
+        assert_eq!(preview.len(), 30);
+        assert!(preview.iter().all(|row| row.len() == 41));
+        assert_eq!(preview[0][0], preview[1][1]);
+
+        // The result is the same as a render on the main thread.
+        let (expected, _) =
+            state
+                .render_settings
+                .get_canvas_diverg_matrix(&Vec2::new(40, 30), None, None);
+        assert_eq!(matrix, expected);
+
+        // Once the result is stored in the tile cache, rendering the same view computes nothing.
+        let (hits, tiles) = render.cache_usage.unwrap();
+        assert_eq!(hits, 0);
+        let view = render.cache_view.clone().unwrap();
+        state.tile_cache.store(&view, &matrix);
+        let mut render =
+            CanvasRender::start(&mut state.render_settings, None, &mut state.tile_cache).unwrap();
+        assert_eq!(render.cache_usage, Some((tiles, 0)));
+        let CanvasRenderResult::Divergence(cached, _) = wait(&mut render, &mut state) else {
+            panic!("Expected a divergence matrix");
+        };
+        assert_eq!(cached, matrix);
+        assert_eq!((render.lines, render.rendered_lines), (30, 0));
+
+        // In GPU mode, the render shares the GPU objects of the settings.
+        // This needs an adapter, which can be a software one such as lavapipe.
+        if let Err(err) = block_on(state.render_settings.initialize_gpu(None)) {
+            eprintln!("Skipping the GPU test: {err}");
+            return;
+        }
+        state.render_settings.wgpu_state.use_gpu = true;
+        let mut render =
+            CanvasRender::start(&mut state.render_settings, None, &mut state.tile_cache).unwrap();
+        assert!(render.gpu);
+        assert_eq!(render.lines, 30);
+        let CanvasRenderResult::Divergence(matrix, None) = wait(&mut render, &mut state) else {
+            panic!("Expected a divergence matrix without subdivision");
+        };
+        let expected = block_on(
+            state
+                .render_settings
+                .get_gpu_diverg_matrix_async(&Vec2::new(40, 30), None),
+        )
+        .unwrap();
+        assert_eq!(matrix, expected);
+        assert!(render.preview().is_none());
+    }
+
+    #[test]
+    fn test_canvas_render_reuse() {
+        let mut state = AppState::default();
+        state.render_settings.canvas_size = CanvasCoords::new(40, 30);
+        state.render_settings.reset_cell_size();
+        let size = Vec2::new(40, 30);
+
+        // The points still visible after the move are copied from the previous matrix.
+        let marker = Diverg::new(12345);
+        let previous = vec![vec![marker; 41]; 30];
+        let reuse = Some((previous.clone(), Vec2::new(3, -2)));
+        let mut render =
+            CanvasRender::start(&mut state.render_settings, reuse, &mut TileCache::default())
+                .unwrap();
+        assert_eq!(render.lines, 30);
+        let CanvasRenderResult::Divergence(matrix, _) = wait(&mut render, &mut state) else {
+            panic!("Expected a divergence matrix");
+        };
+        assert!(render.preview().is_none());
+        for (y, row) in matrix.iter().enumerate() {
+            for (x, diverg) in row.iter().enumerate() {
+                let reused = x + 3 <= 40 && y >= 2;
+                assert_eq!(*diverg == marker, reused, "at {x}, {y}");
+            }
+        }
+
+        // The new points are the same as in a complete render.
+        let expected = state.render_settings.get_diverg_matrix(&size);
+        assert_eq!(matrix[0], expected[0]);
+        assert_eq!(matrix[15][38..], expected[15][38..]);
+
+        // Moving the canvas gives the same result as rendering it again.
+        state.redraw_canvas = false;
+        state.pan(3, -2);
+        state.pan(0, 1);
+        assert_eq!(state.canvas_shift, Some(Vec2::new(3, -1)));
+        state.pan(0, -1);
+        assert_eq!(state.canvas_shift, Some(Vec2::new(3, -2)));
+        let reuse = Some((expected, Vec2::new(3, -2)));
+        let mut render =
+            CanvasRender::start(&mut state.render_settings, reuse, &mut TileCache::default())
+                .unwrap();
+        let CanvasRenderResult::Divergence(matrix, _) = wait(&mut render, &mut state) else {
+            panic!("Expected a divergence matrix");
+        };
+        let expected = state.render_settings.get_diverg_matrix(&size);
+        let mismatches = (matrix.iter().flatten())
+            .zip(expected.iter().flatten())
+            .filter(|(diverg, expected)| diverg.iter != expected.iter)
+            .count();
+        assert!(mismatches * 100 < 41 * 30);
+
+        // The previous matrix is ignored if it does not match the canvas.
+        let reuse = Some((previous[1..].to_vec(), Vec2::new(3, -2)));
+        let mut render =
+            CanvasRender::start(&mut state.render_settings, reuse, &mut TileCache::default())
+                .unwrap();
+        let CanvasRenderResult::Divergence(matrix, _) = wait(&mut render, &mut state) else {
+            panic!("Expected a divergence matrix");
+        };
+        assert!(matrix.iter().flatten().all(|diverg| *diverg != marker));
+    }
+}
@@ -0,0 +1,104 @@
+//! Contains the estimation of the memory and disk space used by the captures,
+//! and the limits they are checked against before they are queued.
+
+use std::mem::size_of;
+
+use crate::frac_logic::Diverg;
+
+use super::WaitingScreenshot;
+
+/// The approximate memory used by the band of a streamed capture.
+const BAND_MEMORY: u64 = 32 * MEGABYTE;
+const MEGABYTE: u64 = 1024 * 1024;
+
+/// The maximum memory and disk space the captures are allowed to use, in megabytes.
+pub(crate) struct CaptureLimits {
+    pub(crate) memory: u64,
+    pub(crate) disk: u64,
+}
+
+impl Default for CaptureLimits {
+    fn default() -> Self {
+        Self {
+            memory: 4096,
+            disk: 16384,
+        }
+    }
+}
+
+/// The estimated memory and disk space used by a capture, in megabytes.
+pub(crate) struct CaptureEstimate {
+    pub(crate) memory: u64,
+    /// The size of the uncompressed image, the file is usually smaller.
+    pub(crate) disk: u64,
+}
+
+/// Returns the memory used to render and encode a line of the capture, in bytes.
+fn line_memory(width: i32) -> u64 {
+    let width = width as u64;
+    // The divergence of the points, the sums of the colors of the samples,
+    // their average and the bytes sent to the encoder.
+    (width + 1) * size_of::<Diverg>() as u64 + width * (12 + 3 + 3)
+}
+
+/// Returns the number of lines rendered at once by a streamed capture,
+/// so that a band fits in `BAND_MEMORY`.
+pub(crate) fn band_lines(width: i32, height: i32) -> i32 {
+    (BAND_MEMORY / line_memory(width)).clamp(1, height as u64) as i32
+}
+
+impl WaitingScreenshot {
+    /// Estimates the memory and disk space used by the capture.
+    pub(crate) fn estimate(&self) -> CaptureEstimate {
+        let pixels = self.size.x as u64 * self.size.y as u64;
+        let band = band_lines(self.size.x, self.size.y) as u64 * line_memory(self.size.x);
+        let memory = if self.rs.use_density() {
+            // Up to 3 channels of counts, which are copied once at the end of the render.
+            pixels * 3 * 4 * 2 + pixels * 3
+        } else if self.streamed() {
+            band
+        } else {
+            // The bands are copied to the image, which is saved once it is complete.
+            band + pixels * 3
+        };
+        CaptureEstimate {
+            memory: memory.div_ceil(MEGABYTE),
+            disk: (pixels * 3).div_ceil(MEGABYTE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::band_lines;
+    use crate::{
+        app::{Supersampling, WaitingScreenshot},
+        frac_logic::RenderSettings,
+        helpers::Vec2,
+    };
+
+    #[test]
+    fn test_capture_estimate() {
+        let mut capture = WaitingScreenshot {
+            size: Vec2::new(65535, 65535),
+            rs: RenderSettings::default(),
+            name: None,
+            supersampling: Supersampling::default(),
+            checkpoint: None,
+        };
+        // PNG captures are streamed, with a few dozen lines at once.
+        assert!(capture.streamed());
+        assert!((1..100).contains(&band_lines(65535, 65535)));
+        let streamed = capture.estimate();
+        assert!(streamed.memory <= 32);
+        assert_eq!(streamed.disk, 12288);
+
+        capture.rs.image_format = image::ImageFormat::Jpeg;
+        assert!(!capture.streamed());
+        let estimate = capture.estimate();
+        assert!(estimate.memory > streamed.disk);
+        assert_eq!(estimate.disk, streamed.disk);
+
+        assert_eq!(band_lines(16, 16), 16);
+    }
+}
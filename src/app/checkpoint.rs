@@ -0,0 +1,264 @@
+//! Contains the checkpoints of the capture jobs: the description of each job and the bands
+//! it already rendered are saved to disk, so that it can be resumed after it was stopped,
+//! or after the application was closed.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use image::ImageFormat;
+use rug::{ops::CompleteRound, Float};
+use serde::{Deserialize, Serialize};
+
+use super::{capture_estimate::band_lines, Supersampling, WaitingScreenshot};
+use crate::{
+    frac_logic::CanvasCoords,
+    helpers::{markup::esc, SavedState, Vec2},
+    AppState,
+};
+
+/// The default directory of the checkpoints, in the working directory.
+pub(crate) const CHECKPOINTS_DIR: &str = ".rsfrac_jobs";
+const JOB_FILE: &str = "job.toml";
+
+/// Describes a capture job, saved in the `job.toml` file of its checkpoint.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct JobDescription {
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    /// The filename of the capture and its state file, without the extension.
+    pub(crate) filename: String,
+    pub(crate) format: String,
+    pub(crate) supersampling: i32,
+    pub(crate) jitter: bool,
+    /// The seed of the jittered offsets, which must not change when the job is resumed.
+    pub(crate) jitter_seed: u32,
+    /// The number of lines of the bands, which must not change when the job is resumed.
+    pub(crate) band_lines: i32,
+    /// The cell size of the capture, which would otherwise depend on the size of the canvas.
+    pub(crate) cell_size: String,
+    /// The render settings which are not saved in the state files, but change the rendered
+    /// points: the resumed bands must be rendered the same way as the saved ones.
+    pub(crate) gpu: bool,
+    pub(crate) perturbation: bool,
+    pub(crate) subdivision: bool,
+    pub(crate) refinement: Vec<i32>,
+    pub(crate) state: SavedState,
+}
+
+/// The checkpoint directory of a capture job.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Checkpoint {
+    pub(crate) dir: PathBuf,
+    pub(crate) band_lines: i32,
+}
+
+impl Checkpoint {
+    /// Saves the description of a capture job in a new checkpoint in the `root` directory.
+    pub(crate) fn create(
+        root: &Path,
+        capture: &WaitingScreenshot,
+        filename: &str,
+    ) -> Result<Self, String> {
+        let job = JobDescription {
+            width: capture.size.x,
+            height: capture.size.y,
+            filename: filename.to_string(),
+            format: capture.rs.image_format.extensions_str()[0].to_string(),
+            supersampling: capture.supersampling.factor,
+            jitter: capture.supersampling.jitter,
+            jitter_seed: capture.supersampling.seed,
+            band_lines: band_lines(capture.size.x, capture.size.y),
+            cell_size: capture.rs.cell_size_from_height(capture.size.y).to_string(),
+            gpu: capture.rs.wgpu_state.use_gpu,
+            perturbation: capture.rs.perturbation,
+            subdivision: capture.rs.subdivision.captures,
+            refinement: capture.rs.refinement.clone(),
+            state: SavedState::from(&capture.rs),
+        };
+        let str = toml::to_string_pretty(&job)
+            .map_err(|err| format!("Could not save the capture job: {}", esc(err)))?;
+
+        // The jobs saved together on exit may have the same timestamp.
+        let mut timestamp = Utc::now().timestamp_micros();
+        while root.join(timestamp.to_string()).exists() {
+            timestamp += 1;
+        }
+        let dir = root.join(timestamp.to_string());
+        fs::create_dir_all(&dir)
+            .and_then(|_| fs::write(dir.join(JOB_FILE), str))
+            .map_err(|err| {
+                format!(
+                    "Could not create the checkpoint <command {}>: {}",
+                    esc(dir.to_string_lossy()),
+                    esc(err)
+                )
+            })?;
+        Ok(Self {
+            dir,
+            band_lines: job.band_lines,
+        })
+    }
+
+    fn band_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("band_{index:05}.rgb"))
+    }
+
+    /// Returns the bytes of a band saved in the checkpoint, if it has the expected length.
+    pub(crate) fn band(&self, index: usize, len: usize) -> Option<Vec<u8>> {
+        fs::read(self.band_path(index))
+            .ok()
+            .filter(|bytes| bytes.len() == len)
+    }
+
+    /// Saves the bytes of a rendered band. The band is written to a temporary file first,
+    /// so that a band interrupted while it is written is not found when resuming.
+    pub(crate) fn save_band(&self, index: usize, bytes: &[u8]) -> Result<(), String> {
+        let tmp = self.dir.join("band.tmp");
+        fs::write(&tmp, bytes)
+            .and_then(|_| fs::rename(&tmp, self.band_path(index)))
+            .map_err(|err| format!("Could not save the band to the checkpoint: {}", esc(err)))
+    }
+
+    /// Returns the number of bands saved in the checkpoint.
+    fn saved_bands(&self) -> usize {
+        fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_name().to_string_lossy().ends_with(".rgb"))
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Deletes the checkpoint, once the capture is saved.
+    pub(crate) fn remove(&self) -> Result<(), String> {
+        fs::remove_dir_all(&self.dir).map_err(|err| {
+            format!(
+                "Could not remove the checkpoint <command {}>: {}",
+                esc(self.dir.to_string_lossy()),
+                esc(err)
+            )
+        })
+    }
+}
+
+/// A capture job found in the checkpoints directory.
+pub(crate) struct UnfinishedJob {
+    pub(crate) checkpoint: Checkpoint,
+    pub(crate) job: JobDescription,
+}
+
+/// Returns the capture jobs saved in the `root` directory, from the oldest.
+/// The checkpoints that can not be read are ignored.
+pub(crate) fn unfinished_jobs(root: &Path) -> Vec<UnfinishedJob> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut jobs: Vec<UnfinishedJob> = entries
+        .filter_map(|entry| {
+            let dir = entry.ok()?.path();
+            let str = fs::read_to_string(dir.join(JOB_FILE)).ok()?;
+            let job: JobDescription = toml::from_str(&str).ok()?;
+            Some(UnfinishedJob {
+                checkpoint: Checkpoint {
+                    dir,
+                    band_lines: job.band_lines,
+                },
+                job,
+            })
+        })
+        .collect();
+    jobs.sort_unstable_by(|a, b| a.checkpoint.dir.cmp(&b.checkpoint.dir));
+    jobs
+}
+
+impl UnfinishedJob {
+    /// Returns a short description of the job, with its progression.
+    pub(crate) fn describe(&self) -> String {
+        let total = (self.job.height as usize).div_ceil(self.job.band_lines.max(1) as usize);
+        format!(
+            "<acc {}.{}> ({}x{}{}), <acc {}/{}> bands rendered",
+            esc(&self.job.filename),
+            esc(&self.job.format),
+            self.job.width,
+            self.job.height,
+            if self.job.supersampling > 1 {
+                format!(", x{} supersampling", self.job.supersampling)
+            } else {
+                String::new()
+            },
+            self.checkpoint.saved_bands(),
+            total
+        )
+    }
+
+    /// Recreates the capture job, with the render settings of the job applied
+    /// to a copy of the current ones, so that the GPU can be reused.
+    pub(crate) fn restore(self, state: &AppState) -> Result<WaitingScreenshot, String> {
+        let job = self.job;
+        let mut copy = AppState {
+            render_settings: state.render_settings.clone(),
+            ..Default::default()
+        };
+        if job.gpu && copy.render_settings.wgpu_state.device.is_none() {
+            return Err(format!(
+                "<acc {}> was rendered with the GPU, enable it with <command gpu> to resume it.",
+                esc(&job.filename)
+            ));
+        }
+        // The fractal shader is loaded when the settings are applied in GPU mode.
+        copy.render_settings.wgpu_state.use_gpu = job.gpu;
+        copy.apply_settings(job.state)?;
+
+        let mut rs = copy.render_settings;
+        rs.perturbation = job.perturbation;
+        rs.subdivision.captures = job.subdivision;
+        rs.refinement = job.refinement;
+        rs.image_format = ImageFormat::from_extension(&job.format)
+            .ok_or("Invalid capture format in the checkpoint.")?;
+        rs.cell_size = Float::parse(&job.cell_size)
+            .map_err(|err| format!("Invalid cell size in the checkpoint: {}", esc(err)))?
+            .complete(rs.prec);
+        // With the size of the capture as the canvas size, the cell size of the capture is kept.
+        rs.canvas_size = CanvasCoords::new(job.width, job.height);
+
+        Ok(WaitingScreenshot {
+            size: Vec2::new(job.width, job.height),
+            rs,
+            name: Some(job.filename),
+            supersampling: Supersampling {
+                factor: job.supersampling,
+                jitter: job.jitter,
+                seed: job.jitter_seed,
+            },
+            checkpoint: Some(self.checkpoint),
+        })
+    }
+}
+
+impl AppState {
+    /// Saves a checkpoint for each queued capture job which has none yet,
+    /// so that they can be resumed on the next launch.
+    pub(crate) fn save_requested_jobs(&mut self) {
+        let mut saved = 0;
+        for capture in std::mem::take(&mut self.requested_jobs) {
+            if capture.checkpoint.is_some() {
+                saved += 1;
+                continue;
+            }
+            match Checkpoint::create(&self.checkpoints_dir, &capture, &capture.filename()) {
+                Ok(_) => saved += 1,
+                Err(err) => self.log_error(err),
+            }
+        }
+        if saved > 0 {
+            self.log_info(format!(
+                "<acc {saved}> queued capture job(s) saved, use <command resume> to continue them on the next launch."
+            ));
+        }
+    }
+}
@@ -5,7 +5,7 @@ use ratatui::{
 
 use crate::{
     app::App,
-    components::{canvas::Canvas, Input, LogPanel},
+    components::{canvas::Canvas, Input, JobPanel, LogPanel},
     helpers::Focus,
 };
 
@@ -16,6 +16,7 @@ impl App {
             Focus::Canvas => Canvas::handle_key_code(self, key.code),
             Focus::Input => Input::handle_event(&mut self.app_state, key),
             Focus::LogPanel => LogPanel::handle_event(&mut self.app_state, key.code),
+            Focus::JobPanel => JobPanel::handle_event(self, key.code),
         }
     }
 
@@ -32,13 +33,17 @@ impl App {
                 Focus::Canvas => Canvas::handle_mouse_event(self, event),
                 Focus::Input => Input::handle_mouse_event(&mut self.app_state, event),
                 Focus::LogPanel => LogPanel::handle_mouse_event(&mut self.app_state, event),
+                Focus::JobPanel => JobPanel::handle_mouse_event(&mut self.app_state, event),
             }
         }
     }
 
     /// Return the component at the given position as a Focus variant
     pub(crate) fn get_component_at_pos(&self, pos: Position) -> Option<Focus> {
-        Some(if self.chunks.canvas.contains(pos) {
+        // The job panel is displayed over the canvas.
+        Some(if self.chunks.job_panel.contains(pos) {
+            Focus::JobPanel
+        } else if self.chunks.canvas.contains(pos) {
             Focus::Canvas
         } else if self.chunks.log_panel.contains(pos) {
             Focus::LogPanel
@@ -56,12 +61,13 @@ impl App {
                 self.app_state.quit = true
             }
             KeyCode::Tab => {
-                if !self.hide_sidepanel {
-                    self.app_state.focused = match self.app_state.focused {
-                        Focus::Input => Focus::Canvas,
-                        Focus::Canvas => Focus::LogPanel,
-                        Focus::LogPanel => Focus::Input,
-                    }
+                let job_panel = self.app_state.show_job_panel;
+                self.app_state.focused = match self.app_state.focused {
+                    Focus::Input => Focus::Canvas,
+                    Focus::Canvas if job_panel => Focus::JobPanel,
+                    Focus::Canvas | Focus::JobPanel if !self.hide_sidepanel => Focus::LogPanel,
+                    Focus::LogPanel => Focus::Input,
+                    _ => Focus::Canvas,
                 }
             }
             _ => {
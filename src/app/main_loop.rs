@@ -10,7 +10,12 @@ use std::{
 };
 
 use crate::{
-    app::SlaveMessage, commands::gpu::execute_gpu, frac_logic::CanvasCoords, helpers::Chunks, App,
+    app::{checkpoint::unfinished_jobs, SlaveMessage},
+    app_state::config::default_config_path,
+    commands::gpu::execute_gpu,
+    frac_logic::CanvasCoords,
+    helpers::Chunks,
+    App,
 };
 
 /// The delay listening for key events before each terminal redraw.
@@ -25,12 +30,24 @@ const MIN_SCREEN_SIZE: u16 = 10;
 
 impl App {
     /// Run the main application loop, perform rendering and event passing
+    // These lints were added to clippy after the loop was written.
+    #[allow(clippy::obfuscated_if_else, clippy::collapsible_match)]
     pub fn run(&mut self, term: &mut DefaultTerminal) -> io::Result<()> {
         self.app_state.initial_message();
+        if let Some(path) = default_config_path() {
+            let res = self.app_state.load_config(path);
+            self.app_state.handle_res(res);
+        }
         if let Err(err) = execute_gpu(&mut self.app_state, Default::default()) {
             self.app_state.log_error(err);
             self.app_state.cpu_defaults();
         }
+        let unfinished = unfinished_jobs(&self.app_state.checkpoints_dir).len();
+        if unfinished > 0 {
+            self.app_state.log_info(format!(
+                "<acc {unfinished}> unfinished capture job(s) found, use <command resume> to continue them."
+            ));
+        }
         while !self.app_state.quit {
             let start = Instant::now();
 
@@ -62,7 +79,10 @@ impl App {
                     self.app_state.render_settings.reset_cell_size();
                 }
 
-                if self.app_state.redraw_canvas || self.app_state.repaint_canvas {
+                if self.app_state.redraw_canvas
+                    || self.app_state.repaint_canvas
+                    || self.canvas_render.is_some()
+                {
                     self.render_canvas();
                 }
 
@@ -79,17 +99,15 @@ impl App {
             if self.app_state.remove_jobs {
                 self.app_state.remove_jobs = false;
                 self.app_state.requested_jobs = Vec::new();
-                while !self.parallel_jobs.is_empty() {
-                    let job = self.parallel_jobs.remove(0);
-                    // If the job thread is still running, it should
-                    // exit by itself when detecting that the message pipe is closed.
-                    self.app_state.prioritized_log_messages.remove(&job.id);
+                while !self.app_state.parallel_jobs.is_empty() {
+                    self.app_state.cancel_running_job(0);
                 }
             }
 
             // 2 - Cycle through all the running jobs, non-blockingly handle messages
             // and remove finished ones.
-            self.parallel_jobs.retain_mut(|job| {
+            let mut parallel_jobs = std::mem::take(&mut self.app_state.parallel_jobs);
+            parallel_jobs.retain_mut(|job| {
                 if job.finished {
                     // remove the priorotized progression message when the screenshot if
                     // finished
@@ -108,8 +126,8 @@ impl App {
                                 format!(
                                     "Screenshot progression:\nline {}/{} (<command {:?}%>)",
                                     job.rendered_lines,
-                                    job.size.y,
-                                    job.rendered_lines * 100 / job.size.y
+                                    job.total_lines(),
+                                    job.rendered_lines * 100 / job.total_lines()
                                 ),
                             );
                         }
@@ -136,14 +154,29 @@ impl App {
                 }
                 true
             });
+            self.app_state.parallel_jobs = parallel_jobs;
+            self.app_state.remove_stopped_jobs();
 
-            // 3 - Start a new job if possible
-            if !self.app_state.pause_jobs
-                && self.parallel_jobs.is_empty()
+            // 3 - Start new jobs if possible
+            while !self.app_state.pause_jobs
+                && self.app_state.parallel_jobs.len() < self.app_state.max_jobs
                 && !self.app_state.requested_jobs.is_empty()
             {
-                self.parallel_jobs
-                    .push(self.app_state.requested_jobs.remove(0).start());
+                let mut job = self
+                    .app_state
+                    .requested_jobs
+                    .remove(0)
+                    .start(&self.app_state.checkpoints_dir);
+                // The jobs started together need distinct ids for their progression messages.
+                while self
+                    .app_state
+                    .parallel_jobs
+                    .iter()
+                    .any(|other| other.id == job.id)
+                {
+                    job.id += 1;
+                }
+                self.app_state.parallel_jobs.push(job);
             }
 
             // 4 - Report waiting jobs
@@ -192,6 +225,7 @@ impl App {
             }
         }
 
+        self.app_state.save_requested_jobs();
         Ok(())
     }
 }
@@ -3,18 +3,25 @@
 use ratatui::style::Color;
 use std::collections::HashMap;
 
+mod canvas_render;
+mod capture_estimate;
+pub(crate) mod checkpoint;
 mod events;
 mod main_loop;
 mod parallel_jobs;
 mod render_app;
 mod render_canvas;
 
-pub(crate) use parallel_jobs::{ScreenshotMaster, SlaveMessage, WaitingScreenshot};
+use canvas_render::CanvasRender;
+pub(crate) use capture_estimate::CaptureLimits;
+pub(crate) use parallel_jobs::{
+    FinishedJob, ScreenshotMaster, SlaveMessage, StoppingJob, Supersampling, WaitingScreenshot,
+};
 pub(crate) type CanvasPoints = HashMap<Color, Vec<(f64, f64)>>;
 
 use crate::{
-    frac_logic::DivergMatrix,
-    helpers::{Chunks, Focus},
+    frac_logic::{DivergMatrix, Histogram},
+    helpers::{Chunks, Focus, Vec2},
     AppState,
 };
 
@@ -26,7 +33,13 @@ pub struct App {
     pub(crate) chunks: Chunks,
     pub(crate) app_state: AppState,
     pub(crate) diverg_matrix: DivergMatrix,
-    pub(crate) parallel_jobs: Vec<ScreenshotMaster>,
+    /// The move of the canvas in cells since the divergence matrix was rendered,
+    /// if it is a complete render which only moved by whole cells since then.
+    pub(crate) diverg_matrix_shift: Option<Vec2<i32>>,
+    /// The histogram rendered instead of the divergence matrix in density mode.
+    pub(crate) density_histogram: Option<Histogram>,
+    /// The canvas render running in the background, if the canvas is being redrawn.
+    pub(crate) canvas_render: Option<CanvasRender>,
     /// Whether or not to render the command system sidebar on the screen.
     pub(crate) hide_sidepanel: bool,
 }
@@ -1,36 +1,211 @@
 use std::{
-    sync::mpsc::{self, Receiver, Sender},
+    fmt,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    ops::Range,
+    path::Path,
+    str::FromStr,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        OnceLock,
+    },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 use chrono::{Local, Utc};
 use futures::executor::block_on;
-use image::ImageBuffer;
+use image::{ImageBuffer, ImageFormat, RgbImage};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 use ratatui::style::Color;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use rug::Complex;
+use tiff::encoder::{colortype, TiffEncoder, TiffKind};
 
+use super::{capture_estimate::band_lines, checkpoint::Checkpoint};
 use crate::{
     commands::save::SAVE_EXTENSION,
-    frac_logic::{DivergMatrix, RenderSettings},
-    helpers::{markup::esc, Vec2},
+    frac_logic::{DivergMatrix, Histogram, RenderSettings},
+    helpers::{markup::esc, SavedState, Vec2},
     AppState,
 };
 
+/// The maximum supersampling factor, which renders 16 samples per pixel.
+pub(crate) const MAX_SUPERSAMPLING: i32 = 4;
+
+/// The supersampling of a capture: each pixel is the average color of `factor`x`factor`
+/// samples, which are evenly spaced in the pixel, or randomly placed in their part of it
+/// if `jitter` is true.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Supersampling {
+    pub(crate) factor: i32,
+    pub(crate) jitter: bool,
+    /// The seed of the random offsets of the jittered samples, drawn when the capture is
+    /// requested, so that all the bands of a capture use the same offsets, even if resumed.
+    pub(crate) seed: u32,
+}
+
+impl Default for Supersampling {
+    fn default() -> Self {
+        Self {
+            factor: 1,
+            jitter: false,
+            seed: 0,
+        }
+    }
+}
+
+impl Supersampling {
+    /// Returns the offsets of the samples from the center of their pixel, in pixels.
+    /// The jittered offsets are the same for all the pixels of a capture.
+    fn offsets(&self) -> Vec<(f64, f64)> {
+        let mut rng = StdRng::seed_from_u64(self.seed as u64);
+        let factor = self.factor as f64;
+        let mut offset = |i: i32| {
+            let position = if self.jitter { rng.gen::<f64>() } else { 0.5 };
+            (i as f64 + position) / factor - 0.5
+        };
+        (0..self.factor)
+            .flat_map(|y| (0..self.factor).map(move |x| (x, y)))
+            .map(|(x, y)| (offset(x), offset(y)))
+            .collect()
+    }
+}
+
+/// Parses a supersampling factor, followed by `j` if the samples are jittered, such as `3j`.
+impl FromStr for Supersampling {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (factor, jitter) = match s.strip_suffix('j') {
+            Some(factor) => (factor, true),
+            None => (s, false),
+        };
+        let factor = factor
+            .parse()
+            .map_err(|err| format!("Invalid supersampling factor: {}", esc(err)))?;
+        if !(1..=MAX_SUPERSAMPLING).contains(&factor) {
+            return Err(format!(
+                "The supersampling factor must be between 1 and {MAX_SUPERSAMPLING}."
+            ));
+        }
+        Ok(Self {
+            factor,
+            jitter,
+            seed: if jitter { thread_rng().gen() } else { 0 },
+        })
+    }
+}
+
+impl fmt::Display for Supersampling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.factor, self.factor)?;
+        if self.jitter {
+            write!(f, " jittered")?;
+        }
+        Ok(())
+    }
+}
+
 pub(crate) struct WaitingScreenshot {
     pub(crate) size: Vec2<i32>,
     pub(crate) rs: RenderSettings,
     pub(crate) name: Option<String>,
+    pub(crate) supersampling: Supersampling,
+    /// The checkpoint of the job, if it was saved before.
+    pub(crate) checkpoint: Option<Checkpoint>,
 }
 
 impl WaitingScreenshot {
-    pub(crate) fn start(self) -> ScreenshotMaster {
+    /// Returns true if the capture is rendered in bands written to the image file one by one,
+    /// which is possible for the divergence renders saved in PNG or TIFF.
+    pub(crate) fn streamed(&self) -> bool {
+        !self.rs.use_density()
+            && matches!(self.rs.image_format, ImageFormat::Png | ImageFormat::Tiff)
+    }
+
+    /// Returns the filename of the capture and its state file, without the extension.
+    pub(crate) fn filename(&self) -> String {
+        self.name.clone().unwrap_or(format!(
+            "{} {}",
+            self.rs.get_frac_obj().name,
+            Local::now().format("%F %H-%M-%S%.f")
+        ))
+    }
+
+    /// Starts the job, and saves its checkpoint in `checkpoints_dir` if it has none yet.
+    pub(crate) fn start(self, checkpoints_dir: &Path) -> ScreenshotMaster {
         let (tx, rx) = mpsc::channel();
 
-        let screenshot = ScreenshotSlave::new(self.size.clone(), tx, self.rs.clone());
+        let filename = self.filename();
+        let stream_to = self
+            .streamed()
+            .then(|| capture_filename(&filename, &self.rs));
+        let checkpoint = match &self.checkpoint {
+            Some(checkpoint) => Some(checkpoint.clone()),
+            None => Checkpoint::create(checkpoints_dir, &self, &filename)
+                .map_err(|err| {
+                    // The message is displayed by the main process once the job started.
+                    let _ = tx.send(SlaveMessage::Warning(format!(
+                        "{err}\nThis capture can not be resumed if it is interrupted."
+                    )));
+                })
+                .ok(),
+        };
+
+        let screenshot = ScreenshotSlave::new(
+            self.size.clone(),
+            tx,
+            self.rs.clone(),
+            self.supersampling,
+            stream_to,
+            checkpoint.clone(),
+        );
         let handle = ScreenshotSlave::start(screenshot);
-        ScreenshotMaster::new(self.size.clone(), rx, handle, self.rs, self.name)
+        ScreenshotMaster::new(
+            self.size.clone(),
+            rx,
+            handle,
+            self.rs,
+            filename,
+            self.supersampling,
+            checkpoint,
+        )
     }
 }
 
+/// Returns the thread pool of the CPU renders of the captures. It is separate from the global
+/// pool used by the canvas renders, which would otherwise wait for the captures to finish.
+/// Like the global pool, it has one thread per processor core: the two pools share the
+/// processor while a capture runs, and the jobs running at once share this pool.
+fn capture_pool() -> &'static ThreadPool {
+    static CAPTURE_POOL: OnceLock<ThreadPool> = OnceLock::new();
+    CAPTURE_POOL.get_or_init(|| {
+        ThreadPoolBuilder::new()
+            .thread_name(|index| format!("rsfrac-capture-{index}"))
+            .build()
+            .expect("Could not create the capture thread pool")
+    })
+}
+
+/// Returns the filename of the image of a capture, with the extension of the selected format.
+fn capture_filename(filename: &str, rs: &RenderSettings) -> String {
+    format!("{}.{}", filename, rs.image_format.extensions_str()[0])
+}
+
+fn encoding_error(err: impl fmt::Display) -> String {
+    format!("Could not encode the capture: {}", esc(err))
+}
+
+/// The result of a screenshot job, which depends on the rendering model.
+pub(crate) enum ScreenshotResult {
+    /// The image of a divergence render, in the formats that can not be streamed.
+    Image(RgbImage),
+    /// The result of a Buddhabrot or Nebulabrot render.
+    Density(Histogram),
+    /// The capture was written to its file while it was rendered.
+    Streamed,
+}
+
 pub(crate) struct ScreenshotMaster {
     /// We want to know the size of the screenshot in order
     /// to compute the current progression percentage.
@@ -41,11 +216,31 @@ pub(crate) struct ScreenshotMaster {
     /// Used to keep track of the progression and display
     /// a percentage in the main process.
     pub(crate) rendered_lines: i32,
-    pub(crate) handle: Option<JoinHandle<Result<DivergMatrix, String>>>,
+    pub(crate) handle: Option<JoinHandle<Result<ScreenshotResult, String>>>,
     pub(crate) id: i64,
     pub(crate) rs_copy: RenderSettings,
     pub(crate) finished: bool,
-    pub(crate) name: Option<String>,
+    /// The filename of the capture and its state file, without the extension.
+    pub(crate) filename: String,
+    pub(crate) supersampling: Supersampling,
+    pub(crate) checkpoint: Option<Checkpoint>,
+    pub(crate) started: Instant,
+}
+
+/// A capture job which is not running anymore, kept to be displayed in the job panel.
+pub(crate) struct FinishedJob {
+    pub(crate) filename: String,
+    pub(crate) size: Vec2<i32>,
+    pub(crate) frac_name: &'static str,
+    pub(crate) duration: Duration,
+    /// The reason why the job failed or was cancelled, if it did not complete.
+    pub(crate) error: Option<String>,
+}
+
+/// A cancelled job whose thread did not exit yet, and may still save a band to its checkpoint.
+pub(crate) struct StoppingJob {
+    pub(crate) checkpoint: Option<Checkpoint>,
+    pub(crate) handle: JoinHandle<Result<ScreenshotResult, String>>,
 }
 
 /// Represents a message sent from a child process
@@ -67,9 +262,11 @@ impl ScreenshotMaster {
     pub(crate) fn new(
         size: Vec2<i32>,
         receiver: Receiver<SlaveMessage>,
-        handle: JoinHandle<Result<DivergMatrix, String>>,
+        handle: JoinHandle<Result<ScreenshotResult, String>>,
         rs: RenderSettings,
-        name: Option<String>,
+        filename: String,
+        supersampling: Supersampling,
+        checkpoint: Option<Checkpoint>,
     ) -> Self {
         Self {
             finished: false,
@@ -79,42 +276,66 @@ impl ScreenshotMaster {
             handle: Some(handle),
             id: Utc::now().timestamp_micros(),
             rs_copy: rs,
-            name,
+            filename,
+            supersampling,
+            checkpoint,
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns the estimated time left before the end of the render,
+    /// once a part of the capture was rendered.
+    pub(crate) fn eta(&self) -> Option<Duration> {
+        (self.rendered_lines > 0).then(|| {
+            self.started.elapsed() * (self.total_lines() - self.rendered_lines).max(0) as u32
+                / self.rendered_lines as u32
+        })
+    }
+
+    /// Returns the summary of the job once it is not running anymore.
+    pub(crate) fn summary(&self, error: Option<String>) -> FinishedJob {
+        FinishedJob {
+            filename: self.filename.clone(),
+            size: self.size.clone(),
+            frac_name: self.rs_copy.get_frac_obj().name,
+            duration: self.started.elapsed(),
+            error,
+        }
+    }
+
+    /// Returns the number of lines to render, to compute the progression:
+    /// the lines of the capture are rendered once per sample of its pixels.
+    pub(crate) fn total_lines(&self) -> i32 {
+        if self.rs_copy.use_density() {
+            self.size.y
+        } else {
+            self.size.y * self.supersampling.factor.pow(2)
         }
     }
     /// Handles the output of the screenshot child process:
-    /// Save the render to a png file, and print a log message.
-    pub(crate) fn finished(&self, state: &mut AppState, result: Result<DivergMatrix, String>) {
-        match result {
-            Err(err) => state.log_error(format!("Could not finish screenshot, reason: {err}")),
+    /// Save the render to an image file, and print a log message.
+    pub(crate) fn finished(&self, state: &mut AppState, result: Result<ScreenshotResult, String>) {
+        let error = match result {
+            Err(err) => {
+                state.log_error(format!("Could not finish screenshot, reason: {err}"));
+                if self.checkpoint.is_some() {
+                    state.log_info(
+                        "The rendered part of the capture was saved, use <command resume> to continue it.",
+                    );
+                }
+                Some(err)
+            }
             Ok(result) => {
-                let height = self.size.y as usize;
-                let rs_copy = &self.rs_copy;
-                let buf =
-                    ImageBuffer::from_par_fn(self.size.x as u32, self.size.y as u32, |x, y| {
-                        let div = &result[height - y as usize - 1][x as usize];
-                        let color = rs_copy.color_from_div(div);
-                        if let Color::Rgb(r, g, b) = color {
-                            image::Rgb([r, g, b])
-                        } else {
-                            image::Rgb([0, 0, 0])
-                        }
-                    });
-
-                let filename_base = self.name.clone().unwrap_or(format!(
-                    "{} {}",
-                    self.rs_copy.get_frac_obj().name,
-                    Local::now().format("%F %H-%M-%S%.f")
-                ));
-
-                let filename_save = format!("{}{}", filename_base, SAVE_EXTENSION);
-                let filename_cap = format!(
-                    "{}.{}",
-                    filename_base,
-                    self.rs_copy.image_format.extensions_str()[0]
-                );
-
-                match self.rs_copy.save(&filename_save) {
+                let filename_save = format!("{}{}", self.filename, SAVE_EXTENSION);
+                let filename_cap = capture_filename(&self.filename, &self.rs_copy);
+                let supersampled = !self.rs_copy.use_density() && self.supersampling.factor > 1;
+
+                let mut saved = SavedState::from(&self.rs_copy);
+                if supersampled {
+                    saved.supersampling = Some(self.supersampling.factor);
+                    saved.supersampling_jitter = Some(self.supersampling.jitter);
+                }
+                match saved.write(&filename_save) {
                     Err(err) => state.log_error(err),
                     Ok(_) => state.log_info(format!(
                         "State file containing capture parameters saved to <acc {}> in case of failure.",
@@ -122,18 +343,47 @@ impl ScreenshotMaster {
                     )),
                 }
 
-                if let Err(err) = buf.save_with_format(&filename_cap, self.rs_copy.image_format) {
-                    state.log_error(format!("Could not save screenshot: {}", esc(err)));
+                let format = self.rs_copy.image_format;
+                let saved_capture = match result {
+                    ScreenshotResult::Streamed => Ok(()),
+                    ScreenshotResult::Image(image) => image.save_with_format(&filename_cap, format),
+                    ScreenshotResult::Density(histogram) => {
+                        let height = self.size.y;
+                        ImageBuffer::from_par_fn(self.size.x as u32, height as u32, |x, y| {
+                            let y = height - y as i32 - 1;
+                            match histogram.color(x as i32, y) {
+                                Color::Rgb(r, g, b) => image::Rgb([r, g, b]),
+                                _ => image::Rgb([0, 0, 0]),
+                            }
+                        })
+                        .save_with_format(&filename_cap, format)
+                    }
+                };
+                if let Err(err) = saved_capture {
+                    let err = format!("Could not save screenshot: {}", esc(err));
+                    state.log_error(err.clone());
+                    Some(err)
                 } else {
                     state.log_success(format!(
-                        "Screenshot ({}x{}) saved to <acc {}>",
+                        "Screenshot ({}x{}{}) saved to <acc {}>",
                         self.size.x,
                         self.size.y,
+                        if supersampled {
+                            format!(", {} supersampling", self.supersampling)
+                        } else {
+                            String::new()
+                        },
                         esc(filename_cap)
                     ));
+                    // The checkpoint is not needed anymore once the capture is saved.
+                    if let Some(Err(err)) = self.checkpoint.as_ref().map(Checkpoint::remove) {
+                        state.log_warn(err);
+                    }
+                    None
                 }
             }
-        }
+        };
+        state.record_finished_job(self.summary(error));
     }
 }
 
@@ -146,37 +396,435 @@ pub(crate) struct ScreenshotSlave {
     sender: Sender<SlaveMessage>,
     /// Copy of the render settings at the moment of the request
     rs_copy: RenderSettings,
+    supersampling: Supersampling,
+    /// The image file the capture is written to while it is rendered, if it is streamed.
+    stream_to: Option<String>,
+    /// The checkpoint where the rendered bands are saved, and read from when resuming.
+    checkpoint: Option<Checkpoint>,
 }
 
 impl ScreenshotSlave {
-    pub(crate) fn new(size: Vec2<i32>, sender: Sender<SlaveMessage>, rs: RenderSettings) -> Self {
+    pub(crate) fn new(
+        size: Vec2<i32>,
+        sender: Sender<SlaveMessage>,
+        rs: RenderSettings,
+        supersampling: Supersampling,
+        stream_to: Option<String>,
+        checkpoint: Option<Checkpoint>,
+    ) -> Self {
         Self {
             size,
             sender,
             rs_copy: rs,
+            supersampling,
+            stream_to,
+            checkpoint,
         }
     }
 }
 impl ScreenshotSlave {
-    /// Creates a new process, running the screenshot rendering.
-    pub(crate) fn start(mut screenshot: Self) -> JoinHandle<Result<DivergMatrix, String>> {
+    /// Creates a new process driving the screenshot rendering, whose CPU renders are
+    /// computed in the capture thread pool. The process itself is not a thread of the pool,
+    /// so that it does not keep a thread busy while it waits for the GPU.
+    pub(crate) fn start(mut screenshot: Self) -> JoinHandle<Result<ScreenshotResult, String>> {
         thread::spawn(move || block_on(screenshot.run()))
     }
-    pub(crate) async fn run(&mut self) -> Result<DivergMatrix, String> {
-        if self.rs_copy.wgpu_state.use_gpu {
-            self.rs_copy.initialize_gpu(Some(&self.sender)).await?;
-            let result = self
-                .rs_copy
-                .get_gpu_diverg_matrix_async(&self.size, Some(&self.sender))
-                .await;
+    pub(crate) async fn run(&mut self) -> Result<ScreenshotResult, String> {
+        // The density renders are always computed with the CPU.
+        if self.rs_copy.use_density() {
+            let histogram = capture_pool().install(|| {
+                self.rs_copy.get_density_histogram(
+                    &self.size,
+                    self.rs_copy.buddhabrot.samples_per_pixel,
+                    Some(&self.sender),
+                )
+            })?;
+            self.sender
+                .send(SlaveMessage::JobFinished)
+                .map_err(|err| format!("Could not open message channel: {err}"))?;
+            Ok(ScreenshotResult::Density(histogram))
+        } else {
+            if self.rs_copy.wgpu_state.use_gpu {
+                self.rs_copy.initialize_gpu(Some(&self.sender)).await?;
+            }
+            let result = if let Some(filename) = self.stream_to.clone() {
+                let result = self.stream(&filename).await;
+                // Do not leave an incomplete image behind.
+                if result.is_err() {
+                    let _ = fs::remove_file(&filename);
+                }
+                result.map(|_| ScreenshotResult::Streamed)
+            } else {
+                self.render_image().await.map(ScreenshotResult::Image)
+            };
+            // Indicate that the screenshot finished, and that the parent process
+            // should now wait for the result transfer through the `JoinHandle`.
             self.sender
                 .send(SlaveMessage::JobFinished)
                 .map_err(|err| format!("Could not open message channel: {err}"))?;
             result
+        }
+    }
+
+    /// Renders the given rows of the divergence matrix of the capture, with the CPU or the GPU.
+    async fn render_matrix(&mut self, rows: Range<i32>) -> Result<DivergMatrix, String> {
+        if self.rs_copy.wgpu_state.use_gpu {
+            self.rs_copy
+                .get_gpu_diverg_rows_async(&self.size, rows, Some(&self.sender))
+                .await
         } else {
-            Ok(self
-                .rs_copy
-                .get_diverg_matrix_with_status(&self.size, &self.sender))
+            Ok(capture_pool().install(|| {
+                self.rs_copy
+                    .get_diverg_matrix_with_status(&self.size, rows, &self.sender)
+            }))
+        }
+    }
+
+    /// Renders the given rows of the divergence matrix of the capture once per sample of their
+    /// pixels, with the position moved by the offset of the sample, and returns the average
+    /// colors of the samples, row by row. The rows have the colors they have in the whole
+    /// capture, so that the capture can be rendered in bands.
+    async fn render_colors(&mut self, rows: Range<i32>) -> Result<Vec<[u8; 3]>, String> {
+        let (width, height) = (self.size.x as usize, rows.len());
+        let cell_size = self.rs_copy.cell_size_from_height(self.size.y);
+        let pos = self.rs_copy.pos.clone();
+        let offsets = self.supersampling.offsets();
+        // The sums of the channels of the colored samples, and their number.
+        let mut sums = vec![[0u32; 4]; width * height];
+
+        for (x, y) in &offsets {
+            let offset = Complex::with_val(self.rs_copy.prec, (*x * &cell_size, *y * &cell_size));
+            self.rs_copy.pos = Complex::with_val(self.rs_copy.prec, &pos + offset);
+            let matrix = self.render_matrix(rows.clone()).await;
+            self.rs_copy.pos = pos.clone();
+            let matrix = matrix?;
+            // The lines are missing if the job was cancelled.
+            if matrix.len() < height || matrix.iter().any(|row| row.len() < width) {
+                return Err("The capture was cancelled.".to_string());
+            }
+
+            let rs = &self.rs_copy;
+            capture_pool().install(|| {
+                sums.par_chunks_mut(width)
+                    .zip(matrix.par_iter())
+                    .for_each(|(sums, row)| {
+                        for (sum, diverg) in sums.iter_mut().zip(row) {
+                            if let Color::Rgb(r, g, b) = rs.color_from_div(diverg) {
+                                sum[0] += r as u32;
+                                sum[1] += g as u32;
+                                sum[2] += b as u32;
+                                sum[3] += 1;
+                            }
+                        }
+                    })
+            });
+        }
+
+        // The transparent samples are left out of the average, and the pixels
+        // without any colored sample are black, as without supersampling.
+        Ok(sums
+            .into_iter()
+            .map(|[r, g, b, count]| {
+                let count = count.max(1);
+                [r, g, b].map(|channel| ((channel + count / 2) / count) as u8)
+            })
+            .collect())
+    }
+
+    /// Returns the number of lines of the bands, which are those of the checkpoint if the
+    /// job is resumed.
+    fn band_lines(&self) -> i32 {
+        match &self.checkpoint {
+            Some(checkpoint) => checkpoint.band_lines,
+            None => band_lines(self.size.x, self.size.y),
         }
     }
+
+    /// Renders the capture in bands, from the top, and passes the bytes of the lines of each
+    /// band to `write` as soon as it is rendered, so that the whole image is never in memory.
+    /// The bands are saved to the checkpoint, and the bands already saved are not rendered again.
+    async fn render_bands(
+        &mut self,
+        mut write: impl FnMut(&[u8]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let (width, height) = (self.size.x, self.size.y);
+        let band = self.band_lines();
+        for (index, top) in (0..height).step_by(band as usize).enumerate() {
+            let lines = band.min(height - top);
+            let saved = self
+                .checkpoint
+                .as_ref()
+                .and_then(|checkpoint| checkpoint.band(index, (width * lines * 3) as usize));
+
+            let bytes = match saved {
+                Some(bytes) => {
+                    // Count the lines of the saved band in the progression.
+                    for _ in 0..lines * self.supersampling.factor.pow(2) {
+                        self.sender
+                            .send(SlaveMessage::LineRender)
+                            .map_err(|_| "The capture was cancelled.".to_string())?;
+                    }
+                    bytes
+                }
+                None => {
+                    // The divergence matrix starts from the bottom of the capture.
+                    let first = height - top - lines;
+                    let colors = self.render_colors(first..first + lines).await?;
+                    let bytes: Vec<u8> = colors
+                        .chunks(width as usize)
+                        .rev()
+                        .flat_map(|line| line.iter().flatten().copied())
+                        .collect();
+                    if let Some(Err(err)) = self
+                        .checkpoint
+                        .as_ref()
+                        .map(|checkpoint| checkpoint.save_band(index, &bytes))
+                    {
+                        // Keep rendering the capture, which will not be resumable.
+                        let _ = self.sender.send(SlaveMessage::Warning(err));
+                        self.checkpoint = None;
+                    }
+                    bytes
+                }
+            };
+            write(&bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the capture in memory, for the formats that can not be streamed.
+    async fn render_image(&mut self) -> Result<RgbImage, String> {
+        let mut bytes = Vec::with_capacity(self.size.x as usize * self.size.y as usize * 3);
+        self.render_bands(|band| {
+            bytes.extend_from_slice(band);
+            Ok(())
+        })
+        .await?;
+        RgbImage::from_raw(self.size.x as u32, self.size.y as u32, bytes)
+            .ok_or("The capture is incomplete.".to_string())
+    }
+
+    /// Renders the capture and writes it to the image file while it is rendered.
+    async fn stream(&mut self, filename: &str) -> Result<(), String> {
+        let file = File::create(filename)
+            .map_err(|err| format!("Could not create <command {}>: {}", esc(filename), esc(err)))?;
+        let file = BufWriter::new(file);
+        let (width, height) = (self.size.x as u32, self.size.y as u32);
+
+        if self.rs_copy.image_format == ImageFormat::Tiff {
+            // Standard TIFF files are limited to 4 GB.
+            if width as u64 * height as u64 * 3 > u32::MAX as u64 {
+                self.stream_tiff(TiffEncoder::new_big(file).map_err(encoding_error)?)
+                    .await
+            } else {
+                self.stream_tiff(TiffEncoder::new(file).map_err(encoding_error)?)
+                    .await
+            }
+        } else {
+            let mut encoder = png::Encoder::new(file, width, height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder
+                .write_header()
+                .and_then(|writer| writer.into_stream_writer())
+                .map_err(encoding_error)?;
+            self.render_bands(|bytes| writer.write_all(bytes).map_err(encoding_error))
+                .await?;
+            writer.finish().map_err(encoding_error)
+        }
+    }
+
+    /// Writes the capture to a TIFF file, with one strip per band.
+    async fn stream_tiff<K: TiffKind>(
+        &mut self,
+        mut encoder: TiffEncoder<BufWriter<File>, K>,
+    ) -> Result<(), String> {
+        let mut image = encoder
+            .new_image::<colortype::RGB8>(self.size.x as u32, self.size.y as u32)
+            .map_err(encoding_error)?;
+        image
+            .rows_per_strip(self.band_lines() as u32)
+            .map_err(encoding_error)?;
+        self.render_bands(|bytes| image.write_strip(bytes).map_err(encoding_error))
+            .await?;
+        image.finish().map_err(encoding_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::checkpoint::unfinished_jobs,
+        frac_logic::CanvasCoords,
+        helpers::{void_fills, VoidFill},
+    };
+
+    #[test]
+    fn test_supersampling() {
+        let supersampling: Supersampling = "2".parse().unwrap();
+        assert_eq!(supersampling.to_string(), "2x2");
+        assert_eq!(
+            supersampling.offsets(),
+            vec![(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)]
+        );
+        let jittered: Supersampling = "4j".parse().unwrap();
+        assert_eq!(jittered.to_string(), "4x4 jittered");
+        let offsets = jittered.offsets();
+        // The bands of a capture, rendered separately, have the same offsets.
+        assert_eq!(jittered.offsets(), offsets);
+        assert_eq!(offsets.len(), 16);
+        assert!(offsets
+            .iter()
+            .all(|(x, y)| (-0.5..0.5).contains(x) && (-0.5..0.5).contains(y)));
+        assert!("0".parse::<Supersampling>().is_err());
+        assert!("5".parse::<Supersampling>().is_err());
+        assert!("j".parse::<Supersampling>().is_err());
+
+        // Each pass sends the progression of all its lines.
+        let size = Vec2::new(24, 16);
+        let (tx, rx) = mpsc::channel();
+        let mut slave = ScreenshotSlave::new(
+            size.clone(),
+            tx,
+            RenderSettings::default(),
+            supersampling,
+            None,
+            None,
+        );
+        let Ok(ScreenshotResult::Image(image)) = block_on(slave.run()) else {
+            panic!("Expected the image of a supersampled capture");
+        };
+        assert_eq!(image.dimensions(), (24, 16));
+        let lines = rx
+            .try_iter()
+            .filter(|message| matches!(message, SlaveMessage::LineRender))
+            .count();
+        assert_eq!(lines, 16 * 4);
+        // The position is restored after the passes.
+        assert_eq!(slave.rs_copy.pos, RenderSettings::default().pos);
+
+        // The transparent void samples are not averaged in as black, unlike the black ones.
+        slave.rs_copy.canvas_size = CanvasCoords::new(24, 16);
+        slave.rs_copy.reset_cell_size();
+        let transparent = block_on(slave.render_colors(0..16)).unwrap();
+        slave.rs_copy.void_fill_index = void_fills()
+            .iter()
+            .position(|fill| *fill == VoidFill::Black)
+            .unwrap();
+        let black = block_on(slave.render_colors(0..16)).unwrap();
+        let pairs = || transparent.iter().zip(&black);
+        assert!(pairs().all(|(t, b)| t.iter().zip(b).all(|(t, b)| t >= b)));
+        assert!(pairs().any(|(t, b)| t != b));
+    }
+
+    #[test]
+    fn test_streamed_capture() {
+        let size = Vec2::new(30, 21);
+        let (tx, _rx) = mpsc::channel();
+        let mut rs = RenderSettings {
+            canvas_size: CanvasCoords::new(30, 21),
+            ..Default::default()
+        };
+        rs.reset_cell_size();
+        let mut slave =
+            ScreenshotSlave::new(size.clone(), tx, rs, Supersampling::default(), None, None);
+        let colors = block_on(slave.render_colors(0..21)).unwrap();
+
+        // The bands, whatever their height, have the colors of the whole capture.
+        for lines in [1, 4, 21] {
+            let mut bands = Vec::new();
+            for first in (0..21).step_by(lines) {
+                let last = (first + lines).min(21) as i32;
+                bands.extend(block_on(slave.render_colors(first as i32..last)).unwrap());
+            }
+            assert_eq!(bands, colors);
+        }
+
+        // The streamed images are the same as the ones rendered in memory.
+        for format in [ImageFormat::Png, ImageFormat::Tiff] {
+            slave.rs_copy.image_format = format;
+            let filename = std::env::temp_dir().join(format!(
+                "rsfrac_streamed_test.{}",
+                format.extensions_str()[0]
+            ));
+            let filename = filename.to_str().unwrap();
+            block_on(slave.stream(filename)).unwrap();
+            let image = image::open(filename).unwrap().into_rgb8();
+            let _ = fs::remove_file(filename);
+            assert_eq!(image.dimensions(), (30, 21));
+            for (x, y, pixel) in image.enumerate_pixels() {
+                assert_eq!(pixel.0, colors[(20 - y as usize) * 30 + x as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_resumed_capture() {
+        let root = std::env::temp_dir().join(format!("rsfrac_checkpoints_{}", std::process::id()));
+        let mut rs = RenderSettings {
+            canvas_size: CanvasCoords::new(40, 30),
+            ..Default::default()
+        };
+        rs.reset_cell_size();
+        let capture = WaitingScreenshot {
+            size: Vec2::new(30, 21),
+            rs,
+            name: Some("resumed".to_string()),
+            supersampling: Supersampling::default(),
+            checkpoint: None,
+        };
+        let mut checkpoint = Checkpoint::create(&root, &capture, "resumed").unwrap();
+        // Render the capture in 6 bands, which are saved to the checkpoint.
+        checkpoint.band_lines = 4;
+        let new_slave = |checkpoint: &Checkpoint| {
+            let (tx, rx) = mpsc::channel();
+            let slave = ScreenshotSlave::new(
+                capture.size.clone(),
+                tx,
+                capture.rs.clone(),
+                capture.supersampling,
+                None,
+                Some(checkpoint.clone()),
+            );
+            (slave, rx)
+        };
+        let (mut slave, _rx) = new_slave(&checkpoint);
+        let image = block_on(slave.render_image()).unwrap();
+
+        // The saved bands are not rendered again, and count in the progression.
+        fs::remove_file(checkpoint.dir.join("band_00002.rgb")).unwrap();
+        fs::write(checkpoint.dir.join("band_00003.rgb"), [0; 30 * 4 * 3]).unwrap();
+        let (mut slave, rx) = new_slave(&checkpoint);
+        let resumed = block_on(slave.render_image()).unwrap();
+        for (y, (line, resumed_line)) in image.rows().zip(resumed.rows()).enumerate() {
+            if (12..16).contains(&y) {
+                assert!(resumed_line.into_iter().all(|pixel| pixel.0 == [0; 3]));
+            } else {
+                assert!(line.eq(resumed_line));
+            }
+        }
+        assert_eq!(rx.try_iter().count(), 21);
+
+        // The job is restored with the cell size of the capture, whatever the canvas size.
+        let jobs = unfinished_jobs(&root);
+        assert_eq!(jobs.len(), 1);
+        let restored = jobs
+            .into_iter()
+            .next()
+            .unwrap()
+            .restore(&AppState::default())
+            .unwrap();
+        assert_eq!(restored.filename(), "resumed");
+        assert_eq!(restored.checkpoint.as_ref().unwrap().dir, checkpoint.dir);
+        assert_eq!(restored.rs.pos, capture.rs.pos);
+        assert_eq!(
+            restored.rs.cell_size_from_height(21),
+            capture.rs.cell_size_from_height(21)
+        );
+
+        checkpoint.remove().unwrap();
+        assert!(unfinished_jobs(&root).is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
 }
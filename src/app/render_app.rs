@@ -1,7 +1,7 @@
 use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget, Frame};
 
 use crate::{
-    components::{canvas::Canvas, Footer, Input, LogPanel},
+    components::{canvas::Canvas, Footer, Input, JobPanel, LogPanel},
     helpers::Focus,
     App,
 };
@@ -32,6 +32,9 @@ impl Widget for &App {
     fn render(self, _area: Rect, buf: &mut Buffer) {
         let canvas = Canvas::new(&self.app_state, &self.points);
         canvas.render(self.chunks.canvas, buf);
+        if self.app_state.show_job_panel {
+            JobPanel::new(&self.app_state).render(self.chunks.job_panel, buf);
+        }
 
         let footer = Footer::new(&self.app_state);
         footer.render(self.chunks.footer, buf);
@@ -1,62 +1,106 @@
 use std::collections::HashMap;
-use std::time::Instant;
 
-use futures::executor::block_on;
-
-use crate::{app::App, app_state::Stats, helpers::Vec2};
+use crate::{
+    app::{
+        canvas_render::{CanvasRender, CanvasRenderResult},
+        App,
+    },
+    app_state::Stats,
+    helpers::Vec2,
+};
 
 impl App {
-    /// Run the selected fractal algorithm for each canvas coord
+    /// Run the selected fractal algorithm for each canvas coord on a background thread,
+    /// and color the canvas points once the result is available.
     pub(crate) fn render_canvas(&mut self) {
-        // Used to know how long the rendering took.
-        let before = Instant::now();
-
-        // Compute a new divergenve matrix if we are redrawing the canvas.
+        // Start a new render if we are redrawing the canvas. Replacing
+        // the running render cancels it, since it is outdated.
         if self.app_state.redraw_canvas {
-            let size = Vec2::new(
-                self.app_state.render_settings.canvas_size.x,
-                self.app_state.render_settings.canvas_size.y,
-            );
+            self.app_state.redraw_canvas = false;
+            self.diverg_matrix_shift = match (
+                &self.diverg_matrix_shift,
+                self.app_state.canvas_shift.take(),
+            ) {
+                (Some(a), Some(b)) => Some(Vec2::new(a.x + b.x, a.y + b.y)),
+                _ => None,
+            };
+            self.canvas_render = None;
+            self.app_state.canvas_render_progress = None;
+            let reuse = self
+                .diverg_matrix_shift
+                .clone()
+                .map(|shift| (self.diverg_matrix.clone(), shift));
+            match CanvasRender::start(
+                &mut self.app_state.render_settings,
+                reuse,
+                &mut self.app_state.tile_cache,
+            ) {
+                Ok(render) => self.canvas_render = Some(render),
+                Err(err) => self.gpu_render_failed(err),
+            }
+        }
 
-            // Render differently depending on whether or not GPU mode is enabled.
-            if self.app_state.render_settings.wgpu_state.use_gpu {
-                // In GPU mode, try to render with the GPU, and disable GPU mode in case of a
-                // failure.
-                self.diverg_matrix = match block_on(
-                    self.app_state
-                        .render_settings
-                        .get_gpu_diverg_matrix_async(&size, None),
-                ) {
-                    // If the render was ok => use the result
-                    Ok(res) => res,
-                    // If the render failed:
-                    Err(err) => {
-                        // Disable GPU mode.
-                        self.app_state.render_settings.wgpu_state.use_gpu = false;
-                        self.app_state.log_error(format!(
-                            "Disabling GPU mode, because the render failed with error: {err}",
-                        ));
-                        // Run render_canvas again, and ask to redraw.
-                        self.render_canvas();
-                        return;
+        if let Some((result, render_time)) = self
+            .canvas_render
+            .as_mut()
+            .and_then(|render| render.poll(&mut self.app_state))
+        {
+            let render = self.canvas_render.take().unwrap();
+            self.app_state.stats.render_time = render_time;
+            self.app_state.stats.subdivision_fill = None;
+            self.app_state.stats.cache_usage = render.cache_usage;
+            self.density_histogram = None;
+            self.diverg_matrix_shift = None;
+            match result {
+                Ok(CanvasRenderResult::Divergence(diverg_matrix, fill)) => {
+                    if let Some(view) = &render.cache_view {
+                        self.app_state.tile_cache.store(view, &diverg_matrix);
                     }
+                    self.diverg_matrix = diverg_matrix;
+                    self.diverg_matrix_shift = Some(Vec2::default());
+                    self.app_state.stats.subdivision_fill = fill;
+                }
+                Ok(CanvasRenderResult::Density(histogram)) => {
+                    self.diverg_matrix = Vec::new();
+                    self.density_histogram = Some(histogram);
                 }
-            } else {
-                self.diverg_matrix = self.app_state.render_settings.get_diverg_matrix(&size);
+                Err(err) if render.gpu => self.gpu_render_failed(err),
+                Err(err) => self.app_state.log_error(err),
             }
-
-            // Avoid redrawing the canvas again on the next frame.
-            self.app_state.redraw_canvas = false;
+            self.app_state.request_repaint();
+        } else if let Some(preview) = self.canvas_render.as_ref().and_then(CanvasRender::preview) {
+            // Display the coarse previews until the canvas is rendered.
+            self.diverg_matrix = preview;
+            self.diverg_matrix_shift = None;
+            self.density_histogram = None;
+            self.app_state.request_repaint();
         }
 
         if self.app_state.repaint_canvas {
-            // reset the stats
-            self.app_state.stats = Stats::default();
+            // reset the stats, except the ones of the last render
+            self.app_state.stats = Stats {
+                render_time: self.app_state.stats.render_time,
+                subdivision_fill: self.app_state.stats.subdivision_fill,
+                cache_usage: self.app_state.stats.cache_usage,
+                ..Default::default()
+            };
 
             self.points = HashMap::default();
             self.app_state.repaint_canvas = false;
             let mut non_void_points = 0;
 
+            if let Some(histogram) = &self.density_histogram {
+                let size = &self.app_state.render_settings.canvas_size;
+                for y in 0..size.y {
+                    for x in 0..size.x {
+                        self.points
+                            .entry(histogram.color(x, y))
+                            .or_default()
+                            .push((x.into(), y.into()));
+                    }
+                }
+            }
+
             for (y, line) in self.diverg_matrix.iter().enumerate() {
                 let y: i32 = y.try_into().unwrap();
                 for (x, diverg) in line.iter().enumerate() {
@@ -67,24 +111,25 @@ impl App {
                         .entry(color)
                         .or_default()
                         .push((x.into(), y.into()));
-                    if *diverg != -1 {
+                    if !diverg.is_void() {
                         non_void_points += 1;
-                        self.app_state.stats.avg_diverg += *diverg as f64;
+                        self.app_state.stats.avg_diverg += diverg.iter as f64;
                     }
-                    if diverg > &self.app_state.stats.highest_diverg {
-                        self.app_state.stats.highest_diverg = *diverg;
+                    if diverg.iter > self.app_state.stats.highest_diverg {
+                        self.app_state.stats.highest_diverg = diverg.iter;
                     }
                 }
             }
             self.app_state.stats.avg_diverg /= non_void_points as f64;
         }
+    }
 
-        // Render the canvas again if for some reason a
-        // redraw was requested during the first render
-        if self.app_state.redraw_canvas {
-            self.render_canvas();
-        }
-
-        self.app_state.stats.render_time = before.elapsed();
+    /// Disables GPU mode after a failed GPU render, and renders the canvas again with the CPU.
+    fn gpu_render_failed(&mut self, err: String) {
+        self.app_state.render_settings.wgpu_state.use_gpu = false;
+        self.app_state.log_error(format!(
+            "Disabling GPU mode, because the render failed with error: {err}",
+        ));
+        self.app_state.request_redraw();
     }
 }
@@ -8,6 +8,7 @@ pub(crate) enum ClickMode {
     MandelConstant,
     Move,
     BailOut,
+    TrapCenter,
     Info,
 }
 
@@ -20,6 +21,7 @@ impl ClickMode {
             ClickMode::JuliaConstant,
             ClickMode::BailOut,
             ClickMode::MandelConstant,
+            ClickMode::TrapCenter,
             ClickMode::Info,
         ]
     }
@@ -32,6 +34,7 @@ impl ClickMode {
             ("juliaconstant", Self::JuliaConstant),
             ("mandelconstant", Self::MandelConstant),
             ("bailout", Self::BailOut),
+            ("trapcenter", Self::TrapCenter),
             ("info", Self::Info),
         ]
         .iter()
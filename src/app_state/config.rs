@@ -0,0 +1,71 @@
+//! Contains the configuration of the application, which is saved between the sessions.
+
+use std::{env, fs, io::ErrorKind, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::AppState;
+use crate::{commands::max_jobs::MAX_JOBS, helpers::markup::esc};
+
+/// Describes the settings saved to the configuration file.
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Config {
+    pub(crate) max_jobs: Option<usize>,
+}
+
+/// Returns the path of the configuration file in the configuration directory of the user.
+pub(crate) fn default_config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("rsfrac").join("config.toml"))
+}
+
+impl AppState {
+    /// Loads the configuration file if it exists, and keeps its path to save the configuration.
+    pub(crate) fn load_config(&mut self, path: PathBuf) -> Result<(), String> {
+        let loaded = match fs::read_to_string(&path) {
+            // The configuration file is created when a setting is changed.
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            result => result
+                .map_err(esc)
+                .and_then(|str| toml::from_str(&str).map_err(esc))
+                .map_err(|err| {
+                    format!(
+                        "Could not load the configuration file <command {}>: {err}",
+                        esc(path.to_string_lossy())
+                    )
+                }),
+        };
+        self.config_path = Some(path);
+        let config: Config = loaded?;
+
+        if let Some(max_jobs) = config.max_jobs {
+            self.max_jobs = max_jobs.clamp(1, MAX_JOBS);
+        }
+        Ok(())
+    }
+
+    /// Saves the configuration, if it was loaded from a configuration file.
+    pub(crate) fn save_config(&self) -> Result<(), String> {
+        let Some(path) = &self.config_path else {
+            return Ok(());
+        };
+        let config = Config {
+            max_jobs: Some(self.max_jobs),
+        };
+        let str = toml::to_string_pretty(&config)
+            .map_err(|err| format!("Could not save the configuration: {}", esc(err)))?;
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, str))
+            .map_err(|err| {
+                format!(
+                    "Could not write the configuration file <command {}>: {}",
+                    esc(path.to_string_lossy()),
+                    esc(err)
+                )
+            })
+    }
+}
@@ -1,4 +1,7 @@
-use crate::components::canvas::{selectable_variables, SelectedVariable};
+use crate::{
+    app::checkpoint::CHECKPOINTS_DIR,
+    components::canvas::{selectable_variables, SelectedVariable},
+};
 
 use super::AppState;
 
@@ -11,9 +14,11 @@ impl Default for AppState {
     fn default() -> Self {
         Self {
             stats: Default::default(),
+            canvas_render_progress: None,
             redraw_canvas: true,
             remove_jobs: false,
             repaint_canvas: true,
+            canvas_shift: None,
             last_commands: vec![],
             quit: false,
             focused: Default::default(),
@@ -22,6 +27,7 @@ impl Default for AppState {
             prioritized_log_messages: Default::default(),
             log_panel_scroll_state: Default::default(),
             render_settings: Default::default(),
+            tile_cache: Default::default(),
             scaling_factor: DF_SCALING_FACTOR_GPU,
             move_dist: DF_MOVE_DISTANCE_GPU,
             // Basically, since this is an index, we need to get the index of the default
@@ -31,10 +37,19 @@ impl Default for AppState {
                 .position(|x| x.eq(&SelectedVariable::PaletteOffset))
                 .unwrap(),
             requested_jobs: Default::default(),
+            capture_limits: Default::default(),
+            checkpoints_dir: CHECKPOINTS_DIR.into(),
+            parallel_jobs: Default::default(),
+            max_jobs: 1,
+            finished_jobs: Default::default(),
+            stopping_jobs: Default::default(),
+            show_job_panel: false,
+            selected_job: 0,
             click_config: Default::default(),
             pause_jobs: false,
             detected_state_files: Default::default(),
             current_state_file_index: Default::default(),
+            config_path: None,
         }
     }
 }
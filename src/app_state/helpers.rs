@@ -1,11 +1,12 @@
 use crate::{
     components::{
         canvas::{selectable_variables, Canvas, SelectedVariable},
-        Input, LogPanel,
+        Input, JobPanel, LogPanel,
     },
     frac_logic::CanvasCoords,
-    helpers::{Focus, ZoomDirection},
+    helpers::{ColoringMode, Focus, Vec2, ZoomDirection},
 };
+use rug::Complex;
 
 use super::{
     default_app_state::{DF_MOVE_DISTANCE_CPU, DF_SCALING_FACTOR_CPU},
@@ -20,6 +21,13 @@ impl AppState {
     }
     /// Returns true if the currently selected canvas var is hidden
     pub(crate) fn is_selected_var_hidden(&self) -> bool {
+        if self.render_settings.coloring_mode() != ColoringMode::OrbitTrap
+            && (self.is_var_selected(SelectedVariable::TrapSize)
+                || self.is_var_selected(SelectedVariable::TrapAngle))
+        {
+            // If the orbit trap coloring is not used
+            return true;
+        }
         if !self.render_settings.hsl_settings.enabled {
             // If hsl mode is not enabled
             self.is_var_selected(SelectedVariable::HSLSat)
@@ -90,6 +98,7 @@ impl AppState {
     /// Update the divergence matrix and repaint the canvas.
     pub(crate) fn request_redraw(&mut self) {
         self.redraw_canvas = true;
+        self.canvas_shift = None;
         self.request_repaint();
     }
     /// Return the text to display in the footer
@@ -98,6 +107,7 @@ impl AppState {
             Focus::LogPanel => LogPanel::FOOTER_TEXT,
             Focus::Canvas => Canvas::FOOTER_TEXT,
             Focus::Input => Input::FOOTER_TEXT,
+            Focus::JobPanel => JobPanel::FOOTER_TEXT,
         }
     }
     /// Increment positively or negatively the maximum divergence, and ask for canvas redraw
@@ -133,4 +143,18 @@ impl AppState {
             ZoomDirection::Out => self.render_settings.cell_size *= scaling_factor,
         }
     }
+    /// Move the canvas by the given number of cells, and ask for a redraw
+    /// which reuses the points that remain visible.
+    pub(crate) fn pan(&mut self, x: i32, y: i32) {
+        let rs = &mut self.render_settings;
+        rs.pos += Complex::with_val(rs.prec, (x * &rs.cell_size, y * &rs.cell_size));
+        // The points can't be reused if the canvas is also redrawn for another reason.
+        let shift = match (&self.canvas_shift, self.redraw_canvas) {
+            (Some(shift), _) => Some(Vec2::new(shift.x + x, shift.y + y)),
+            (None, true) => None,
+            (None, false) => Some(Vec2::new(x, y)),
+        };
+        self.request_redraw();
+        self.canvas_shift = shift;
+    }
 }
@@ -0,0 +1,238 @@
+//! Contains the control of the capture jobs, used by the job panel and the `jobs` command.
+//! The jobs are indexed in the order of the job panel: the running jobs first,
+//! then the queued jobs and the finished jobs.
+
+use std::path::PathBuf;
+
+use crate::{
+    app::{FinishedJob, StoppingJob, WaitingScreenshot},
+    commands::capture::queue_capture,
+    helpers::{markup::esc, Focus, Vec2},
+};
+
+use super::AppState;
+
+/// The number of finished jobs kept in the job panel.
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// A job of the job panel, identified by its index in its list.
+#[derive(Debug, PartialEq)]
+pub(crate) enum JobIndex {
+    Running(usize),
+    Queued(usize),
+    Finished(usize),
+}
+
+impl AppState {
+    /// Shows or hides the job panel, which is focused when it is shown.
+    pub(crate) fn toggle_job_panel(&mut self) {
+        self.show_job_panel = !self.show_job_panel;
+        self.focused = if self.show_job_panel {
+            Focus::JobPanel
+        } else {
+            Focus::Canvas
+        };
+    }
+
+    /// Returns the number of jobs displayed in the job panel.
+    pub(crate) fn job_count(&self) -> usize {
+        self.parallel_jobs.len() + self.requested_jobs.len() + self.finished_jobs.len()
+    }
+
+    /// Returns the job at the provided index of the job panel.
+    pub(crate) fn job_at(&self, index: usize) -> Result<JobIndex, String> {
+        let queue_start = self.parallel_jobs.len();
+        let finished_start = queue_start + self.requested_jobs.len();
+        Ok(if index < queue_start {
+            JobIndex::Running(index)
+        } else if index < finished_start {
+            JobIndex::Queued(index - queue_start)
+        } else if index < self.job_count() {
+            JobIndex::Finished(index - finished_start)
+        } else {
+            return Err(format!(
+                "<acc {index}> is not the index of a job, see the job panel with <command jobs>."
+            ));
+        })
+    }
+
+    /// Returns the checkpoints of the running jobs, including the cancelled
+    /// jobs whose thread did not exit yet.
+    pub(crate) fn running_checkpoints(&self) -> impl Iterator<Item = &PathBuf> {
+        let running = self.parallel_jobs.iter().map(|job| &job.checkpoint);
+        let stopping = self.stopping_jobs.iter().map(|job| &job.checkpoint);
+        running
+            .chain(stopping)
+            .filter_map(|checkpoint| checkpoint.as_ref().map(|checkpoint| &checkpoint.dir))
+    }
+
+    /// Forgets the cancelled jobs whose thread exited, their checkpoints can then be resumed.
+    pub(crate) fn remove_stopped_jobs(&mut self) {
+        self.stopping_jobs.retain(|job| !job.handle.is_finished());
+    }
+
+    /// Adds a job to the finished jobs of the job panel.
+    pub(crate) fn record_finished_job(&mut self, job: FinishedJob) {
+        self.finished_jobs.push(job);
+        if self.finished_jobs.len() > MAX_FINISHED_JOBS {
+            self.finished_jobs.remove(0);
+        }
+    }
+
+    /// Stops a running job, its thread exits when it detects that the message pipe is closed.
+    /// Until then, the job is kept with the stopping jobs so that it can not be resumed.
+    pub(crate) fn cancel_running_job(&mut self, index: usize) {
+        let mut job = self.parallel_jobs.remove(index);
+        self.prioritized_log_messages.remove(&job.id);
+        if let Some(handle) = job.handle.take() {
+            self.stopping_jobs.push(StoppingJob {
+                checkpoint: job.checkpoint.clone(),
+                handle,
+            });
+        }
+        if job.checkpoint.is_some() {
+            self.log_info(format!(
+                "The rendered part of <acc {}> was saved, use <command resume> to continue it.",
+                esc(&job.filename)
+            ));
+        }
+        self.record_finished_job(job.summary(Some("Cancelled".to_string())));
+    }
+
+    /// Cancels a running or queued job, or removes a finished job from the job panel.
+    pub(crate) fn cancel_job(&mut self, index: usize) -> Result<(), String> {
+        match self.job_at(index)? {
+            JobIndex::Running(i) => self.cancel_running_job(i),
+            JobIndex::Queued(i) => {
+                self.requested_jobs.remove(i);
+            }
+            JobIndex::Finished(i) => {
+                self.finished_jobs.remove(i);
+            }
+        }
+        self.selected_job = self.selected_job.min(self.job_count().saturating_sub(1));
+        Ok(())
+    }
+
+    /// Moves a queued job to another position of the queue.
+    pub(crate) fn move_job(&mut self, index: usize, to: usize) -> Result<(), String> {
+        let (JobIndex::Queued(from), Ok(JobIndex::Queued(to))) =
+            (self.job_at(index)?, self.job_at(to))
+        else {
+            return Err("Only the queued jobs can be moved, inside of the queue.".to_string());
+        };
+        let job = self.requested_jobs.remove(from);
+        self.requested_jobs.insert(to, job);
+        Ok(())
+    }
+
+    /// Queues a copy of a running or queued job, with another size.
+    pub(crate) fn duplicate_job(&mut self, index: usize, size: Vec2<i32>) -> Result<(), String> {
+        let suffix = format!("_{}x{}", size.x, size.y);
+        let capture = match self.job_at(index)? {
+            JobIndex::Running(i) => {
+                let job = &self.parallel_jobs[i];
+                WaitingScreenshot {
+                    size,
+                    rs: job.rs_copy.clone(),
+                    name: Some(job.filename.clone() + &suffix),
+                    supersampling: job.supersampling,
+                    checkpoint: None,
+                }
+            }
+            JobIndex::Queued(i) => {
+                let job = &self.requested_jobs[i];
+                WaitingScreenshot {
+                    size,
+                    rs: job.rs.clone(),
+                    name: job.name.as_ref().map(|name| name.clone() + &suffix),
+                    supersampling: job.supersampling,
+                    checkpoint: None,
+                }
+            }
+            JobIndex::Finished(_) => {
+                return Err("Only the running and queued jobs can be duplicated.".to_string())
+            }
+        };
+        queue_capture(self, capture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, thread, time::Duration};
+
+    use super::JobIndex;
+    use crate::{
+        app::{checkpoint::Checkpoint, ScreenshotMaster, SlaveMessage},
+        commands::capture::execute_capture,
+        frac_logic::RenderSettings,
+        helpers::Vec2,
+        AppState,
+    };
+
+    #[test]
+    fn test_job_control() {
+        let mut state = AppState::default();
+        for name in ["first", "second", "third"] {
+            execute_capture(&mut state, vec!["64", "32", name]).unwrap();
+        }
+        assert_eq!(state.job_at(2), Ok(JobIndex::Queued(2)));
+        assert!(state.job_at(3).is_err());
+
+        state.move_job(2, 0).unwrap();
+        assert!(state.move_job(0, 3).is_err());
+        state.duplicate_job(1, Vec2::new(128, 64)).unwrap();
+        let names: Vec<_> = state
+            .requested_jobs
+            .iter()
+            .map(|job| job.filename())
+            .collect();
+        assert_eq!(names, ["third", "first", "second", "first_128x64"]);
+        assert_eq!(state.requested_jobs[3].size, Vec2::new(128, 64));
+
+        state.selected_job = 3;
+        state.cancel_job(3).unwrap();
+        state.cancel_job(0).unwrap();
+        assert_eq!(state.job_count(), 2);
+        assert_eq!(state.selected_job, 1);
+    }
+
+    #[test]
+    fn test_stopping_job() {
+        let mut state = AppState::default();
+        let checkpoint = Checkpoint {
+            dir: "stopping_job".into(),
+            band_lines: 4,
+        };
+        // The thread renders until the message pipe is closed, like a capture.
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            while tx.send(SlaveMessage::LineRender).is_ok() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Err("Cancelled".to_string())
+        });
+        state.parallel_jobs.push(ScreenshotMaster::new(
+            Vec2::new(64, 32),
+            rx,
+            handle,
+            RenderSettings::default(),
+            "stopping_job".to_string(),
+            Default::default(),
+            Some(checkpoint.clone()),
+        ));
+
+        // The checkpoint can not be resumed until the thread exited.
+        state.cancel_job(0).unwrap();
+        assert!(state.parallel_jobs.is_empty());
+        assert!(state
+            .running_checkpoints()
+            .any(|dir| *dir == checkpoint.dir));
+        while !state.stopping_jobs.is_empty() {
+            state.remove_stopped_jobs();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(state.running_checkpoints().count(), 0);
+    }
+}
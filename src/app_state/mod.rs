@@ -5,20 +5,30 @@ use tui_input::Input as TuiInput;
 use tui_scrollview::ScrollViewState;
 
 mod click_modes;
+pub(crate) mod config;
 pub(crate) mod default_app_state;
 mod helpers;
 pub(crate) mod hsl_settings;
+pub(crate) mod jobs;
 mod state_loading;
 mod stats;
 pub(crate) use click_modes::{ClickConfig, ClickMode};
 pub(crate) use stats::Stats;
 
-use crate::{app::WaitingScreenshot, frac_logic::RenderSettings, helpers::Focus};
+use crate::{
+    app::{CaptureLimits, FinishedJob, ScreenshotMaster, StoppingJob, WaitingScreenshot},
+    frac_logic::{RenderSettings, TileCache},
+    helpers::{Focus, Vec2},
+};
 
 pub(crate) struct AppState {
     pub(crate) redraw_canvas: bool,
     pub(crate) repaint_canvas: bool,
+    /// The move of the canvas in cells since the last redraw, if it only moved by whole cells.
+    pub(crate) canvas_shift: Option<Vec2<i32>>,
     pub(crate) stats: Stats,
+    /// The progression of the canvas render running in the background, in percent.
+    pub(crate) canvas_render_progress: Option<i32>,
     pub(crate) focused: Focus,
     pub(crate) quit: bool,
     pub(crate) log_messages: Vec<String>,
@@ -31,7 +41,24 @@ pub(crate) struct AppState {
     pub(crate) move_dist: i32,
     pub(crate) scaling_factor: i32,
     pub(crate) render_settings: RenderSettings,
+    /// The recently rendered points of the canvas, reused when going back to a previous view.
+    pub(crate) tile_cache: TileCache,
     pub(crate) requested_jobs: Vec<WaitingScreenshot>,
+    /// The memory and disk space the captures are allowed to use.
+    pub(crate) capture_limits: CaptureLimits,
+    /// The directory where the checkpoints of the capture jobs are saved.
+    pub(crate) checkpoints_dir: PathBuf,
+    /// The capture jobs running in the background.
+    pub(crate) parallel_jobs: Vec<ScreenshotMaster>,
+    /// The maximum number of capture jobs running at the same time.
+    pub(crate) max_jobs: usize,
+    /// The last jobs which completed, failed or were cancelled.
+    pub(crate) finished_jobs: Vec<FinishedJob>,
+    /// The cancelled jobs whose thread is still running, which can not be resumed yet.
+    pub(crate) stopping_jobs: Vec<StoppingJob>,
+    pub(crate) show_job_panel: bool,
+    /// The index of the selected job in the job panel.
+    pub(crate) selected_job: usize,
     pub(crate) click_config: ClickConfig,
     pub(crate) remove_jobs: bool,
     pub(crate) pause_jobs: bool,
@@ -39,4 +66,6 @@ pub(crate) struct AppState {
     pub(crate) selected_canvas_variable: usize,
     pub(crate) detected_state_files: Vec<PathBuf>,
     pub(crate) current_state_file_index: usize,
+    /// The configuration file, where the settings are saved when they are changed.
+    pub(crate) config_path: Option<PathBuf>,
 }
@@ -2,8 +2,18 @@ use rug::{ops::CompleteRound, Complex, Float};
 
 use crate::{
     colors::get_palette_index_by_name,
+    commands::{
+        exponent::{MAX_EXPONENT, MIN_EXPONENT},
+        max_iter::{MAX_MAX_ITER, MIN_MAX_ITER},
+    },
+    frac_logic::buddhabrot::MAX_SAMPLES_PER_PIXEL,
     fractals::get_frac_index_by_name,
-    helpers::{markup::esc, void_fills, SavedState},
+    helpers::{
+        coloring_modes,
+        markup::esc,
+        polynomial::{parse_complex, parse_complex_list},
+        void_fills, NativeComplex, Polynomial, SavedState,
+    },
     VERSION,
 };
 
@@ -15,124 +25,226 @@ impl AppState {
         if saved.version.is_none() || saved.version.as_ref().unwrap() != VERSION {
             self.log_warn(format!(
                 "The loaded state file was generated with Rsfrac version [<red {}>]. This should be OK but it is important to know, if you have any issues.",
-                saved.version.unwrap_or("Unspecified".to_string())
+                saved.version.as_deref().unwrap_or("Unspecified")
             ));
         }
 
-        let result = (|| -> Result<(), String> {
-            // Change selected fractal
-            if let Some(frac_name) = saved.frac_name {
-                let res = self.render_settings.select_fractal(
-                    get_frac_index_by_name(&frac_name)
-                        .ok_or("Invalid fractal name in state file.")?,
-                );
+        // The supersampling is a parameter of the capture, not of the render settings
+        let supersampling = (saved.supersampling, saved.supersampling_jitter);
+        let result = self.apply_settings(saved);
 
-                self.handle_res(res);
-            }
+        self.request_redraw();
 
-            // Change selected color palette
-            if let Some(color_palette_name) = saved.color_palette_name {
-                self.render_settings.palette_index = get_palette_index_by_name(&color_palette_name)
-                    .ok_or("Invalid color palette name in state file.")?;
-            }
-            // Change the palette offset
-            if let Some(palette_offset) = saved.palette_offset {
-                self.render_settings.color_scheme_offset = palette_offset;
-            }
+        match result {
+            Err(err) => self.log_error(format!(
+                "Could not finish loading the state file (<command {filename}>) due to an error: <red {err}>"
+            )),
+            Ok(_) => self.log_success(format!(
+                "Successfully loaded state from: <command {}>.", esc(filename),
+            ))
+        }
 
-            // Change the decimal precision
-            if let Some(precision) = saved.precision {
-                self.set_decimal_prec(precision);
-            }
+        if let (Some(factor @ 2..), jitter) = supersampling {
+            let jitter = if jitter == Some(true) { "j" } else { "" };
+            self.log_info(format!(
+                "This state was captured with supersampling, use <command capture x{factor}{jitter}> to take the same capture."
+            ));
+        }
+    }
 
-            // Change the smoothness
-            if let Some(smoothness) = saved.smoothness {
-                self.render_settings.smoothness = smoothness;
-            }
+    /// Applies the render settings of a state file, stopping at the first invalid one.
+    pub(crate) fn apply_settings(&mut self, saved: SavedState) -> Result<(), String> {
+        // Change the user-defined formula, before selecting the fractal
+        // so that the GPU shader is generated from the new formula
+        if let Some(formula) = saved.formula {
+            self.render_settings.formula = formula
+                .parse()
+                .map_err(|err| format!("Invalid formula: {err}"))?;
+        }
 
-            // Change the canvas position
-            if let Some(pos) = saved.pos {
-                self.render_settings.pos = Complex::parse(pos)
-                    .map_err(|err| format!("Invalid canvas position: {}", esc(err)))?
-                    .complete((self.render_settings.prec, self.render_settings.prec));
-            }
+        // Change selected fractal
+        if let Some(frac_name) = saved.frac_name {
+            let res = self.render_settings.select_fractal(
+                get_frac_index_by_name(&frac_name).ok_or("Invalid fractal name in state file.")?,
+            );
 
-            // Change the mandelbrot constant
-            if let Some(c) = saved.mandel_constant {
-                self.render_settings.mandel_constant = Complex::parse(c)
-                    .map_err(|err| format!("Invalid mandelbrot constant: {}", esc(err)))?
-                    .complete((self.render_settings.prec, self.render_settings.prec));
-            }
+            self.handle_res(res);
+        }
 
-            // Change the julia constant
-            if let Some(c) = saved.julia_constant {
-                self.render_settings.julia_constant = Complex::parse(c)
-                    .map_err(|err| format!("Invalid julia constant: {}", esc(err)))?
-                    .complete((self.render_settings.prec, self.render_settings.prec));
-            }
+        // Change selected color palette
+        if let Some(color_palette_name) = saved.color_palette_name {
+            self.render_settings.palette_index = get_palette_index_by_name(&color_palette_name)
+                .ok_or("Invalid color palette name in state file.")?;
+        }
+        // Change the palette offset
+        if let Some(palette_offset) = saved.palette_offset {
+            self.render_settings.color_scheme_offset = palette_offset;
+        }
 
-            // Change the bailout
-            if let Some(b) = saved.bailout {
-                self.render_settings.bailout = b;
-            }
+        // Change the decimal precision
+        if let Some(precision) = saved.precision {
+            self.set_decimal_prec(precision);
+        }
 
-            // Change the cell size
-            if let Some(complex_width) = saved.complex_width {
-                self.render_settings.set_width(
-                    Float::parse(complex_width)
-                        .map_err(|err| format!("Invalid canvas width: {}", esc(err)))?
-                        .complete(self.render_settings.prec),
-                );
-            }
+        // Change the smoothness
+        if let Some(smoothness) = saved.smoothness {
+            self.render_settings.smoothness = smoothness;
+        }
 
-            // Change the max_iter value
-            if let Some(max_iter) = saved.max_iter {
-                self.render_settings.max_iter = max_iter;
-            }
+        // Change the canvas position
+        if let Some(pos) = saved.pos {
+            self.render_settings.pos = Complex::parse(pos)
+                .map_err(|err| format!("Invalid canvas position: {}", esc(err)))?
+                .complete((self.render_settings.prec, self.render_settings.prec));
+        }
 
-            // Change the void fill method
-            if let Some(void_fill) = saved.void_fill {
-                self.render_settings.void_fill_index = void_fills()
-                    .iter()
-                    .position(|vf| *vf == void_fill)
-                    .ok_or("Invalid void fill name in state file.")?;
-            }
+        // Change the mandelbrot constant
+        if let Some(c) = saved.mandel_constant {
+            self.render_settings.mandel_constant = Complex::parse(c)
+                .map_err(|err| format!("Invalid mandelbrot constant: {}", esc(err)))?
+                .complete((self.render_settings.prec, self.render_settings.prec));
+        }
 
-            // Enable or disable hsl mode
-            if let Some(hsl_enabled) = saved.hsl_mode {
-                self.render_settings.hsl_settings.enabled = hsl_enabled
-            }
+        // Change the julia constant
+        if let Some(c) = saved.julia_constant {
+            self.render_settings.julia_constant = Complex::parse(c)
+                .map_err(|err| format!("Invalid julia constant: {}", esc(err)))?
+                .complete((self.render_settings.prec, self.render_settings.prec));
+        }
 
-            // Change the hsl smoothness
-            if let Some(smoothness) = saved.hsl_smoothness {
-                self.render_settings.hsl_settings.smoothness = smoothness;
-            }
+        // Change the bailout
+        if let Some(b) = saved.bailout {
+            self.render_settings.bailout = b;
+        }
+        if let Some(escape_norm) = saved.escape_norm {
+            self.render_settings.escape_norm = escape_norm;
+        }
 
-            // Change the hsl saturation
-            if let Some(sat) = saved.hsl_saturation {
-                self.render_settings.hsl_settings.saturation = sat;
+        // Change the exponent
+        if let Some(exponent) = saved.exponent {
+            if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent) {
+                return Err("Invalid exponent in state file.".to_string());
             }
-            // Change the hsl lum
-            if let Some(lum) = saved.hsl_lum {
-                self.render_settings.hsl_settings.lum = lum;
+            self.render_settings.exponent = exponent;
+        }
+
+        // Change the polynomial of the Newton fractal
+        if let Some(roots) = saved.newton_roots {
+            self.render_settings.newton_poly = Polynomial::from_roots(
+                parse_complex_list(&roots)
+                    .map_err(|err| format!("Invalid Newton polynomial roots: {err}"))?,
+            )?;
+        }
+
+        // Change the cell size
+        if let Some(complex_width) = saved.complex_width {
+            self.render_settings.set_width(
+                Float::parse(complex_width)
+                    .map_err(|err| format!("Invalid canvas width: {}", esc(err)))?
+                    .complete(self.render_settings.prec),
+            );
+        }
+
+        // Change the max_iter value
+        if let Some(max_iter) = saved.max_iter {
+            self.render_settings.max_iter = max_iter;
+        }
+
+        // Change the void fill method
+        if let Some(void_fill) = saved.void_fill {
+            self.render_settings.void_fill_index = void_fills()
+                .iter()
+                .position(|vf| *vf == void_fill)
+                .ok_or("Invalid void fill name in state file.")?;
+        }
+
+        // Change the coloring mode
+        if let Some(coloring_mode) = saved.coloring_mode {
+            self.render_settings.coloring_mode_index = coloring_modes()
+                .iter()
+                .position(|cm| *cm == coloring_mode)
+                .ok_or("Invalid coloring mode name in state file.")?;
+        }
+
+        // Change the distance estimation threshold
+        if let Some(distance_threshold) = saved.distance_threshold {
+            self.render_settings.distance_threshold = distance_threshold;
+        }
+
+        // Change the orbit trap
+        if let Some(trap_shape) = saved.trap_shape {
+            self.render_settings.orbit_trap.shape = trap_shape;
+        }
+        if let Some(trap_center) = saved.trap_center {
+            self.render_settings.orbit_trap.center = NativeComplex::from_rug(
+                &parse_complex(&trap_center)
+                    .map_err(|err| format!("Invalid orbit trap center: {err}"))?,
+            );
+        }
+        if let Some(trap_size) = saved.trap_size {
+            if !(trap_size > 0.0 && trap_size.is_finite()) {
+                return Err("Invalid orbit trap size in state file.".to_string());
             }
-            // Change the hsl hue offset
-            if let Some(hue_offset) = saved.hsl_hue_offset {
-                self.render_settings.hsl_settings.hue_offset = hue_offset;
+            self.render_settings.orbit_trap.size = trap_size;
+        }
+        if let Some(trap_angle) = saved.trap_angle {
+            self.render_settings.orbit_trap.angle = 0.0;
+            self.render_settings.orbit_trap.increment_angle(trap_angle);
+        }
+
+        // Change the Buddhabrot settings
+        if let Some(density_mode) = saved.density_mode {
+            self.render_settings.buddhabrot.mode = density_mode;
+        }
+        if let Some(samples_per_pixel) = saved.samples_per_pixel {
+            if !(1..=MAX_SAMPLES_PER_PIXEL).contains(&samples_per_pixel) {
+                return Err("Invalid number of samples per pixel in state file.".to_string());
             }
+            self.render_settings.buddhabrot.samples_per_pixel = samples_per_pixel;
+        }
+        if let Some(nebula_limits) = saved.nebula_limits {
+            if !nebula_limits
+                .iter()
+                .all(|limit| (MIN_MAX_ITER..=MAX_MAX_ITER).contains(limit))
+            {
+                return Err("Invalid Nebulabrot limits in state file.".to_string());
+            }
+            self.render_settings.buddhabrot.nebula_limits = nebula_limits;
+        }
 
-            Ok(())
-        })();
+        // Change the interior coloring
+        if let Some(interior_mode) = saved.interior_mode {
+            self.render_settings.interior.mode = interior_mode;
+        }
+        if let Some(interior_palette_name) = saved.interior_palette_name {
+            self.render_settings.interior.palette_index =
+                get_palette_index_by_name(&interior_palette_name)
+                    .ok_or("Invalid interior palette name in state file.")?;
+        }
 
-        self.request_redraw();
+        // Enable or disable hsl mode
+        if let Some(hsl_enabled) = saved.hsl_mode {
+            self.render_settings.hsl_settings.enabled = hsl_enabled
+        }
 
-        match result {
-            Err(err) => self.log_error(format!(
-                "Could not finish loading the state file (<command {filename}>) due to an error: <red {err}>"
-            )),
-            Ok(_) => self.log_success(format!(
-                "Successfully loaded state from: <command {}>.", esc(filename),
-            ))
+        // Change the hsl smoothness
+        if let Some(smoothness) = saved.hsl_smoothness {
+            self.render_settings.hsl_settings.smoothness = smoothness;
         }
+
+        // Change the hsl saturation
+        if let Some(sat) = saved.hsl_saturation {
+            self.render_settings.hsl_settings.saturation = sat;
+        }
+        // Change the hsl lum
+        if let Some(lum) = saved.hsl_lum {
+            self.render_settings.hsl_settings.lum = lum;
+        }
+        // Change the hsl hue offset
+        if let Some(hue_offset) = saved.hsl_hue_offset {
+            self.render_settings.hsl_settings.hue_offset = hue_offset;
+        }
+
+        Ok(())
     }
 }
@@ -12,6 +12,12 @@ pub(crate) struct Stats {
     pub(crate) highest_diverg: i32,
     /// The duration took by the latest full canvas rendering
     pub(crate) render_time: Duration,
+    /// The fraction of the points filled by the subdivision instead
+    /// of being computed, if the canvas was rendered with it.
+    pub(crate) subdivision_fill: Option<f64>,
+    /// The number of tiles of the canvas found in the tile cache,
+    /// and the number of tiles missing, if the cache was used.
+    pub(crate) cache_usage: Option<(usize, usize)>,
 }
 
 impl RenderSettings {
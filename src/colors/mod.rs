@@ -6,8 +6,9 @@ mod palettes;
 pub(crate) use palettes::*;
 
 /// Returns the color assiciated to the given divergence in the provided palette.
-pub(crate) fn palette_color(i: i32, offset: i32, pal: &Palette, smoothing: i32) -> Color {
-    let d = (offset + i) as f32 / smoothing as f32;
+/// The divergence can be fractional, when smooth coloring is used.
+pub(crate) fn palette_color(i: f32, offset: i32, pal: &Palette, smoothing: i32) -> Color {
+    let d = (offset as f32 + i) / smoothing as f32;
     let min = d.floor() as i32;
     let max = d.ceil() as i32;
     interpolate(
@@ -17,6 +18,35 @@ pub(crate) fn palette_color(i: i32, offset: i32, pal: &Palette, smoothing: i32)
     )
 }
 
+/// How dark the color of a convergence basin gets with the number of iterations, between 0 and 1.
+const BASIN_SHADING: f32 = 0.8;
+
+/// Returns the color associated to a convergence basin in the provided palette.
+/// The basins are spread evenly across the palette, and their color gets darker
+/// as the number of iterations grows.
+pub(crate) fn basin_color(
+    basin: i32,
+    basin_count: i32,
+    iter: i32,
+    offset: i32,
+    pal: &Palette,
+    smoothing: i32,
+) -> Color {
+    let palette_len = pal.colors.len() as i32 * smoothing;
+    let base = palette_color(
+        (basin * palette_len / basin_count.max(1)) as f32,
+        offset,
+        pal,
+        smoothing,
+    );
+    interpolate(base, Color::Rgb(0, 0, 0), basin_shade(iter, smoothing))
+}
+
+/// Returns how much the color of a basin should be darkened, between 0 and `BASIN_SHADING`.
+pub(crate) fn basin_shade(iter: i32, smoothing: i32) -> f32 {
+    BASIN_SHADING * iter as f32 / (iter + smoothing).max(1) as f32
+}
+
 pub(crate) fn palette_color_at(i: i32, pal: &Palette) -> Color {
     pal.colors[i as usize % pal.colors.len()]
 }
@@ -0,0 +1,105 @@
+use super::Command;
+use crate::{
+    frac_logic::{escape_norm::get_escape_norm_by_name, EscapeNorm},
+    helpers::markup::esc,
+    AppState,
+};
+use strum::IntoEnumIterator;
+
+/// Above this bailout, the terms of the sequences may not fit in an `f32` on the GPU.
+const MAX_BAILOUT: f32 = 1e6;
+
+pub(crate) fn execute_bailout(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let rs = &mut state.render_settings;
+
+    if args.is_empty() {
+        let msg = format!(
+            "Current bailout: <acc {}>, escape norm: <acc {}>\nAvailable norms: {}",
+            rs.bailout,
+            rs.escape_norm,
+            EscapeNorm::iter()
+                .map(|norm| format!("<acc {norm}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        state.log_raw(msg);
+        return Ok(());
+    }
+
+    match args[0] {
+        "norm" if args.len() == 2 => {
+            rs.escape_norm = get_escape_norm_by_name(args[1]).ok_or(format!(
+                "Could not find escape norm with name: <command {}>",
+                esc(args[1])
+            ))?;
+            let norm = rs.escape_norm;
+            state.log_success(format!("Escape norm set to <acc {norm}>."));
+        }
+        value if args.len() == 1 => {
+            let bailout = value
+                .parse::<f32>()
+                .map_err(|err| format!("Please provide a valid number: {}", esc(err)))?;
+            if !(bailout > 0.0 && bailout <= MAX_BAILOUT) {
+                return Err(format!(
+                    "The bailout must be positive and at most {MAX_BAILOUT}."
+                ));
+            }
+            rs.bailout = bailout;
+            state.log_success(format!("Bailout successfully set to <acc {bailout}>."));
+        }
+        _ => {
+            return Err(format!(
+                "Unexpected arguments, see <command help {}>.",
+                BAILOUT.name
+            ))
+        }
+    }
+    state.request_redraw();
+    Ok(())
+}
+
+pub(crate) const BAILOUT: Command = Command {
+    execute: &execute_bailout,
+    name: "bailout",
+    aliases: &["bo"],
+    accepted_arg_count: &[0, 1, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Display the current bailout and escape norm, and the available norms.\n",
+        "<green Usage: <command [value]>>\n",
+        "Set the bailout: a sequence escapes when the norm of one of its terms reaches it. ",
+        "It can also be selected with the <acc BailOut> click mode.\n",
+        "<green Usage: <command norm [norm]>>\n",
+        "Select the norm compared with the bailout:\n",
+        "- <acc Euclidean>: the modulus, which is the default.\n",
+        "- <acc Max>: the largest absolute value of the two parts.\n",
+        "- <acc Real>: the absolute value of the real part.\n",
+        "- <acc Imag>: the absolute value of the imaginary part.\n",
+        "- <acc Manhattan>: the sum of the absolute values of the two parts.\n",
+        "The bailout and the norm are used the same way on the CPU and on the GPU.",
+    )),
+    basic_desc: "Set the bailout and the norm used to decide when the sequences escape.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_bailout;
+    use crate::{frac_logic::EscapeNorm, AppState};
+
+    #[test]
+    fn test_bailout_command() {
+        let mut state = AppState::default();
+
+        execute_bailout(&mut state, vec!["100"]).unwrap();
+        assert_eq!(state.render_settings.bailout, 100.0);
+        execute_bailout(&mut state, vec!["norm", "max"]).unwrap();
+        assert_eq!(state.render_settings.escape_norm, EscapeNorm::Max);
+
+        assert!(execute_bailout(&mut state, vec!["-1"]).is_err());
+        assert!(execute_bailout(&mut state, vec!["NaN"]).is_err());
+        assert!(execute_bailout(&mut state, vec!["norm", "hexagon"]).is_err());
+        assert!(execute_bailout(&mut state, vec!["radius", "2"]).is_err());
+        assert_eq!(state.render_settings.bailout, 100.0);
+        assert_eq!(state.render_settings.escape_norm, EscapeNorm::Max);
+    }
+}
@@ -0,0 +1,150 @@
+use super::{
+    max_iter::{MAX_MAX_ITER, MIN_MAX_ITER},
+    Command,
+};
+use crate::{
+    frac_logic::{
+        buddhabrot::{get_density_mode_by_name, MAX_SAMPLES_PER_PIXEL},
+        DensityMode,
+    },
+    fractals::MANDELBROT,
+    helpers::markup::esc,
+    AppState,
+};
+use strum::IntoEnumIterator;
+
+pub(crate) fn execute_buddhabrot(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let settings = &mut state.render_settings.buddhabrot;
+
+    if args.is_empty() {
+        let msg = format!(
+            "Current density mode: <acc {}>, samples per pixel: <acc {}>, Nebulabrot limits: <acc {:?}>\nAvailable modes: {}",
+            settings.mode,
+            settings.samples_per_pixel,
+            settings.nebula_limits,
+            DensityMode::iter()
+                .map(|mode| format!("<acc {mode}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        state.log_raw(msg);
+        return Ok(());
+    }
+
+    match args[0] {
+        "samples" if args.len() == 2 => {
+            let samples = args[1]
+                .parse::<u32>()
+                .map_err(|err| format!("Please provide a valid integer: {}", esc(err)))?;
+            if !(1..=MAX_SAMPLES_PER_PIXEL).contains(&samples) {
+                return Err(format!(
+                    "The number of samples per pixel must be between 1 and {MAX_SAMPLES_PER_PIXEL}."
+                ));
+            }
+            settings.samples_per_pixel = samples;
+            state.log_success(format!(
+                "The captures will sample <acc {samples}> points per pixel."
+            ));
+            return Ok(());
+        }
+        "limits" if args.len() == 4 => {
+            let mut limits = [0; 3];
+            for (limit, arg) in limits.iter_mut().zip(&args[1..]) {
+                *limit = arg
+                    .parse()
+                    .ok()
+                    .filter(|limit| (MIN_MAX_ITER..=MAX_MAX_ITER).contains(limit))
+                    .ok_or(format!(
+                        "The limits must be integers between {MIN_MAX_ITER} and {MAX_MAX_ITER}, got <acc {}>.",
+                        esc(arg)
+                    ))?;
+            }
+            settings.nebula_limits = limits;
+            state.log_success(format!(
+                "Nebulabrot limits successfully set to <acc {limits:?}>."
+            ));
+        }
+        name if args.len() == 1 => {
+            settings.mode = get_density_mode_by_name(name).ok_or(format!(
+                "Could not find density mode with name: <command {}>",
+                esc(name)
+            ))?;
+            let mode = settings.mode;
+            if mode != DensityMode::Off && !state.render_settings.use_density() {
+                state.log_warn(format!(
+                    "The <acc {mode}> is only rendered for the {} set with an integer exponent.",
+                    MANDELBROT.name
+                ));
+            }
+            state.log_success(format!("Density mode set to <acc {mode}>."));
+        }
+        _ => {
+            return Err(format!(
+                "Unexpected arguments, see <command help {}>.",
+                BUDDHABROT.name
+            ))
+        }
+    }
+    state.request_redraw();
+    Ok(())
+}
+
+pub(crate) const BUDDHABROT: Command = Command {
+    execute: &execute_buddhabrot,
+    name: "buddhabrot",
+    aliases: &["bb"],
+    accepted_arg_count: &[0, 1, 2, 4],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Display the current density mode and settings.\n",
+        "<green Usage: <command [mode]>>\n",
+        "Select the density mode: <acc Off>, <acc Buddhabrot> or <acc Nebulabrot>. ",
+        "Instead of coloring each point by its divergence, random points are sampled, ",
+        "and the terms of their sequences are accumulated when they escape. ",
+        "The <acc Buddhabrot> shows the density of the orbits escaping before the maximum ",
+        "number of iterations, and the <acc Nebulabrot> combines three of them, with the ",
+        "iteration limits of the red, green and blue channels.\n",
+        "<green Usage: <command samples [count]>>\n",
+        "Set the number of points sampled per pixel of the captures, the canvas uses fewer.\n",
+        "<green Usage: <command limits [red] [green] [blue]>>\n",
+        "Set the iteration limits of the Nebulabrot channels.\n",
+        "Density modes are only available for the Mandelbrot set with an integer exponent, ",
+        "and are always rendered with the CPU.",
+    )),
+    basic_desc: "Render the Buddhabrot or the Nebulabrot instead of the Mandelbrot set.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_buddhabrot;
+    use crate::{frac_logic::DensityMode, AppState};
+
+    #[test]
+    fn test_buddhabrot_command() {
+        let mut state = AppState::default();
+
+        execute_buddhabrot(&mut state, vec!["nebula"]).unwrap();
+        assert_eq!(
+            state.render_settings.buddhabrot.mode,
+            DensityMode::Nebulabrot
+        );
+        assert!(state.render_settings.use_density());
+
+        execute_buddhabrot(&mut state, vec!["limits", "1000", "100", "10"]).unwrap();
+        assert_eq!(
+            state.render_settings.buddhabrot.nebula_limits,
+            [1000, 100, 10]
+        );
+        execute_buddhabrot(&mut state, vec!["samples", "5"]).unwrap();
+        assert_eq!(state.render_settings.buddhabrot.samples_per_pixel, 5);
+
+        assert!(execute_buddhabrot(&mut state, vec!["limits", "1000", "0", "10"]).is_err());
+        assert!(execute_buddhabrot(&mut state, vec!["samples", "0"]).is_err());
+        assert!(execute_buddhabrot(&mut state, vec!["anti"]).is_err());
+        assert!(execute_buddhabrot(&mut state, vec!["off", "5"]).is_err());
+        assert_eq!(
+            state.render_settings.buddhabrot.nebula_limits,
+            [1000, 100, 10]
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use super::Command;
+use crate::{helpers::markup::esc, AppState};
+
+/// The maximum size of the tile cache, in megabytes.
+const MAX_CACHE_SIZE: usize = 4096;
+
+pub(crate) fn execute_cache(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let cache = &mut state.tile_cache;
+    match args.first() {
+        None => {
+            let msg = format!(
+                "Tiles: <acc {}> (<acc {:.1}> of <acc {}> MB)\nHits: <acc {}>, misses: <acc {}>",
+                cache.len(),
+                cache.used_bytes() as f64 / (1024.0 * 1024.0),
+                cache.size(),
+                cache.hits,
+                cache.misses,
+            );
+            state.log_info_title("Tile Cache", msg);
+        }
+        Some(&"clear") => {
+            cache.clear();
+            state.log_success("Tile cache cleared.");
+        }
+        Some(value) => {
+            let size = value
+                .parse::<usize>()
+                .map_err(|err| format!("Please provide a valid size in megabytes: {}", esc(err)))?;
+            if size > MAX_CACHE_SIZE {
+                return Err(format!(
+                    "The cache size must be at most {MAX_CACHE_SIZE} MB."
+                ));
+            }
+            cache.resize(size);
+            state.log_success(if size == 0 {
+                "Tile cache disabled.".to_string()
+            } else {
+                format!("Tile cache size set to <acc {size}> MB.")
+            });
+        }
+    }
+    Ok(())
+}
+
+pub(crate) const CACHE: Command = Command {
+    execute: &execute_cache,
+    name: "cache",
+    aliases: &["ca"],
+    accepted_arg_count: &[0, 1],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Display the number of tiles in the cache, and the number of hits and misses.\n",
+        "<green Usage: <command clear>>\n",
+        "Remove all the tiles from the cache.\n",
+        "<green Usage: <command [size]>>\n",
+        "Set the maximum size of the cache in megabytes, 64 by default, ",
+        "or disable it with a size of 0.\n",
+        "In CPU mode, the rendered points are stored by tiles of 32x32 points, identified by ",
+        "the fractal, its parameters, the maximum iteration count, the precision, the zoom level ",
+        "and their position. Going back to a previous view, for example by zooming back out, ",
+        "reuses the stored points instead of computing them again. When the cache is full, the ",
+        "least recently used tiles are removed. The tiles of the last render found in the cache ",
+        "are displayed on the canvas.",
+    )),
+    basic_desc: "Display, clear or resize the cache of the rendered tiles.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_cache;
+    use crate::AppState;
+
+    #[test]
+    fn test_cache_command() {
+        let mut state = AppState::default();
+        assert_eq!(state.tile_cache.size(), 64);
+
+        execute_cache(&mut state, vec![]).unwrap();
+        execute_cache(&mut state, vec!["16"]).unwrap();
+        assert_eq!(state.tile_cache.size(), 16);
+        execute_cache(&mut state, vec!["clear"]).unwrap();
+        execute_cache(&mut state, vec!["0"]).unwrap();
+        assert!(!state.tile_cache.enabled());
+
+        assert!(execute_cache(&mut state, vec!["-1"]).is_err());
+        assert!(execute_cache(&mut state, vec!["100000"]).is_err());
+        assert!(execute_cache(&mut state, vec!["empty"]).is_err());
+        assert_eq!(state.tile_cache.size(), 0);
+    }
+}
@@ -1,11 +1,54 @@
 use super::Command;
 use crate::{
-    app::WaitingScreenshot,
+    app::{Supersampling, WaitingScreenshot},
     helpers::{markup::esc, Vec2},
     AppState,
 };
 
-pub(crate) fn execute_capture(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+/// Parses the width and the height of a capture.
+pub(crate) fn parse_capture_size(width: &str, height: &str) -> Result<Vec2<i32>, String> {
+    let parsed_width = width.parse().map_err(|err| {
+        format!(
+            "The provided width could not be parsed, make sure to enter a valid integer: {}",
+            esc(err)
+        )
+    })?;
+
+    let parsed_height: i32 = height.parse().map_err(|err| {
+        format!(
+            "The provided height could not be parsed, make sure to enter a valid integer: {}",
+            esc(err)
+        )
+    })?;
+
+    let size_range = 16..u16::MAX as i32 + 1;
+
+    if !size_range.contains(&parsed_height) || !size_range.contains(&parsed_width) {
+        return Err(
+            "The screenshot must be at least 16 and at most 65535 pixels in width and height."
+                .to_string(),
+        );
+    }
+
+    Ok(Vec2::new(parsed_width, parsed_height))
+}
+
+pub(crate) fn execute_capture(state: &mut AppState, mut args: Vec<&str>) -> Result<(), String> {
+    // If the last argument is a supersampling factor such as `x3` or `x3j`, remove it
+    let supersampling = match args.last().and_then(|arg| arg.strip_prefix('x')) {
+        Some(factor) if factor.starts_with(|c: char| c.is_ascii_digit()) => {
+            let supersampling = factor.parse::<Supersampling>()?;
+            args.pop();
+            supersampling
+        }
+        _ => Supersampling::default(),
+    };
+    if supersampling.factor > 1 && state.render_settings.use_density() {
+        state.log_warn(
+            "Supersampling is not used with the density rendering models, increase the samples per pixel instead.",
+        );
+    }
+
     // No name by default
     let mut name = None;
     // default size
@@ -15,48 +58,57 @@ pub(crate) fn execute_capture(state: &mut AppState, args: Vec<&str>) -> Result<(
         1 => name = Some(args[0].to_string()),
         2 | 3 => {
             // If there are two or three arguments, parse the first two as the size
-            let width = args[0];
-            let height = args[1];
-
-            let parsed_width = width.parse().map_err(|err| {
-                format!(
-                    "The provided width could not be parsed, make sure to enter a valid integer: {}",
-                    esc(err)
-                )
-            })?;
-
-            let parsed_height: i32 = height.parse().map_err(|err| {
-                format!(
-                    "The provided height could not be parsed, make sure to enter a valid integer: {}",
-                    esc(err)
-                )
-            })?;
-
-            let size_range = 16..u16::MAX as i32 + 1;
-
-            if !size_range.contains(&parsed_height) || !size_range.contains(&parsed_width) {
-                return Err(
-                    "The screenshot must be at least 16 and at most 65535 pixels in width and height."
-                        .to_string(),
-                );
-            }
-
-            size = Vec2::new(parsed_width, parsed_height);
+            size = parse_capture_size(args[0], args[1])?;
 
             // If there is a third argument, it is the capture name
             if args.len() == 3 {
                 name = Some(args[2].to_string())
             }
         }
-        _ => {}
+        0 => {}
+        _ => {
+            return Err(format!(
+                "Too many arguments, see <command help {}>.",
+                CAPTURE.name
+            ))
+        }
     }
 
-    state.requested_jobs.push(WaitingScreenshot {
+    let capture = WaitingScreenshot {
         size,
         name,
         rs: state.render_settings.clone(),
-    });
+        supersampling,
+        checkpoint: None,
+    };
+    queue_capture(state, capture)
+}
+
+/// Adds a capture to the job queue, if the resources it would use are within the limits.
+pub(crate) fn queue_capture(
+    state: &mut AppState,
+    capture: WaitingScreenshot,
+) -> Result<(), String> {
+    // Check the estimated resources before queuing the capture
+    let estimate = capture.estimate();
+    let limits = &state.capture_limits;
+    if estimate.memory > limits.memory {
+        return Err(format!(
+            "This capture would use about <acc {} MB> of memory, more than the limit of <acc {} MB>. \
+            Captures saved as PNG or TIFF are rendered in bands and use much less memory, \
+            see <command capture_format> and <command capture_limits>.",
+            estimate.memory, limits.memory
+        ));
+    }
+    if estimate.disk > limits.disk {
+        state.log_warn(format!(
+            "The image of this capture could take up to <acc {} MB> on the disk, \
+            more than the limit of <acc {} MB>, see <command capture_limits>.",
+            estimate.disk, limits.disk
+        ));
+    }
 
+    state.requested_jobs.push(capture);
     Ok(())
 }
 
@@ -64,12 +116,47 @@ pub(crate) const CAPTURE: Command = Command {
     execute: &execute_capture,
     name: "capture",
     aliases: &["cp"],
-    accepted_arg_count: &[0, 1, 2, 3],
+    accepted_arg_count: &[0, 1, 2, 3, 4],
     detailed_desc: Some(concat!(
-        "<green Usage: <command [width] [height] [?name]>>\n",
-        "<green Usage: <command [?name]>>\n",
+        "<green Usage: <command [width] [height] [?name] [?supersampling]>>\n",
+        "<green Usage: <command [?name] [?supersampling]>>\n",
         "Take a screenshot with the specified size or the default of <acc 1920x1080>. ",
-        "The order of the arguments must match the examples above.",
+        "The order of the arguments must match the examples above.\n",
+        "The supersampling is written as <acc x2>, <acc x3> or <acc x4>, to render 4, 9 or 16 ",
+        "samples per pixel and average their colors, which smooths the edges at the cost of a ",
+        "longer render. Add a <acc j> such as <acc x3j> to place the samples randomly in their ",
+        "part of the pixel instead of on a regular grid. It is ignored by the density ",
+        "rendering models.\n",
+        "The PNG and TIFF captures are rendered in bands written to the file one by one, so that ",
+        "the whole image is never in memory. The memory and disk space used by the capture are ",
+        "estimated first, see <command capture_limits>.",
     )),
     basic_desc: "Takes a high quality screenshot of the canvas.",
 };
+
+#[cfg(test)]
+mod tests {
+    use super::execute_capture;
+    use crate::{app::Supersampling, AppState};
+
+    #[test]
+    fn test_capture_supersampling() {
+        let mut state = AppState::default();
+        execute_capture(&mut state, vec!["64", "32", "name", "x3j"]).unwrap();
+        execute_capture(&mut state, vec!["x2"]).unwrap();
+        execute_capture(&mut state, vec!["x_name"]).unwrap();
+
+        let jobs = &state.requested_jobs;
+        assert_eq!(jobs[0].name.as_deref(), Some("name"));
+        assert_eq!(jobs[0].supersampling.factor, 3);
+        assert!(jobs[0].supersampling.jitter);
+        assert_eq!(jobs[1].name, None);
+        assert_eq!(jobs[1].supersampling.factor, 2);
+        assert_eq!(jobs[2].name.as_deref(), Some("x_name"));
+        assert_eq!(jobs[2].supersampling, Supersampling::default());
+
+        assert!(execute_capture(&mut state, vec!["x5"]).is_err());
+        assert!(execute_capture(&mut state, vec!["64", "32", "name", "other"]).is_err());
+        assert_eq!(state.requested_jobs.len(), 3);
+    }
+}
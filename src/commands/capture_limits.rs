@@ -0,0 +1,78 @@
+use super::Command;
+use crate::{helpers::markup::esc, AppState};
+
+pub(crate) fn execute_capture_limits(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    if args.is_empty() {
+        let limits = &state.capture_limits;
+        state.log_info_title(
+            "Capture Limits",
+            format!(
+                "Memory: <acc {} MB>\nDisk: <acc {} MB>",
+                limits.memory, limits.disk
+            ),
+        );
+        return Ok(());
+    }
+
+    let [memory, disk] = [args[0], args[1]].map(|arg| {
+        arg.parse::<u64>()
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or(format!(
+                "Please provide sizes in megabytes greater than 0, got <acc {}>.",
+                esc(arg)
+            ))
+    });
+    let (memory, disk) = (memory?, disk?);
+    state.capture_limits.memory = memory;
+    state.capture_limits.disk = disk;
+    state.log_success(format!(
+        "Capture limits set to <acc {memory} MB> of memory and <acc {disk} MB> of disk space."
+    ));
+    Ok(())
+}
+
+pub(crate) const CAPTURE_LIMITS: Command = Command {
+    execute: &execute_capture_limits,
+    name: "capture_limits",
+    aliases: &["cpl"],
+    accepted_arg_count: &[0, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Display the current limits.\n",
+        "<green Usage: <command [memory] [disk]>>\n",
+        "Set the limits in megabytes, <acc 4096> and <acc 16384> by default.\n",
+        "Before a capture is queued, the memory and disk space it will use are estimated. ",
+        "A capture that would use more memory than the limit is refused, and a warning is ",
+        "displayed if its image could take more disk space than the limit: the estimate is the ",
+        "size of the uncompressed image, the file is usually smaller. ",
+        "The PNG and TIFF captures are rendered in bands and only use a few dozen megabytes, ",
+        "whatever their size, while the other formats need the whole image in memory.",
+    )),
+    basic_desc: "Set the memory and disk space the captures are allowed to use.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_capture_limits;
+    use crate::{commands::capture::execute_capture, AppState};
+
+    #[test]
+    fn test_capture_limits() {
+        let mut state = AppState::default();
+        execute_capture_limits(&mut state, vec![]).unwrap();
+        execute_capture_limits(&mut state, vec!["64", "8"]).unwrap();
+        assert_eq!(state.capture_limits.memory, 64);
+        assert_eq!(state.capture_limits.disk, 8);
+        assert!(execute_capture_limits(&mut state, vec!["0", "8"]).is_err());
+        assert!(execute_capture_limits(&mut state, vec!["64", "much"]).is_err());
+        assert_eq!(state.capture_limits.memory, 64);
+
+        // A streamed capture is only limited by the disk space, with a warning.
+        execute_capture(&mut state, vec!["4000", "4000"]).unwrap();
+        state.render_settings.image_format = image::ImageFormat::Jpeg;
+        assert!(execute_capture(&mut state, vec!["4000", "4000"]).is_err());
+        execute_capture(&mut state, vec!["1000", "1000"]).unwrap();
+        assert_eq!(state.requested_jobs.len(), 2);
+    }
+}
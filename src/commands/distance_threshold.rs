@@ -0,0 +1,74 @@
+use super::Command;
+use crate::{helpers::markup::esc, AppState};
+
+const MAX_DISTANCE_THRESHOLD: f64 = 100.0;
+
+pub(crate) fn execute_distance_threshold(
+    state: &mut AppState,
+    args: Vec<&str>,
+) -> Result<(), String> {
+    // If no args are provided, just display the current value
+    if args.is_empty() {
+        state.log_info(format!(
+            "The distance estimation threshold is currently set to <acc {}> cells.",
+            state.render_settings.distance_threshold
+        ));
+        return Ok(());
+    }
+
+    let new_val = args[0]
+        .parse::<f64>()
+        .map_err(|err| format!("Please provide a valid number: {}", esc(err)))?;
+
+    if !(0.0..=MAX_DISTANCE_THRESHOLD).contains(&new_val) {
+        return Err(format!(
+            "The threshold must be between 0 and {MAX_DISTANCE_THRESHOLD}."
+        ));
+    }
+
+    state.render_settings.distance_threshold = new_val;
+    state.log_success(format!(
+        "Distance estimation threshold successfully set to <acc {new_val}> cells."
+    ));
+    state.request_repaint();
+    Ok(())
+}
+
+pub(crate) const DISTANCE_THRESHOLD: Command = Command {
+    execute: &execute_distance_threshold,
+    name: "distance_threshold",
+    aliases: &["dt"],
+    accepted_arg_count: &[0, 1],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [threshold]>>\n",
+        "<green Usage: <command [without args]>>\n",
+        "- If no arguments are given, display the current threshold.\n",
+        "- Else, set the threshold to the given value.\n",
+        "With the <acc Distance> coloring mode (cycled with <acc m> on the canvas), ",
+        "the points that are closer to the set than <acc [threshold]> cells are colored as the set, ",
+        "which draws the thin filaments and gives crisp boundaries. ",
+        "<acc [threshold]> must be a number between <acc 0> and <acc 100>.\n",
+        "Distance estimation is available for the Mandelbrot set, the Julia set ",
+        "and the Burning Ship, with an exponent greater than 1.",
+    )),
+    basic_desc: "Set the distance under which points are colored as the set with distance estimation.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_distance_threshold;
+    use crate::AppState;
+
+    #[test]
+    fn test_distance_threshold_command() {
+        let mut state = AppState::default();
+
+        execute_distance_threshold(&mut state, vec!["2.5"]).unwrap();
+        assert_eq!(state.render_settings.distance_threshold, 2.5);
+
+        assert!(execute_distance_threshold(&mut state, vec!["-1"]).is_err());
+        assert!(execute_distance_threshold(&mut state, vec!["1000"]).is_err());
+        assert!(execute_distance_threshold(&mut state, vec!["abc"]).is_err());
+        assert_eq!(state.render_settings.distance_threshold, 2.5);
+    }
+}
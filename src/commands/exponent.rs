@@ -0,0 +1,100 @@
+use super::Command;
+use crate::{helpers::markup::esc, AppState};
+
+pub(crate) const MIN_EXPONENT: f64 = -64.0;
+pub(crate) const MAX_EXPONENT: f64 = 64.0;
+/// The exponent increment when changing it from the canvas.
+pub(crate) const EXPONENT_STEP: f64 = 1.0;
+
+pub(crate) fn execute_exponent(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    // If no args are provided, just display the current value
+    if args.is_empty() {
+        state.log_info(format!(
+            "The exponent is currently set to <acc {}>",
+            state.render_settings.exponent
+        ));
+        return Ok(());
+    }
+
+    // The last argument is always the value
+    let parsed = args[args.len() - 1]
+        .parse::<f64>()
+        .map_err(|err| format!("Please provide a valid number: {}", esc(err)))?;
+
+    // If two arguments are given, the first one is the operator
+    let new_val = if args.len() == 2 {
+        match args[0] {
+            "+" => state.render_settings.exponent + parsed,
+            "-" => state.render_settings.exponent - parsed,
+            _ => {
+                return Err("The first argument must be either <acc +> or <acc ->.".to_string());
+            }
+        }
+    } else {
+        parsed
+    };
+
+    if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&new_val) {
+        return Err(format!(
+            "The exponent must remain between {MIN_EXPONENT} and {MAX_EXPONENT}."
+        ));
+    }
+
+    state.render_settings.exponent = new_val;
+    state.log_success(format!("Exponent successfully set to <acc {new_val}>."));
+    state.request_redraw();
+    Ok(())
+}
+
+pub(crate) const EXPONENT: Command = Command {
+    execute: &execute_exponent,
+    name: "exponent",
+    aliases: &["exp"],
+    accepted_arg_count: &[0, 1, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command +/- [value]>>\n",
+        "<green Usage: <command [exponent]>>\n",
+        "<green Usage: <command [without args]>>\n",
+        "- If no arguments are given, display the current exponent.\n",
+        "- If a value is specified directly, set the exponent to the given value.\n",
+        "- If a value is specified alongside an operator, ",
+        "increase of decrease the exponent by the given value.\n",
+        "<acc [exponent]> can be any real number between <acc -64> and <acc 64>, ",
+        "including non-integer and negative values. ",
+        "The exponent can also be changed from the canvas, by selecting it with <acc t>.",
+    )),
+    basic_desc: concat!(
+        "Change the exponent used in the Mandelbrot, Julia, Burning Ship and derived formulas ",
+        "(Multibrot sets). Use <command frac info [frac]> to see where it is used."
+    ),
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{commands::exponent::execute_exponent, AppState};
+
+    #[test]
+    fn test_exponent_command() {
+        let mut state = AppState::default();
+
+        // `exp 3` should return Ok and set the exponent to 3
+        execute_exponent(&mut state, vec!["3"]).unwrap();
+        assert_eq!(state.render_settings.exponent, 3.0);
+
+        // `exp + 0.5` should return Ok and set the exponent to 3.5
+        execute_exponent(&mut state, vec!["+", "0.5"]).unwrap();
+        assert_eq!(state.render_settings.exponent, 3.5);
+
+        // `exp -2` should return Ok, negative exponents are allowed
+        execute_exponent(&mut state, vec!["-2"]).unwrap();
+        assert_eq!(state.render_settings.exponent, -2.0);
+
+        // `exp 100` and `exp * 2` should return Err
+        assert!(execute_exponent(&mut state, vec!["100"]).is_err());
+        assert!(execute_exponent(&mut state, vec!["*", "2"]).is_err());
+        assert!(execute_exponent(&mut state, vec!["blahblah"]).is_err());
+
+        // The exponent should have remained to -2
+        assert_eq!(state.render_settings.exponent, -2.0);
+    }
+}
@@ -0,0 +1,90 @@
+use super::Command;
+use crate::fractals::{get_frac_index_by_name, Formula, FORMULA, FORMULA_FUNCTIONS};
+use crate::helpers::markup::esc;
+use crate::AppState;
+
+/// The formula can contain spaces, so it can be split in any number of arguments.
+const ACCEPTED_ARG_COUNTS: [usize; 64] = {
+    let mut counts = [0; 64];
+    let mut i = 0;
+    while i < counts.len() {
+        counts[i] = i;
+        i += 1;
+    }
+    counts
+};
+
+pub(crate) fn execute_formula(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    // If no args are provided, just display the current formula
+    if args.is_empty() {
+        state.log_info(format!(
+            "The current formula is <acc {}>\nAvailable functions: {}",
+            esc(&state.render_settings.formula.source),
+            FORMULA_FUNCTIONS
+                .iter()
+                .map(|(name, _)| format!("<acc {name}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        return Ok(());
+    }
+
+    let formula: Formula = args
+        .join(" ")
+        .parse()
+        .map_err(|err| format!("Invalid formula: {err}"))?;
+
+    state.render_settings.formula = formula;
+    // Select the formula pseudo-fractal, which also updates the GPU shader.
+    let res = state
+        .render_settings
+        .select_fractal(get_frac_index_by_name(FORMULA.name).unwrap());
+    state.log_success(format!(
+        "Formula successfully set to <acc {}>.",
+        esc(&state.render_settings.formula.source)
+    ));
+    state.request_redraw();
+    res
+}
+
+pub(crate) const FORMULA_COMMAND: Command = Command {
+    execute: &execute_formula,
+    name: "formula",
+    aliases: &["fml"],
+    accepted_arg_count: &ACCEPTED_ARG_COUNTS,
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [formula]>>\n",
+        "<green Usage: <command [without args]>>\n",
+        "- If no arguments are given, display the current formula and the available functions.\n",
+        "- Else, set the formula used to compute the next term of the sequence, ",
+        "and select the <acc Formula> fractal.\n",
+        "The formula can use the current term <acc z>, the pixel position <acc c>, ",
+        "the imaginary unit <acc i>, real numbers, the <acc + - * / ^> operators, ",
+        "parentheses and functions like <acc sin(z)>. ",
+        "A missing operator between two factors means a multiplication.",
+        "\n<green Example>: <command formula z^3 + sin(z) + c>",
+    )),
+    basic_desc: "View or set the user-defined formula of the <acc Formula> fractal.",
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{commands::formula::execute_formula, AppState};
+
+    #[test]
+    fn test_formula_command() {
+        let mut state = AppState::default();
+
+        // `formula z^3 + sin(z) + c` should return Ok, set the formula and select the fractal
+        execute_formula(&mut state, vec!["z^3", "+", "sin(z)", "+", "c"]).unwrap();
+        assert_eq!(state.render_settings.formula.source, "z^3 + sin(z) + c");
+        assert_eq!(state.render_settings.get_frac_obj().name, "Formula");
+
+        // Invalid formulas should return Err
+        assert!(execute_formula(&mut state, vec!["z^"]).is_err());
+        assert!(execute_formula(&mut state, vec!["x", "+", "c"]).is_err());
+
+        // The formula should have remained the same
+        assert_eq!(state.render_settings.formula.source, "z^3 + sin(z) + c");
+    }
+}
@@ -81,6 +81,14 @@ mod tests {
         execute_frac(&mut state, vec!["ju"]).unwrap();
         assert_eq!(state.render_settings.get_frac_obj().name, "Julia");
 
+        // `frac tri` should return Ok and the Tricorn fractal should be selected
+        execute_frac(&mut state, vec!["tri"]).unwrap();
+        assert_eq!(state.render_settings.get_frac_obj().name, "Tricorn");
+
+        // `frac perp` should return Ok and the Perpendicular fractal should be selected
+        execute_frac(&mut state, vec!["perp"]).unwrap();
+        assert_eq!(state.render_settings.get_frac_obj().name, "Perpendicular");
+
         // `frac non_exist` should return Err
         assert!(execute_frac(&mut state, vec!["non_exist"]).is_err());
 
@@ -0,0 +1,111 @@
+use super::Command;
+use crate::{
+    colors::{get_palette_index_by_name, COLORS},
+    frac_logic::{interior::get_interior_mode_by_name, InteriorMode},
+    helpers::markup::esc,
+    AppState,
+};
+use strum::IntoEnumIterator;
+
+pub(crate) fn execute_interior(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let interior = &mut state.render_settings.interior;
+
+    if args.is_empty() {
+        let msg = format!(
+            "Current interior mode: <acc {}>, palette: <acc {}>\nAvailable modes: {}",
+            interior.mode,
+            COLORS[interior.palette_index].name,
+            InteriorMode::iter()
+                .map(|mode| format!("<acc {mode}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        state.log_raw(msg);
+        return Ok(());
+    }
+
+    match args[0] {
+        "palette" if args.len() == 2 => {
+            let pal = get_palette_index_by_name(args[1])
+                .ok_or(format!("Could not find palette: <red {}>", esc(args[1])))?;
+            interior.palette_index = pal;
+            state.log_success(format!(
+                "Selected interior color scheme: <acc {}>",
+                COLORS[pal].name
+            ));
+            // The interior values are already computed.
+            state.request_repaint();
+        }
+        name if args.len() == 1 => {
+            interior.mode = get_interior_mode_by_name(name).ok_or(format!(
+                "Could not find interior mode with name: <command {}>",
+                esc(name)
+            ))?;
+            let mode = interior.mode;
+            if mode != InteriorMode::Flat && !state.render_settings.use_interior_coloring() {
+                state.log_warn(format!(
+                    "The interior of the {} fractal is not colored.",
+                    state.render_settings.get_frac_obj().name
+                ));
+            }
+            state.log_success(format!("Interior mode set to <acc {mode}>."));
+            state.request_redraw();
+        }
+        _ => {
+            return Err(format!(
+                "Unexpected arguments, see <command help {}>.",
+                INTERIOR.name
+            ))
+        }
+    }
+    Ok(())
+}
+
+pub(crate) const INTERIOR: Command = Command {
+    execute: &execute_interior,
+    name: "interior",
+    aliases: &["in"],
+    accepted_arg_count: &[0, 1, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Display the current interior mode and palette, and the available modes.\n",
+        "<green Usage: <command [mode]>>\n",
+        "Select how the points that reach the maximum number of iterations are colored:\n",
+        "- <acc Flat>: with the void fill (cycled with <acc v> on the canvas).\n",
+        "- <acc Magnitude>: by the modulus of the last term of their sequence.\n",
+        "- <acc Argument>: by the argument of the last term of their sequence.\n",
+        "- <acc Period>: by the period of the cycle their sequence is attracted to.\n",
+        "- <acc AtomDomain>: by the index of the term of their sequence closest to the origin.\n",
+        "The modes can also be cycled with <acc g> on the canvas. ",
+        "They are not available for the Newton fractal.\n",
+        "<green Usage: <command palette [color]>>\n",
+        "Select the palette of the interior, independently from the palette of the exterior.",
+    )),
+    basic_desc: "Select how the interior of the fractals is colored, and its palette.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_interior;
+    use crate::{colors::get_palette_index_by_name, frac_logic::InteriorMode, AppState};
+
+    #[test]
+    fn test_interior_command() {
+        let mut state = AppState::default();
+
+        execute_interior(&mut state, vec!["per"]).unwrap();
+        assert_eq!(state.render_settings.interior.mode, InteriorMode::Period);
+        assert!(state.render_settings.use_interior_coloring());
+
+        execute_interior(&mut state, vec!["palette", "iceberg"]).unwrap();
+        assert_eq!(
+            state.render_settings.interior.palette_index,
+            get_palette_index_by_name("iceberg").unwrap()
+        );
+
+        assert!(execute_interior(&mut state, vec!["palette", "non_exist"]).is_err());
+        assert!(execute_interior(&mut state, vec!["cardioid"]).is_err());
+        assert!(execute_interior(&mut state, vec!["flat", "iceberg"]).is_err());
+        assert_eq!(state.render_settings.interior.mode, InteriorMode::Period);
+    }
+}
@@ -0,0 +1,97 @@
+use super::{capture::parse_capture_size, Command};
+use crate::{helpers::markup::esc, AppState};
+
+/// Parses the index of a job in the job panel.
+fn parse_index(arg: &str) -> Result<usize, String> {
+    arg.parse().map_err(|_| {
+        format!(
+            "<acc {}> is not a valid job index, see the job panel with <command jobs>.",
+            esc(arg)
+        )
+    })
+}
+
+pub(crate) fn execute_jobs(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    if args.is_empty() {
+        state.toggle_job_panel();
+        return Ok(());
+    }
+
+    let index = args.get(1).map(|arg| parse_index(arg)).transpose()?;
+    match (args[0], index, args.len()) {
+        ("cancel", Some(index), 2) => {
+            state.cancel_job(index)?;
+            state.log_success(format!("Job <acc {index}> cancelled."));
+        }
+        ("move", Some(index), 3) => {
+            let to = parse_index(args[2])?;
+            state.move_job(index, to)?;
+            state.log_success(format!("Job <acc {index}> moved to <acc {to}>."));
+        }
+        ("duplicate", Some(index), 4) => {
+            let size = parse_capture_size(args[2], args[3])?;
+            state.duplicate_job(index, size.clone())?;
+            state.log_success(format!(
+                "Job <acc {index}> duplicated with a size of <acc {}x{}>.",
+                size.x, size.y
+            ));
+        }
+        _ => {
+            return Err(format!(
+                "Invalid arguments, see <command help {}>.",
+                JOBS.name
+            ))
+        }
+    }
+    Ok(())
+}
+
+pub(crate) const JOBS: Command = Command {
+    execute: &execute_jobs,
+    name: "jobs",
+    aliases: &["j"],
+    accepted_arg_count: &[0, 2, 3, 4],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Show or hide the job panel, which lists the running, queued and finished capture jobs ",
+        "with their index. It can also be toggled with <acc J> in the canvas.\n",
+        "<green Usage: <command cancel [index]>>\n",
+        "Cancel a running or queued job, or remove a finished job from the panel.\n",
+        "<green Usage: <command move [index] [new index]>>\n",
+        "Move a queued job to another position of the queue.\n",
+        "<green Usage: <command duplicate [index] [width] [height]>>\n",
+        "Queue a copy of a running or queued job, with another size.",
+    )),
+    basic_desc: "Show the job panel, and control the capture jobs.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_jobs;
+    use crate::{commands::capture::execute_capture, helpers::Focus, AppState};
+
+    #[test]
+    fn test_jobs() {
+        let mut state = AppState::default();
+        execute_jobs(&mut state, vec![]).unwrap();
+        assert!(state.show_job_panel);
+        assert_eq!(state.focused, Focus::JobPanel);
+
+        execute_capture(&mut state, vec!["64", "32", "first"]).unwrap();
+        execute_capture(&mut state, vec!["64", "32", "second"]).unwrap();
+        execute_jobs(&mut state, vec!["move", "1", "0"]).unwrap();
+        execute_jobs(&mut state, vec!["duplicate", "0", "100", "50"]).unwrap();
+        assert!(execute_jobs(&mut state, vec!["duplicate", "0", "100", "5"]).is_err());
+        assert!(execute_jobs(&mut state, vec!["cancel", "3"]).is_err());
+        assert!(execute_jobs(&mut state, vec!["cancel", "first"]).is_err());
+        assert!(execute_jobs(&mut state, vec!["move", "0"]).is_err());
+        execute_jobs(&mut state, vec!["cancel", "1"]).unwrap();
+
+        let names: Vec<_> = state
+            .requested_jobs
+            .iter()
+            .map(|job| job.filename())
+            .collect();
+        assert_eq!(names, ["second", "second_100x50"]);
+    }
+}
@@ -0,0 +1,59 @@
+use super::{command_increment::command_increment, Command};
+use crate::AppState;
+
+pub(crate) const MAX_JOBS: usize = 16;
+
+pub(crate) fn execute_max_jobs(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let changed = !args.is_empty();
+    state.max_jobs = command_increment(state, state.max_jobs, args, 1, MAX_JOBS)?;
+    if changed {
+        state.save_config()?;
+    }
+    Ok(())
+}
+
+pub(crate) const MAX_JOBS_COMMAND: Command = Command {
+    execute: &execute_max_jobs,
+    name: "max_jobs",
+    aliases: &["mj"],
+    accepted_arg_count: &[0, 1, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command +/- [value]>>\n",
+        "<green Usage: <command [max_jobs]>>\n",
+        "<green Usage: <command [without args]>>\n",
+        "- If no arguments are given, display the current limit.\n",
+        "- If a value is specified directly, set the limit to the given value.\n",
+        "- If a value is specified alongside an operator, ",
+        "increase of decrease the limit by the given value.\n",
+        "The limit is saved in the configuration file, and is <acc 1> by default. ",
+        "The captures rendered with the CPU share a thread pool which is separate from the ",
+        "canvas renders, so running several of them at once does not slow the canvas down more.",
+    )),
+    basic_desc: "Change the number of capture jobs which can run at the same time.",
+};
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use crate::{commands::max_jobs::execute_max_jobs, AppState};
+
+    #[test]
+    fn test_max_jobs_command() {
+        let path = temp_dir().join(format!("rsfrac_config_{}.toml", std::process::id()));
+        let mut state = AppState::default();
+        state.load_config(path.clone()).unwrap();
+        assert_eq!(state.max_jobs, 1);
+
+        assert!(execute_max_jobs(&mut state, vec!["0"]).is_err());
+        assert!(execute_max_jobs(&mut state, vec!["17"]).is_err());
+        execute_max_jobs(&mut state, vec!["4"]).unwrap();
+        execute_max_jobs(&mut state, vec!["+", "2"]).unwrap();
+
+        // The limit is restored from the configuration file.
+        let mut state = AppState::default();
+        state.load_config(path.clone()).unwrap();
+        assert_eq!(state.max_jobs, 6);
+        let _ = std::fs::remove_file(path);
+    }
+}
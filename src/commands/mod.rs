@@ -1,32 +1,48 @@
 //! Contains the command system logic, as well as the code for each available command.
 
 use crate::AppState;
+pub(crate) mod bailout;
+pub(crate) mod buddhabrot;
+pub(crate) mod cache;
 pub(crate) mod capture;
 pub(crate) mod capture_fit;
 pub(crate) mod capture_format;
 pub(crate) mod capture_hq;
+pub(crate) mod capture_limits;
 pub(crate) mod chunk_size;
 pub(crate) mod clear;
 pub(crate) mod click_mode;
 pub(crate) mod color;
 pub(crate) mod command_increment;
+pub(crate) mod distance_threshold;
+pub(crate) mod exponent;
+pub(crate) mod formula;
 pub(crate) mod frac;
 pub(crate) mod gpu;
 pub(crate) mod gpu_info;
 pub(crate) mod gpu_select;
 pub(crate) mod help;
 pub(crate) mod history;
+pub(crate) mod interior;
+pub(crate) mod jobs;
 pub(crate) mod load;
 pub(crate) mod load_remote;
 pub(crate) mod max_iter;
+pub(crate) mod max_jobs;
 pub(crate) mod move_dist;
+pub(crate) mod newton;
+pub(crate) mod orbit_trap;
 pub(crate) mod pause;
+pub(crate) mod perturbation;
 pub(crate) mod pos;
 pub(crate) mod prec;
 pub(crate) mod quit;
+pub(crate) mod refinement;
+pub(crate) mod resume;
 pub(crate) mod save;
 pub(crate) mod smoothness;
 pub(crate) mod stop;
+pub(crate) mod subdivision;
 pub(crate) mod timeout_detection;
 pub(crate) mod version;
 pub(crate) mod zoom_factor;
@@ -48,7 +64,7 @@ pub(crate) struct Command {
     pub(crate) accepted_arg_count: &'static [usize],
 }
 
-pub(crate) fn get_commands_list() -> [&'static Command; 28] {
+pub(crate) fn get_commands_list() -> [&'static Command; 44] {
     [
         &help::HELP,
         &quit::QUIT,
@@ -60,21 +76,37 @@ pub(crate) fn get_commands_list() -> [&'static Command; 28] {
         &load_remote::LOAD_REMOTE,
         &stop::STOP,
         &pause::PAUSE,
+        &jobs::JOBS,
+        &max_jobs::MAX_JOBS_COMMAND,
+        &resume::RESUME,
         &chunk_size::CHUNK_SIZE,
         &timeout_detection::TIMEOUT_DETECTION,
         &capture::CAPTURE,
         &capture_fit::CAPTURE_FIT,
         &capture_hq::CAPTURE_HQ,
         &capture_format::CAPTURE_FORMAT,
+        &capture_limits::CAPTURE_LIMITS,
         &gpu::GPU,
         &gpu_info::GPU_INFO,
         &gpu_select::GPU_SELECT,
         &pos::POS,
         &prec::PREC,
+        &perturbation::PERTURBATION,
+        &subdivision::SUBDIVISION,
+        &refinement::REFINEMENT,
+        &cache::CACHE,
         &max_iter::MAX_ITER,
+        &exponent::EXPONENT,
+        &bailout::BAILOUT,
         &color::COLOR,
         &smoothness::SMOOTHNESS,
+        &distance_threshold::DISTANCE_THRESHOLD,
+        &orbit_trap::ORBIT_TRAP,
+        &interior::INTERIOR,
         &frac::FRAC,
+        &newton::NEWTON,
+        &formula::FORMULA_COMMAND,
+        &buddhabrot::BUDDHABROT,
         &zoom_factor::ZOOM_FACTOR,
         &move_dist::MOVE_DIST,
         &click_mode::CLICK_MODE,
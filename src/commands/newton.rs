@@ -0,0 +1,88 @@
+use super::Command;
+use crate::helpers::polynomial::{format_complex_list, parse_complex_list};
+use crate::helpers::Polynomial;
+use crate::AppState;
+
+pub(crate) fn execute_newton(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    // If no args are provided, display the current polynomial
+    if args.is_empty() {
+        let poly = &state.render_settings.newton_poly;
+        state.log_info_title(
+            "Newton Polynomial",
+            format!(
+                "Degree: <acc {}>\nCoefficients: <acc {}>\nRoots: <acc {}>",
+                poly.degree(),
+                format_complex_list(&poly.coefs),
+                format_complex_list(&poly.roots),
+            ),
+        );
+        return Ok(());
+    }
+
+    let values = parse_complex_list(args[1])?;
+    let poly = match args[0] {
+        "roots" => Polynomial::from_roots(values)?,
+        "coefs" => Polynomial::from_coefs(values)?,
+        _ => {
+            return Err("The first argument must be either <acc roots> or <acc coefs>.".to_string());
+        }
+    };
+
+    state.log_success(format!(
+        "Polynomial successfully set, its roots are: <acc {}>.",
+        format_complex_list(&poly.roots)
+    ));
+    state.render_settings.newton_poly = poly;
+    state.request_redraw();
+    Ok(())
+}
+
+pub(crate) const NEWTON: Command = Command {
+    execute: &execute_newton,
+    name: "newton",
+    aliases: &["poly"],
+    accepted_arg_count: &[0, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command roots [r1,r2,...]>>\n",
+        "<green Usage: <command coefs [c1,c2,...]>>\n",
+        "<green Usage: <command [without args]>>\n",
+        "- If no arguments are given, display the current polynomial and its roots.\n",
+        "- With <acc roots>, set the polynomial from its roots.\n",
+        "- With <acc coefs>, set the polynomial from its coefficients, ",
+        "from the highest to the lowest degree. Its roots are then found numerically.\n",
+        "Values must be separated by commas, without spaces, and can be complex numbers ",
+        "written like <acc 2>, <acc -i> or <acc 0.5+1.2i>. ",
+        "The degree of the polynomial must be between <acc 1> and <acc 16>. ",
+        "The order of the roots determines the color of their basins.",
+        "\n<green Example>: <command newton coefs 1,0,0,-1> for <acc z³ - 1>.",
+    )),
+    basic_desc: "View or set the polynomial used by the Newton fractal, from its roots or coefficients.",
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{commands::newton::execute_newton, AppState};
+
+    #[test]
+    fn test_newton_command() {
+        let mut state = AppState::default();
+
+        // `newton roots 1,-1,i,-i` should return Ok and set a degree 4 polynomial
+        execute_newton(&mut state, vec!["roots", "1,-1,i,-i"]).unwrap();
+        assert_eq!(state.render_settings.newton_poly.degree(), 4);
+        assert_eq!(state.render_settings.newton_poly.roots[2].imag(), &1);
+
+        // `newton coefs 1,0,-1` should return Ok and set a degree 2 polynomial
+        execute_newton(&mut state, vec!["coefs", "1,0,-1"]).unwrap();
+        assert_eq!(state.render_settings.newton_poly.degree(), 2);
+
+        // Invalid values, subcommands and degrees should return Err
+        assert!(execute_newton(&mut state, vec!["roots", "1,blah"]).is_err());
+        assert!(execute_newton(&mut state, vec!["blah", "1,2"]).is_err());
+        assert!(execute_newton(&mut state, vec!["coefs", "3"]).is_err());
+        assert!(execute_newton(&mut state, vec!["roots", &["1"; 17].join(",")]).is_err());
+
+        // The polynomial should have remained the same
+        assert_eq!(state.render_settings.newton_poly.degree(), 2);
+    }
+}
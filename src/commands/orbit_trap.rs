@@ -0,0 +1,149 @@
+use super::Command;
+use crate::{
+    frac_logic::{orbit_trap::get_trap_shape_by_name, TrapShape},
+    helpers::{
+        coloring_modes, markup::esc, polynomial::parse_complex, ColoringMode, NativeComplex,
+    },
+    AppState,
+};
+use strum::IntoEnumIterator;
+
+pub(crate) fn execute_orbit_trap(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let trap = &mut state.render_settings.orbit_trap;
+
+    if args.is_empty() {
+        let msg = format!(
+            "Current orbit trap: <acc {}>, center: <acc {}>, size: <acc {}>, angle: <acc {}°>\nAvailable shapes: {}",
+            trap.shape,
+            esc(trap.format_center()),
+            trap.size,
+            trap.angle,
+            TrapShape::iter()
+                .map(|shape| format!("<acc {shape}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        state.log_raw(msg);
+        return Ok(());
+    }
+
+    if args.len() == 1 {
+        trap.shape = get_trap_shape_by_name(args[0]).ok_or(format!(
+            "Could not find orbit trap shape with name: <command {}>",
+            esc(args[0])
+        ))?;
+        let shape = trap.shape;
+        // Selecting a shape also enables the orbit trap coloring.
+        state.render_settings.coloring_mode_index = coloring_modes()
+            .iter()
+            .position(|mode| *mode == ColoringMode::OrbitTrap)
+            .unwrap();
+        state.prevent_canvas_var_hidden();
+        state.log_success(format!(
+            "Successfully selected the <acc {shape}> orbit trap."
+        ));
+        state.request_redraw();
+        return Ok(());
+    }
+
+    let value = args[1];
+    let parse_number = || {
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or(format!(
+                "Please provide a valid number, got <acc {}>.",
+                esc(value)
+            ))
+    };
+    match args[0] {
+        "center" => trap.center = NativeComplex::from_rug(&parse_complex(value)?),
+        "size" => {
+            let size = parse_number()?;
+            if size <= 0.0 {
+                return Err("The size of the orbit trap must be positive.".to_string());
+            }
+            trap.size = size;
+        }
+        "angle" => {
+            trap.angle = 0.0;
+            trap.increment_angle(parse_number()?);
+        }
+        param => {
+            return Err(format!(
+                "Expected <command center>, <command size> or <command angle>, got <command {}>",
+                esc(param)
+            ))
+        }
+    }
+    state.log_success(format!(
+        "Orbit trap {} successfully set to <acc {}>.",
+        args[0],
+        esc(value)
+    ));
+    state.request_redraw();
+    Ok(())
+}
+
+pub(crate) const ORBIT_TRAP: Command = Command {
+    execute: &execute_orbit_trap,
+    name: "orbit_trap",
+    aliases: &["ot"],
+    accepted_arg_count: &[0, 1, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Display the current orbit trap and the available shapes.\n",
+        "<green Usage: <command [shape]>>\n",
+        "Select the shape of the orbit trap, and switch to the <acc OrbitTrap> coloring mode. ",
+        "The available shapes are <acc Point>, <acc Line>, <acc Cross>, <acc Circle> ",
+        "and <acc PickoverStalk>.\n",
+        "<green Usage: <command center [complex]>>\n",
+        "Set the center of the trap, for example <acc 0.5-0.2i>. ",
+        "It can also be selected with the <acc TrapCenter> click mode.\n",
+        "<green Usage: <command size [value]>>\n",
+        "Set the radius of the circle and the width of the stalks. ",
+        "With the other shapes, it is the distance at which the colors fade.\n",
+        "<green Usage: <command angle [degrees]>>\n",
+        "Set the angle of the line and of the cross.\n",
+        "In the <acc OrbitTrap> coloring mode (cycled with <acc m> on the canvas), ",
+        "each point is colored by the minimum distance between its orbit and the trap. ",
+        "The size and the angle can also be changed from the canvas with <acc t> and <acc +>/<acc ->.",
+    )),
+    basic_desc: "Configure the orbit trap used by the orbit trap coloring mode.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_orbit_trap;
+    use crate::{
+        frac_logic::TrapShape,
+        helpers::{ColoringMode, NativeComplex},
+        AppState,
+    };
+
+    #[test]
+    fn test_orbit_trap_command() {
+        let mut state = AppState::default();
+
+        execute_orbit_trap(&mut state, vec!["circ"]).unwrap();
+        assert_eq!(state.render_settings.orbit_trap.shape, TrapShape::Circle);
+        assert_eq!(
+            state.render_settings.coloring_mode(),
+            ColoringMode::OrbitTrap
+        );
+
+        execute_orbit_trap(&mut state, vec!["center", "0.5-2i"]).unwrap();
+        assert_eq!(
+            state.render_settings.orbit_trap.center,
+            NativeComplex::new(0.5, -2.0)
+        );
+        execute_orbit_trap(&mut state, vec!["angle", "-90"]).unwrap();
+        assert_eq!(state.render_settings.orbit_trap.angle, 270.0);
+
+        assert!(execute_orbit_trap(&mut state, vec!["square"]).is_err());
+        assert!(execute_orbit_trap(&mut state, vec!["size", "-1"]).is_err());
+        assert!(execute_orbit_trap(&mut state, vec!["radius", "1"]).is_err());
+        assert_eq!(state.render_settings.orbit_trap.size, 0.5);
+    }
+}
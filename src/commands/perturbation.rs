@@ -0,0 +1,27 @@
+use super::Command;
+use crate::{helpers::flip_bool, AppState};
+
+pub(crate) fn execute_perturbation(state: &mut AppState, _args: Vec<&str>) -> Result<(), String> {
+    flip_bool(&mut state.render_settings.perturbation);
+    state.log_info(if state.render_settings.perturbation {
+        "Perturbation enabled for deep zooms in the Mandelbrot set."
+    } else {
+        "Perturbation disabled, all the points will be computed with arbitrary precision."
+    });
+    state.request_redraw();
+    Ok(())
+}
+pub(crate) const PERTURBATION: Command = Command {
+    execute: &execute_perturbation,
+    name: "perturbation",
+    aliases: &["pt"],
+    accepted_arg_count: &[0],
+    detailed_desc: Some(concat!(
+        "When zooming deep enough in the Mandelbrot set in CPU mode (cell size below <acc 1e-13>), ",
+        "a single reference point is computed with arbitrary precision, and the other points ",
+        "are computed as small differences from it, which is much faster. ",
+        "The points for which the approximation fails are still computed with arbitrary precision.\n",
+        "Perturbation is only used with the default starting term and an integer exponent.",
+    )),
+    basic_desc: "Toggle the use of perturbation theory for deep zooms in the Mandelbrot set.",
+};
@@ -0,0 +1,104 @@
+use super::Command;
+use crate::{helpers::markup::esc, AppState};
+
+/// The coarsest preview has at least one point for `MAX_REFINEMENT_FACTOR`² canvas points.
+const MAX_REFINEMENT_FACTOR: i32 = 32;
+
+/// Returns the given refinement levels as a readable list.
+fn levels_desc(refinement: &[i32]) -> String {
+    if refinement.is_empty() {
+        return "<acc disabled>".to_string();
+    }
+    refinement
+        .iter()
+        .map(|factor| format!("<acc 1/{factor}>"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(crate) fn execute_refinement(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    if args.is_empty() {
+        state.log_info_title(
+            "Refinement Levels",
+            format!(
+                "Previews rendered before the canvas in CPU mode: {}",
+                levels_desc(&state.render_settings.refinement)
+            ),
+        );
+        return Ok(());
+    }
+
+    let refinement = if args == ["off"] {
+        Vec::new()
+    } else {
+        let mut factors = args
+            .iter()
+            .map(|arg| {
+                let factor = arg
+                    .parse::<i32>()
+                    .map_err(|err| format!("Please provide valid integers: {}", esc(err)))?;
+                if !(2..=MAX_REFINEMENT_FACTOR).contains(&factor) {
+                    return Err(format!(
+                        "The factors must be between 2 and {MAX_REFINEMENT_FACTOR}."
+                    ));
+                }
+                Ok(factor)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        // The previews are rendered from the coarsest to the finest.
+        factors.sort_unstable_by(|a, b| b.cmp(a));
+        factors.dedup();
+        factors
+    };
+
+    state.log_success(format!(
+        "Refinement levels set to {}.",
+        levels_desc(&refinement)
+    ));
+    state.render_settings.refinement = refinement;
+    Ok(())
+}
+
+pub(crate) const REFINEMENT: Command = Command {
+    execute: &execute_refinement,
+    name: "refinement",
+    aliases: &["rf"],
+    accepted_arg_count: &[0, 1, 2, 3, 4],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Display the current refinement levels.\n",
+        "<green Usage: <command [factor] ...>>\n",
+        "Set up to 4 refinement levels, as factors between 2 and 32 dividing the resolution. ",
+        "The default levels are <acc 8 2>.\n",
+        "<green Usage: <command off>>\n",
+        "Disable the refinement, the canvas is then displayed once it is fully rendered.\n",
+        "In CPU mode, each time the canvas is rendered, coarse previews are rendered first with ",
+        "the resolution divided by each factor, from the coarsest one, and displayed pixel-doubled ",
+        "until the next one is available. Moving or zooming again cancels the remaining passes. ",
+        "The previews make the exploration more interactive, at the cost of a slightly longer ",
+        "complete render: a 1/2 preview computes a quarter of the points of the canvas.",
+    )),
+    basic_desc: "Set the coarse previews rendered before the canvas in CPU mode.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_refinement;
+    use crate::AppState;
+
+    #[test]
+    fn test_refinement_command() {
+        let mut state = AppState::default();
+        assert_eq!(state.render_settings.refinement, vec![8, 2]);
+
+        execute_refinement(&mut state, vec!["2", "16", "4", "4"]).unwrap();
+        assert_eq!(state.render_settings.refinement, vec![16, 4, 2]);
+        execute_refinement(&mut state, vec!["off"]).unwrap();
+        assert!(state.render_settings.refinement.is_empty());
+
+        assert!(execute_refinement(&mut state, vec!["1"]).is_err());
+        assert!(execute_refinement(&mut state, vec!["4", "64"]).is_err());
+        assert!(execute_refinement(&mut state, vec!["4", "off"]).is_err());
+        assert!(state.render_settings.refinement.is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use super::Command;
+use crate::{
+    app::checkpoint::{unfinished_jobs, UnfinishedJob},
+    helpers::markup::esc,
+    AppState,
+};
+
+/// Returns the unfinished jobs of the checkpoints directory,
+/// except the ones that are running or already queued.
+fn resumable_jobs(state: &AppState) -> Vec<UnfinishedJob> {
+    let mut jobs = unfinished_jobs(&state.checkpoints_dir);
+    jobs.retain(|job| {
+        !state
+            .running_checkpoints()
+            .any(|dir| *dir == job.checkpoint.dir)
+            && !state
+                .requested_jobs
+                .iter()
+                .any(|capture| capture.checkpoint.as_ref() == Some(&job.checkpoint))
+    });
+    jobs
+}
+
+/// Returns the jobs selected by an index or by "all".
+fn select_jobs(jobs: Vec<UnfinishedJob>, arg: &str) -> Result<Vec<UnfinishedJob>, String> {
+    if arg == "all" {
+        return Ok(jobs);
+    }
+    let index = arg
+        .parse::<usize>()
+        .ok()
+        .filter(|index| *index < jobs.len())
+        .ok_or(format!(
+            "<acc {}> is not the index of an unfinished job, see <command resume>.",
+            esc(arg)
+        ))?;
+    Ok(jobs.into_iter().skip(index).take(1).collect())
+}
+
+pub(crate) fn execute_resume(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let jobs = resumable_jobs(state);
+
+    if args.is_empty() {
+        if !state.stopping_jobs.is_empty() {
+            state.log_info(format!(
+                "<acc {}> cancelled job(s) are still stopping, they can be resumed once they stopped.",
+                state.stopping_jobs.len()
+            ));
+        }
+        if jobs.is_empty() {
+            state.log_info("No unfinished capture job was found.");
+        } else {
+            let list = jobs
+                .iter()
+                .enumerate()
+                .map(|(i, job)| format!("<acc {i}>: {}", job.describe()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            state.log_info_title("Unfinished capture jobs", list);
+        }
+        return Ok(());
+    }
+
+    if args[0] == "remove" {
+        let jobs = select_jobs(jobs, args.get(1).unwrap_or(&"all"))?;
+        for job in &jobs {
+            job.checkpoint.remove()?;
+        }
+        state.log_success(format!("<acc {}> unfinished job(s) removed.", jobs.len()));
+        return Ok(());
+    }
+    if args.len() > 1 {
+        return Err(format!(
+            "Unknown argument: <acc {}>, see <command help resume>.",
+            esc(args[0])
+        ));
+    }
+
+    let jobs = select_jobs(jobs, args[0])?;
+    let count = jobs.len();
+    for job in jobs {
+        let capture = job.restore(state)?;
+        state.requested_jobs.push(capture);
+    }
+    state.log_success(format!(
+        "<acc {count}> capture job(s) added to the queue, their rendered bands will not be rendered again."
+    ));
+    Ok(())
+}
+
+pub(crate) const RESUME: Command = Command {
+    execute: &execute_resume,
+    name: "resume",
+    aliases: &["res"],
+    accepted_arg_count: &[0, 1, 2],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "List the unfinished capture jobs, which were stopped or interrupted when the ",
+        "application was closed.\n",
+        "<green Usage: <command [index|all]>>\n",
+        "Add the selected jobs to the queue, they continue where they left off.\n",
+        "<green Usage: <command remove [?index|all]>>\n",
+        "Delete the checkpoints of the selected jobs, or of all of them.\n",
+        "The captures regularly save their rendered bands to the <acc .rsfrac_jobs> directory, ",
+        "and the queued captures are saved when the application is closed.",
+    )),
+    basic_desc: "Continue the unfinished capture jobs.",
+};
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use super::execute_resume;
+    use crate::{commands::capture::execute_capture, AppState};
+
+    #[test]
+    fn test_resume() {
+        let mut state = AppState {
+            checkpoints_dir: temp_dir().join(format!("rsfrac_resume_{}", std::process::id())),
+            ..Default::default()
+        };
+        execute_resume(&mut state, vec![]).unwrap();
+        assert!(execute_resume(&mut state, vec!["0"]).is_err());
+
+        execute_capture(&mut state, vec!["40", "30", "first"]).unwrap();
+        state.render_settings.perturbation = false;
+        state.render_settings.subdivision.captures = true;
+        execute_capture(&mut state, vec!["40", "30", "second"]).unwrap();
+        state.save_requested_jobs();
+        assert!(state.requested_jobs.is_empty());
+        state.render_settings.perturbation = true;
+        state.render_settings.subdivision.captures = false;
+
+        execute_resume(&mut state, vec![]).unwrap();
+        execute_resume(&mut state, vec!["1"]).unwrap();
+        assert_eq!(state.requested_jobs[0].filename(), "second");
+        // The settings which are not in the state files are restored too.
+        assert!(!state.requested_jobs[0].rs.perturbation);
+        assert!(state.requested_jobs[0].rs.subdivision.captures);
+        // The queued job can not be resumed twice.
+        assert!(execute_resume(&mut state, vec!["1"]).is_err());
+        assert!(execute_resume(&mut state, vec!["0", "1"]).is_err());
+
+        execute_resume(&mut state, vec!["remove", "0"]).unwrap();
+        assert!(execute_resume(&mut state, vec!["0"]).is_err());
+        state.requested_jobs[0]
+            .checkpoint
+            .as_ref()
+            .unwrap()
+            .remove()
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&state.checkpoints_dir);
+    }
+}
@@ -0,0 +1,75 @@
+use super::Command;
+use crate::{helpers::flip_bool, AppState};
+
+pub(crate) fn execute_subdivision(state: &mut AppState, args: Vec<&str>) -> Result<(), String> {
+    let subdivision = &mut state.render_settings.subdivision;
+    match args.first() {
+        None => {
+            flip_bool(&mut subdivision.canvas);
+            let enabled = subdivision.canvas;
+            state.log_info(if enabled {
+                "Subdivision enabled for the canvas in CPU mode."
+            } else {
+                "Subdivision disabled for the canvas, all the points will be computed."
+            });
+            state.request_redraw();
+        }
+        Some(&"capture") => {
+            flip_bool(&mut subdivision.captures);
+            let enabled = subdivision.captures;
+            state.log_info(if enabled {
+                "Subdivision enabled for the captures in CPU mode, some points will be approximated."
+            } else {
+                "Subdivision disabled for the captures, all the points will be computed."
+            });
+        }
+        Some(_) => {
+            return Err(format!(
+                "Unexpected argument, see <command help {}>.",
+                SUBDIVISION.name
+            ))
+        }
+    }
+    Ok(())
+}
+
+pub(crate) const SUBDIVISION: Command = Command {
+    execute: &execute_subdivision,
+    name: "subdivision",
+    aliases: &["sd"],
+    accepted_arg_count: &[0, 1],
+    detailed_desc: Some(concat!(
+        "<green Usage: <command [no args]>>\n",
+        "Toggle the subdivision for the canvas, enabled by default.\n",
+        "<green Usage: <command capture>>\n",
+        "Toggle the subdivision for the captures, disabled by default.\n",
+        "In CPU mode, the canvas is split in rectangles, and only the border of each ",
+        "rectangle is computed. When all the border points have the same color, ",
+        "the inside of the rectangle is filled with it, otherwise the rectangle is split in two ",
+        "(Mariani–Silver algorithm). This is much faster, but the filled points are approximated: ",
+        "small details surrounded by a uniform area may be missed. ",
+        "The percentage of filled points is displayed on the canvas.",
+    )),
+    basic_desc: "Toggle the Mariani–Silver subdivision of the CPU renders.",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::execute_subdivision;
+    use crate::AppState;
+
+    #[test]
+    fn test_subdivision_command() {
+        let mut state = AppState::default();
+        assert!(state.render_settings.subdivision.canvas);
+        assert!(!state.render_settings.subdivision.captures);
+
+        execute_subdivision(&mut state, vec![]).unwrap();
+        execute_subdivision(&mut state, vec!["capture"]).unwrap();
+        assert!(!state.render_settings.subdivision.canvas);
+        assert!(state.render_settings.subdivision.captures);
+
+        assert!(execute_subdivision(&mut state, vec!["canvas"]).is_err());
+        assert!(state.render_settings.subdivision.captures);
+    }
+}
@@ -18,9 +18,12 @@ impl<'a> Canvas<'a> {
         "Color[c]",
         "Frac[f]",
         "Void[v]",
+        "Coloring[m]",
+        "Interior[g]",
         "Rst[r]",
         "HSL[n]",
         "Panel[b]",
+        "Jobs[J]",
     ];
     pub(crate) fn new(state: &'a AppState, points: &'a CanvasPoints) -> Self {
         Self { state, points }
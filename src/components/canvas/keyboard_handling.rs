@@ -1,12 +1,12 @@
 use ratatui::crossterm::event::KeyCode;
-use rug::Float;
-use std::ops::{AddAssign, SubAssign};
 
 use crate::{
     app_state::hsl_settings::MAX_HSL_VALUE,
     colors,
+    commands::exponent::EXPONENT_STEP,
+    frac_logic::orbit_trap::TRAP_ANGLE_STEP,
     fractals::FRACTALS,
-    helpers::{decrement_wrap, increment_wrap, void_fills, ZoomDirection},
+    helpers::{coloring_modes, decrement_wrap, increment_wrap, void_fills, ZoomDirection},
     App,
 };
 
@@ -16,56 +16,16 @@ impl Canvas<'_> {
         match code {
             // When H is pressed move the position of the canvas
             // to the left by r times the cell size.
-            KeyCode::Char('h') | KeyCode::Left => {
-                app.app_state
-                    .render_settings
-                    .pos
-                    .mut_real()
-                    .sub_assign(Float::with_val(
-                        app.app_state.render_settings.prec,
-                        &app.app_state.render_settings.cell_size * app.app_state.move_dist,
-                    ));
-                app.app_state.request_redraw();
-            }
+            KeyCode::Char('h') | KeyCode::Left => app.app_state.pan(-app.app_state.move_dist, 0),
             // When L is pressed move the position of the canvas
             // to the right by n times the cell size.
-            KeyCode::Char('l') | KeyCode::Right => {
-                app.app_state
-                    .render_settings
-                    .pos
-                    .mut_real()
-                    .add_assign(Float::with_val(
-                        app.app_state.render_settings.prec,
-                        &app.app_state.render_settings.cell_size * app.app_state.move_dist,
-                    ));
-                app.app_state.request_redraw();
-            }
+            KeyCode::Char('l') | KeyCode::Right => app.app_state.pan(app.app_state.move_dist, 0),
             // When J is pressed move the position of the canvas
             // down by n times the cell size.
-            KeyCode::Char('j') | KeyCode::Down => {
-                app.app_state
-                    .render_settings
-                    .pos
-                    .mut_imag()
-                    .sub_assign(Float::with_val(
-                        app.app_state.render_settings.prec,
-                        &app.app_state.render_settings.cell_size * app.app_state.move_dist,
-                    ));
-                app.app_state.request_redraw();
-            }
+            KeyCode::Char('j') | KeyCode::Down => app.app_state.pan(0, -app.app_state.move_dist),
             // When K is pressed move the position of the canvas
             // up by n times the cell size.
-            KeyCode::Char('k') | KeyCode::Up => {
-                app.app_state
-                    .render_settings
-                    .pos
-                    .mut_imag()
-                    .add_assign(Float::with_val(
-                        app.app_state.render_settings.prec,
-                        &app.app_state.render_settings.cell_size * app.app_state.move_dist,
-                    ));
-                app.app_state.request_redraw();
-            }
+            KeyCode::Char('k') | KeyCode::Up => app.app_state.pan(0, app.app_state.move_dist),
             // When S is pressed increase the cell size, which will zoom out of the canvas
             KeyCode::Char('s') => {
                 app.app_state.zoom(ZoomDirection::Out);
@@ -78,6 +38,8 @@ impl Canvas<'_> {
             }
             // When b is pressed toggle the side panel
             KeyCode::Char('b') => app.toggle_sidepanel(),
+            // When J is pressed toggle the job panel
+            KeyCode::Char('J') => app.app_state.toggle_job_panel(),
             // When D is pressed decrease the cell size, which will zoom into the canvas
             KeyCode::Char('d') => {
                 app.app_state.zoom(ZoomDirection::In);
@@ -144,6 +106,24 @@ impl Canvas<'_> {
                         &mut app.app_state.render_settings.hsl_settings.smoothness,
                         MAX_HSL_VALUE,
                     ),
+
+                    SelectedVariable::Exponent => {
+                        app.app_state
+                            .render_settings
+                            .increment_exponent(-EXPONENT_STEP);
+                        app.app_state.request_redraw();
+                    }
+                    SelectedVariable::TrapSize => {
+                        app.app_state.render_settings.orbit_trap.scale_size(false);
+                        app.app_state.request_redraw();
+                    }
+                    SelectedVariable::TrapAngle => {
+                        app.app_state
+                            .render_settings
+                            .orbit_trap
+                            .increment_angle(-TRAP_ANGLE_STEP);
+                        app.app_state.request_redraw();
+                    }
                 }
                 app.app_state.request_repaint();
             }
@@ -169,6 +149,23 @@ impl Canvas<'_> {
                         &mut app.app_state.render_settings.hsl_settings.hue_offset,
                         MAX_HSL_VALUE,
                     ),
+                    SelectedVariable::Exponent => {
+                        app.app_state
+                            .render_settings
+                            .increment_exponent(EXPONENT_STEP);
+                        app.app_state.request_redraw();
+                    }
+                    SelectedVariable::TrapSize => {
+                        app.app_state.render_settings.orbit_trap.scale_size(true);
+                        app.app_state.request_redraw();
+                    }
+                    SelectedVariable::TrapAngle => {
+                        app.app_state
+                            .render_settings
+                            .orbit_trap
+                            .increment_angle(TRAP_ANGLE_STEP);
+                        app.app_state.request_redraw();
+                    }
                 }
                 app.app_state.request_repaint();
             }
@@ -190,6 +187,29 @@ impl Canvas<'_> {
                 );
                 app.app_state.request_repaint();
             }
+            // Cycle through the coloring modes
+            KeyCode::Char('m') => {
+                let rs = &mut app.app_state.render_settings;
+                let computed = (rs.use_distance_estimation(), rs.trap_tracker().is_some());
+                increment_wrap(&mut rs.coloring_mode_index, coloring_modes().len());
+                // The distances and the orbit traps are only computed in their coloring modes,
+                // and the rectangles filled by the subdivision depend on the coloring mode.
+                if computed != (rs.use_distance_estimation(), rs.trap_tracker().is_some())
+                    || rs.subdivision.canvas
+                {
+                    app.app_state.request_redraw();
+                } else {
+                    app.app_state.request_repaint();
+                }
+                // The trap parameters are only visible in orbit trap mode.
+                app.app_state.prevent_canvas_var_hidden();
+            }
+            // Cycle through the interior coloring modes
+            KeyCode::Char('g') => {
+                let interior = &mut app.app_state.render_settings.interior;
+                interior.mode = interior.mode.next_mode();
+                app.app_state.request_redraw();
+            }
             // Increment the maximum divergence
             KeyCode::Char('o') => app.app_state.increment_max_iter(10),
             // Decrement the maximum divergence
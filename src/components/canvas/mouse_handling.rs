@@ -2,7 +2,8 @@ use ratatui::crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 
 use crate::{
     app_state::ClickMode,
-    helpers::{Focus, ZoomDirection},
+    frac_logic::NO_BASIN,
+    helpers::{Focus, NativeComplex, ZoomDirection},
     App,
 };
 
@@ -59,6 +60,11 @@ impl Canvas<'_> {
                     .to_f32();
                 app.app_state.request_redraw();
             }
+            ClickMode::TrapCenter => {
+                app.app_state.render_settings.orbit_trap.center =
+                    NativeComplex::from_rug(&app.app_state.render_settings.coord_to_c(canvas_pos));
+                app.app_state.request_redraw();
+            }
             ClickMode::Info => {
                 let point = app.app_state.render_settings.coord_to_c(canvas_pos.clone());
 
@@ -79,10 +85,15 @@ impl Canvas<'_> {
                 app.app_state.log_info_title(
                     "Click Info",
                     format!(
-                        "Real: <acc {}>\nImag: <acc {}>\nDiverg: <acc {}>",
+                        "Real: <acc {}>\nImag: <acc {}>\nDiverg: <acc {}>{}",
                         point.real().to_f32(),
                         point.imag().to_f32(),
-                        diverg,
+                        diverg.iter,
+                        if diverg.basin == NO_BASIN {
+                            String::new()
+                        } else {
+                            format!("\nBasin: <acc {}>", diverg.basin)
+                        },
                     ),
                 )
             }
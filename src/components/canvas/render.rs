@@ -7,7 +7,8 @@ use ratatui::{
     widgets::{canvas::Points, Block, Widget},
 };
 
-use crate::helpers::{void_fills, Focus};
+use crate::frac_logic::InteriorMode;
+use crate::helpers::{coloring_modes, void_fills, ColoringMode, Focus};
 
 use super::{Canvas, SelectedVariable};
 
@@ -35,7 +36,7 @@ impl Widget for Canvas<'_> {
             ratatui::style::Color::DarkGray
         });
 
-        let canvas_block = Block::bordered()
+        let mut canvas_block = Block::bordered()
             .style(border_style)
             .title_bottom(
                 Line::from(format!(
@@ -44,6 +45,13 @@ impl Widget for Canvas<'_> {
                 ))
                 .right_aligned(),
             )
+            .title_bottom(
+                Line::from(format!(
+                    "Coloring[{}]",
+                    coloring_modes()[self.state.render_settings.coloring_mode_index]
+                ))
+                .right_aligned(),
+            )
             .title_bottom(
                 Line::from(format!("Pts[{}]", self.state.render_settings.point_count()))
                     .left_aligned(),
@@ -93,6 +101,17 @@ impl Widget for Canvas<'_> {
             .title_bottom(
                 Line::from(format!("HighDiv[{}]", self.state.stats.highest_diverg)).left_aligned(),
             )
+            .title_bottom(
+                Line::from(vec![
+                    "Exp[".into(),
+                    red_if(
+                        self.state.render_settings.exponent.to_string(),
+                        self.state.is_var_selected(SelectedVariable::Exponent),
+                    ),
+                    "]".into(),
+                ])
+                .right_aligned(),
+            )
             .title_top(
                 Line::from(format!("MxDiv[{}]", self.state.render_settings.max_iter))
                     .right_aligned(),
@@ -107,7 +126,11 @@ impl Widget for Canvas<'_> {
             .title_top(
                 Line::from(format!(
                     "{}[x{:.3e}]",
-                    self.state.render_settings.get_frac_obj().name,
+                    if self.state.render_settings.use_density() {
+                        self.state.render_settings.buddhabrot.mode.to_string()
+                    } else {
+                        self.state.render_settings.get_frac_obj().name.to_string()
+                    },
                     self.state.render_settings.get_zoom()
                 ))
                 .left_aligned()
@@ -130,6 +153,53 @@ impl Widget for Canvas<'_> {
             .title_style(Style::default().fg(ratatui::style::Color::White))
             .title_alignment(Alignment::Center);
 
+        if self.state.render_settings.coloring_mode() == ColoringMode::OrbitTrap {
+            let trap = &self.state.render_settings.orbit_trap;
+            canvas_block = canvas_block.title_bottom(
+                Line::from(vec![
+                    format!("Trap[{},", trap.shape).into(),
+                    red_if(
+                        format!("{:.3}", trap.size),
+                        self.state.is_var_selected(SelectedVariable::TrapSize),
+                    ),
+                    ",".into(),
+                    red_if(
+                        format!("{}°", trap.angle),
+                        self.state.is_var_selected(SelectedVariable::TrapAngle),
+                    ),
+                    "]".into(),
+                ])
+                .right_aligned(),
+            );
+        }
+        let interior = &self.state.render_settings.interior;
+        if interior.mode != InteriorMode::Flat {
+            canvas_block = canvas_block.title_bottom(
+                Line::from(format!(
+                    "Interior[{},{}]",
+                    interior.mode,
+                    self.state.render_settings.get_interior_palette().name
+                ))
+                .right_aligned(),
+            );
+        }
+        if let Some(progress) = self.state.canvas_render_progress {
+            canvas_block = canvas_block.title_top(
+                Line::from(format!("Rendering[{progress}%]"))
+                    .left_aligned()
+                    .style(Style::default().fg(ratatui::style::Color::Yellow)),
+            );
+        }
+        if let Some(fill) = self.state.stats.subdivision_fill {
+            canvas_block = canvas_block
+                .title_bottom(Line::from(format!("Filled[{:.0}%]", fill * 100.0)).left_aligned());
+        }
+        if let Some((hits, misses)) = self.state.stats.cache_usage {
+            canvas_block = canvas_block.title_bottom(
+                Line::from(format!("Cache[{hits}/{}]", hits + misses)).left_aligned(),
+            );
+        }
+
         let canvas_wid = ratatui::widgets::canvas::Canvas::default()
             .marker(Marker::HalfBlock)
             .block(canvas_block)
@@ -8,6 +8,9 @@ pub(crate) enum SelectedVariable {
     HueOffset,
     HSLSat,
     HSLLum,
+    Exponent,
+    TrapSize,
+    TrapAngle,
 }
 
 impl PartialEq<usize> for SelectedVariable {
@@ -0,0 +1,253 @@
+//! Contains the `JobPanel` widget, displayed over the canvas.
+
+use std::time::Duration;
+
+use humantime::format_duration;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, MouseEvent},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Widget, Wrap},
+};
+use tui_markup::compile_with;
+
+use crate::{
+    app_state::jobs::JobIndex,
+    helpers::{markup::get_ratatui_generator, Focus},
+    App, AppState,
+};
+
+/// The number of characters of the progress bars.
+const PROGRESS_BAR_WIDTH: usize = 12;
+
+pub(crate) struct JobPanel<'a> {
+    state: &'a AppState,
+}
+
+/// Formats a duration without the fractions of seconds.
+fn format_secs(duration: Duration) -> String {
+    format_duration(Duration::from_secs(duration.as_secs())).to_string()
+}
+
+/// Returns a progress bar followed by the percentage.
+fn progress_bar(done: i32, total: i32) -> String {
+    let percent = (done as i64 * 100 / total.max(1) as i64).clamp(0, 100) as usize;
+    let filled = percent * PROGRESS_BAR_WIDTH / 100;
+    format!(
+        "{}{} {percent}%",
+        "█".repeat(filled),
+        "░".repeat(PROGRESS_BAR_WIDTH - filled)
+    )
+}
+
+impl<'a> JobPanel<'a> {
+    pub(crate) const FOOTER_TEXT: &'static [&'static str] = &[
+        "Select[arrows/jk]",
+        "Cancel[x]",
+        "MoveInQueue[K/J]",
+        "Duplicate[d]",
+        "Close[Esc]",
+    ];
+    pub(crate) fn new(state: &'a AppState) -> Self {
+        Self { state }
+    }
+    pub(crate) fn handle_mouse_event(app: &mut AppState, _event: MouseEvent) {
+        app.focused = Focus::JobPanel;
+    }
+    pub(crate) fn handle_event(app: &mut App, code: KeyCode) {
+        let state = &mut app.app_state;
+        let selected = state.selected_job;
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => state.selected_job = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                state.selected_job = (selected + 1).min(state.job_count().saturating_sub(1))
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+                let res = state.cancel_job(selected);
+                state.handle_res(res);
+            }
+            KeyCode::Char('K') | KeyCode::Char('J') => {
+                let to = if code == KeyCode::Char('K') {
+                    selected.saturating_sub(1)
+                } else {
+                    selected + 1
+                };
+                if state.move_job(selected, to).is_ok() {
+                    state.selected_job = to;
+                }
+            }
+            // The size of the copy is entered in the command input.
+            KeyCode::Char('d') if selected < state.job_count() => {
+                state.command_input.0 = format!("jobs duplicate {selected} ").into();
+                if app.hide_sidepanel {
+                    app.toggle_sidepanel();
+                }
+                app.app_state.focused = Focus::Input;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => state.toggle_job_panel(),
+            _ => {}
+        }
+    }
+
+    /// Returns the row of each job, in the order of their indices.
+    fn rows(&self) -> Vec<Row<'a>> {
+        let state = self.state;
+        let running = state.parallel_jobs.iter().map(|job| {
+            let cells = [
+                "Running".to_string(),
+                job.filename.clone(),
+                format!("{}x{}", job.size.x, job.size.y),
+                job.rs_copy.get_frac_obj().name.to_string(),
+                progress_bar(job.rendered_lines, job.total_lines()),
+                format_secs(job.started.elapsed()),
+                job.eta()
+                    .map(format_secs)
+                    .unwrap_or("Estimating...".to_string()),
+            ];
+            (Color::LightBlue, cells)
+        });
+        let queued = state.requested_jobs.iter().map(|job| {
+            let cells = [
+                "Queued".to_string(),
+                job.name.clone().unwrap_or("<timestamp>".to_string()),
+                format!("{}x{}", job.size.x, job.size.y),
+                job.rs.get_frac_obj().name.to_string(),
+                if job.checkpoint.is_some() {
+                    "Resumed".to_string()
+                } else {
+                    String::new()
+                },
+                String::new(),
+                String::new(),
+            ];
+            (Color::Yellow, cells)
+        });
+        let finished = state.finished_jobs.iter().map(|job| {
+            let cells = [
+                if job.error.is_some() {
+                    "Failed"
+                } else {
+                    "Finished"
+                }
+                .to_string(),
+                job.filename.clone(),
+                format!("{}x{}", job.size.x, job.size.y),
+                job.frac_name.to_string(),
+                if job.error.is_some() {
+                    String::new()
+                } else {
+                    progress_bar(1, 1)
+                },
+                format_secs(job.duration),
+                String::new(),
+            ];
+            let color = if job.error.is_some() {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            (color, cells)
+        });
+        running
+            .chain(queued)
+            .chain(finished)
+            .enumerate()
+            .map(|(i, (color, cells))| {
+                let row = Row::new(
+                    [Cell::from(i.to_string())]
+                        .into_iter()
+                        .chain(cells.into_iter().map(Cell::from)),
+                )
+                .style(Style::default().fg(color));
+                if i == state.selected_job {
+                    row.reversed()
+                } else {
+                    row
+                }
+            })
+            .collect()
+    }
+}
+
+impl Widget for JobPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block_style = Style::default().fg(if self.state.focused == Focus::JobPanel {
+            Color::LightBlue
+        } else {
+            Color::DarkGray
+        });
+        let block = Block::new()
+            .title("Job Panel")
+            .style(block_style)
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        let [table_area, details_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(inner);
+
+        if self.state.job_count() == 0 {
+            Paragraph::new("No capture job, use the capture command to start one.")
+                .style(Style::default().reset())
+                .render(table_area, buf);
+        } else {
+            let header = Row::new([
+                "#", "Status", "Name", "Size", "Fractal", "Progress", "Elapsed", "ETA",
+            ])
+            .style(Style::default().reset().bold());
+            let widths = [
+                Constraint::Length(3),
+                Constraint::Length(8),
+                Constraint::Fill(1),
+                Constraint::Length(11),
+                Constraint::Length(16),
+                Constraint::Length(PROGRESS_BAR_WIDTH as u16 + 5),
+                Constraint::Length(10),
+                Constraint::Length(13),
+            ];
+            Table::new(self.rows(), widths)
+                .header(header)
+                .render(table_area, buf);
+        }
+
+        // Display the reason why the selected job failed.
+        let error = match self.state.job_at(self.state.selected_job) {
+            Ok(JobIndex::Finished(i)) => self.state.finished_jobs[i].error.as_deref(),
+            _ => None,
+        };
+        if let Some(error) = error {
+            let text = compile_with(error, get_ratatui_generator()).unwrap_or(error.into());
+            Paragraph::new(text)
+                .style(Style::default().reset())
+                .wrap(Wrap { trim: true })
+                .render(details_area, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    use super::{progress_bar, JobPanel};
+    use crate::{commands::capture::execute_capture, AppState};
+
+    #[test]
+    fn test_job_panel() {
+        assert_eq!(progress_bar(1, 4), "███░░░░░░░░░ 25%");
+
+        let mut state = AppState::default();
+        execute_capture(&mut state, vec!["640", "320", "queued_capture"]).unwrap();
+        let area = Rect::new(0, 0, 120, 10);
+        let mut buf = Buffer::empty(area);
+        JobPanel::new(&state).render(area, &mut buf);
+        let row: String = (0..area.width)
+            .map(|x| buf[(x, 2)].symbol().to_string())
+            .collect();
+        assert!(row.contains("Queued"));
+        assert!(row.contains("queued_capture"));
+        assert!(row.contains("640x320"));
+    }
+}
@@ -3,8 +3,10 @@
 pub(crate) mod canvas;
 mod footer;
 mod input;
+mod job_panel;
 mod log_panel;
 
 pub(crate) use footer::Footer;
 pub(crate) use input::Input;
+pub(crate) use job_panel::JobPanel;
 pub(crate) use log_panel::LogPanel;
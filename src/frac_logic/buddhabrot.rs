@@ -0,0 +1,285 @@
+//! Contains the Buddhabrot and Nebulabrot renderers. Instead of computing the divergence
+//! of each point of the canvas, they sample random points of the plane and accumulate
+//! the terms of their sequences into a density histogram, which is then tone-mapped.
+//! https://en.wikipedia.org/wiki/Buddhabrot
+
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    mpsc::Sender,
+};
+
+use rand::{thread_rng, Rng};
+use ratatui::style::Color;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    app::SlaveMessage,
+    fractals::{in_main_components, MANDELBROT},
+    helpers::{NativeComplex, Vec2},
+};
+
+use super::{gpu_util::SendSlaveMessage, RenderSettings};
+
+type C64 = NativeComplex<f64>;
+
+/// The number of random points sampled per pixel of the canvas preview.
+pub(crate) const PREVIEW_SAMPLES_PER_PIXEL: u32 = 10;
+/// The maximum number of random points sampled per pixel of a capture.
+pub(crate) const MAX_SAMPLES_PER_PIXEL: u32 = 100_000;
+const DEFAULT_SAMPLES_PER_PIXEL: u32 = 20;
+/// The iteration limits of the red, green and blue channels of the Nebulabrot.
+const DEFAULT_NEBULA_LIMITS: [i32; 3] = [5000, 500, 50];
+/// The samples are split in batches, and the progression is reported after each batch.
+const BATCH_COUNT: u64 = 100;
+/// The points are sampled in the square of this half-width, which contains the set.
+const SAMPLING_RADIUS: f64 = 2.0;
+
+/// The renderers that can replace the divergence matrix for the Mandelbrot set.
+#[derive(PartialEq, EnumIter, Debug, Display, Clone, Copy, Deserialize, Serialize)]
+pub(crate) enum DensityMode {
+    Off,
+    /// The density of the escaping orbits.
+    Buddhabrot,
+    /// Three Buddhabrots with different iteration limits, in the RGB channels.
+    Nebulabrot,
+}
+
+/// Returns the density mode which name matches, or `None`.
+pub(crate) fn get_density_mode_by_name(name: &str) -> Option<DensityMode> {
+    DensityMode::iter().find(|mode| {
+        mode.to_string()
+            .to_lowercase()
+            .starts_with(&name.to_lowercase())
+    })
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct BuddhabrotSettings {
+    pub(crate) mode: DensityMode,
+    /// The number of random points sampled per pixel of a capture.
+    pub(crate) samples_per_pixel: u32,
+    /// The iteration limits of the red, green and blue channels of the Nebulabrot.
+    pub(crate) nebula_limits: [i32; 3],
+}
+
+impl Default for BuddhabrotSettings {
+    fn default() -> Self {
+        Self {
+            mode: DensityMode::Off,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            nebula_limits: DEFAULT_NEBULA_LIMITS,
+        }
+    }
+}
+
+/// The number of visits of each pixel by the sampled orbits, for each color channel.
+pub(crate) struct Histogram {
+    size: Vec2<i32>,
+    /// The counts of each channel, line by line starting from the bottom,
+    /// like the divergence matrices.
+    channels: Vec<Vec<u32>>,
+    /// The highest count of each channel, used for tone mapping.
+    max: Vec<u32>,
+}
+
+impl Histogram {
+    fn new(size: &Vec2<i32>, channels: Vec<Vec<u32>>) -> Self {
+        let max = channels
+            .iter()
+            .map(|channel| channel.iter().copied().max().unwrap_or_default())
+            .collect();
+        Self {
+            size: size.clone(),
+            channels,
+            max,
+        }
+    }
+
+    /// Returns the tone-mapped color of a pixel, the lines being counted from the bottom.
+    pub(crate) fn color(&self, x: i32, y: i32) -> Color {
+        let index = (y * self.size.x + x) as usize;
+        let value = |channel: usize| {
+            let max = self.max[channel];
+            if max == 0 {
+                return 0;
+            }
+            // The square root brings out the orbits that are rarely visited.
+            ((self.channels[channel][index] as f64 / max as f64).sqrt() * 255.0) as u8
+        };
+        match self.channels.len() {
+            3 => Color::Rgb(value(0), value(1), value(2)),
+            _ => {
+                let value = value(0);
+                Color::Rgb(value, value, value)
+            }
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Returns true if the selected density mode replaces the divergence matrix.
+    pub(crate) fn use_density(&self) -> bool {
+        self.buddhabrot.mode != DensityMode::Off
+            && self.get_frac_obj().name == MANDELBROT.name
+            // The terms are raised to the exponent with repeated multiplications.
+            && self.exponent.fract() == 0.0
+    }
+
+    /// Returns the iteration limit of each channel of the histogram.
+    fn density_limits(&self) -> Vec<i32> {
+        match self.buddhabrot.mode {
+            DensityMode::Nebulabrot => self.buddhabrot.nebula_limits.to_vec(),
+            _ => vec![self.max_iter],
+        }
+    }
+
+    /// Computes the terms of the sequence of `c`, and returns the number of iterations
+    /// after which it escaped, or `None` if it did not escape after `max_iter` iterations.
+    fn density_orbit(&self, c: C64, max_iter: i32, orbit: &mut Vec<C64>) -> Option<i32> {
+        let exponent = self.exponent as i32;
+        let bailout = self.bailout as f64;
+
+        orbit.clear();
+        let mut z = C64::from_rug(&self.mandel_constant);
+        for n in 1..max_iter {
+            z = match exponent {
+                _ if z.is_zero() => z,
+                2 => z * z,
+                exp => z.powi(exp),
+            } + c;
+            if self.escape_norm.escaped(z.re, z.im, bailout) {
+                return Some(n);
+            }
+            orbit.push(z);
+        }
+        None
+    }
+
+    /// Samples random points of the plane, and accumulates their escaping orbits
+    /// in a histogram of the given size. The progression is sent after each batch.
+    pub(crate) fn get_density_histogram(
+        &self,
+        size: &Vec2<i32>,
+        samples_per_pixel: u32,
+        sender: Option<&Sender<SlaveMessage>>,
+    ) -> Result<Histogram, String> {
+        let limits = self.density_limits();
+        let max_iter = limits.iter().copied().max().unwrap_or_default();
+        let pixel_count = (size.x * size.y) as usize;
+        let channels: Vec<Vec<AtomicU32>> = limits
+            .iter()
+            .map(|_| (0..pixel_count).map(|_| AtomicU32::new(0)).collect())
+            .collect();
+
+        let cell_size = self.cell_size_from_height(size.y).to_f64();
+        let pos = C64::from_rug(&self.pos);
+        // The origin of the plane is at the center of the image.
+        let offset =
+            |value: f64, length: i32| (value / cell_size).round() as i64 + length as i64 / 2;
+        let skip_main_components = self.exponent == 2.0 && self.mandel_constant.is_zero();
+
+        let samples = pixel_count as u64 * samples_per_pixel as u64;
+        for batch in 0..BATCH_COUNT {
+            let batch_samples = samples / BATCH_COUNT + u64::from(batch < samples % BATCH_COUNT);
+            (0..batch_samples).into_par_iter().for_each_init(
+                || (thread_rng(), Vec::with_capacity(max_iter as usize)),
+                |(rng, orbit), _| {
+                    let c = C64::new(
+                        rng.gen_range(-SAMPLING_RADIUS..SAMPLING_RADIUS),
+                        rng.gen_range(-SAMPLING_RADIUS..SAMPLING_RADIUS),
+                    );
+                    if skip_main_components && in_main_components(c) {
+                        return;
+                    }
+                    // Only the escaping orbits are accumulated.
+                    let Some(escape) = self.density_orbit(c, max_iter, orbit) else {
+                        return;
+                    };
+
+                    for z in orbit.iter() {
+                        let x = offset(z.re - pos.re, size.x);
+                        let y = offset(z.im - pos.im, size.y);
+                        if !(0..size.x as i64).contains(&x) || !(0..size.y as i64).contains(&y) {
+                            continue;
+                        }
+                        let index = y as usize * size.x as usize + x as usize;
+                        for (channel, limit) in channels.iter().zip(&limits) {
+                            if escape < *limit {
+                                channel[index].fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                },
+            );
+
+            // This also stops the render if the job was cancelled.
+            sender.send(SlaveMessage::SetMessage(format!(
+                "{} progression: <command {}%>",
+                self.buddhabrot.mode,
+                (batch + 1) * 100 / BATCH_COUNT
+            )))?;
+        }
+
+        Ok(Histogram::new(
+            size,
+            channels
+                .into_iter()
+                .map(|channel| channel.into_iter().map(AtomicU32::into_inner).collect())
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::{Complex, Float};
+
+    use super::*;
+    use crate::frac_logic::CanvasCoords;
+
+    #[test]
+    fn test_buddhabrot() {
+        let mut rs = RenderSettings {
+            max_iter: 50,
+            ..Default::default()
+        };
+        rs.buddhabrot.mode = DensityMode::Buddhabrot;
+        rs.pos = Complex::with_val(rs.prec, (-0.5, 0.0));
+        let size = Vec2::new(40, 31);
+        rs.canvas_size = CanvasCoords::new(size.x, size.y);
+        rs.set_width(Float::with_val(rs.prec, 4.0));
+        assert!(rs.use_density());
+
+        let histogram = rs.get_density_histogram(&size, 20, None).unwrap();
+        assert_eq!(histogram.channels.len(), 1);
+        assert!(histogram.max[0] > 0);
+        // The Buddhabrot is symmetric with respect to the real axis, which is the middle line.
+        let total = |lines: std::ops::Range<usize>| -> u64 {
+            histogram.channels[0][lines.start * 40..lines.end * 40]
+                .iter()
+                .map(|&count| count as u64)
+                .sum()
+        };
+        let (bottom, top) = (total(0..15) as f64, total(16..31) as f64);
+        assert!((bottom - top).abs() < 0.05 * (bottom + top));
+
+        // The main cardioid and the period-2 bulb.
+        assert!(in_main_components(C64::new(0.0, 0.0)));
+        assert!(in_main_components(C64::new(-1.0, 0.1)));
+        assert!(!in_main_components(C64::new(0.3, 0.0)));
+
+        rs.buddhabrot.mode = DensityMode::Nebulabrot;
+        rs.buddhabrot.nebula_limits = [100, 50, 10];
+        let histogram = rs.get_density_histogram(&size, 5, None).unwrap();
+        // The channels with higher limits contain more orbits.
+        assert_eq!(histogram.channels.len(), 3);
+        let sums: Vec<u64> = histogram
+            .channels
+            .iter()
+            .map(|channel| channel.iter().map(|&count| count as u64).sum())
+            .collect();
+        assert!(sums[0] >= sums[1] && sums[1] >= sums[2]);
+    }
+}
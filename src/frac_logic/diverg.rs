@@ -0,0 +1,74 @@
+//! Contains the `Diverg` struct.
+
+/// The value of `Diverg::basin` when the fractal does not converge to an attractor.
+pub(crate) const NO_BASIN: i32 = -1;
+
+/// The result of the iteration of a fractal formula for a single point.
+/// Its memory layout must match the `Diverg` struct of the shaders.
+#[derive(bytemuck::Pod, bytemuck::Zeroable, Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub(crate) struct Diverg {
+    /// The number of iterations before the point diverged (or converged),
+    /// and `-1` if it reached the maximum number of iterations.
+    pub(crate) iter: i32,
+    /// The index of the attractor the point converged to, for fractals
+    /// like Newton's, and `NO_BASIN` for escape-time fractals.
+    pub(crate) basin: i32,
+    /// The modulus of the last term of the sequence, used for smooth coloring.
+    /// It is zero when it is not known, or not meaningful for the fractal.
+    pub(crate) mag: f32,
+    /// The estimated distance between the point and the set, in cells (the size of a pixel),
+    /// when distance estimation is enabled. The fractals return it in the complex plane,
+    /// and the renderers convert it.
+    pub(crate) dist: f32,
+    /// The minimum distance between the orbit of the point and the orbit trap,
+    /// when the orbit trap coloring mode is selected.
+    pub(crate) trap: f32,
+    /// The value used to color the point when it reached the maximum number
+    /// of iterations, when an interior coloring mode is selected.
+    pub(crate) interior: f32,
+}
+
+impl Diverg {
+    /// Create a divergence value without convergence basin.
+    pub(crate) const fn new(iter: i32) -> Self {
+        Self {
+            iter,
+            basin: NO_BASIN,
+            mag: 0.0,
+            dist: 0.0,
+            trap: 0.0,
+            interior: 0.0,
+        }
+    }
+
+    /// Create a divergence value for a point that escaped, with
+    /// the modulus of the last term of its sequence.
+    pub(crate) const fn with_mag(iter: i32, mag: f32) -> Self {
+        Self {
+            iter,
+            basin: NO_BASIN,
+            mag,
+            dist: 0.0,
+            trap: 0.0,
+            interior: 0.0,
+        }
+    }
+
+    /// Create a divergence value for a point that converged to the given basin.
+    pub(crate) const fn with_basin(iter: i32, basin: i32) -> Self {
+        Self {
+            iter,
+            basin,
+            mag: 0.0,
+            dist: 0.0,
+            trap: 0.0,
+            interior: 0.0,
+        }
+    }
+
+    /// Returns true if the point reached the maximum number of iterations.
+    pub(crate) fn is_void(&self) -> bool {
+        self.iter == -1
+    }
+}
@@ -0,0 +1,87 @@
+//! Contains the norms used to decide when the sequence of a point escapes.
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// The norms compared with the bailout. Their order must match `escaped` in the shaders.
+#[derive(PartialEq, EnumIter, Debug, Display, Clone, Copy, Deserialize, Serialize)]
+pub(crate) enum EscapeNorm {
+    /// The modulus, which gives round level sets.
+    Euclidean,
+    /// The largest absolute value of the two parts, which gives square level sets.
+    Max,
+    /// The absolute value of the real part.
+    Real,
+    /// The absolute value of the imaginary part.
+    Imag,
+    /// The sum of the absolute values of the two parts, which gives diamond level sets.
+    Manhattan,
+}
+
+impl EscapeNorm {
+    /// Returns the index of the norm, as used by the shaders.
+    pub(crate) fn index(self) -> i32 {
+        EscapeNorm::iter().position(|norm| norm == self).unwrap() as i32
+    }
+
+    /// Returns true if the norm of the complex number with the given parts
+    /// reached the bailout. The shaders use the same comparisons.
+    pub(crate) fn escaped(self, re: f64, im: f64, bailout: f64) -> bool {
+        match self {
+            // Compare the squared modulus to avoid a square root.
+            EscapeNorm::Euclidean => re * re + im * im >= bailout * bailout,
+            EscapeNorm::Max => re.abs().max(im.abs()) >= bailout,
+            EscapeNorm::Real => re.abs() >= bailout,
+            EscapeNorm::Imag => im.abs() >= bailout,
+            EscapeNorm::Manhattan => re.abs() + im.abs() >= bailout,
+        }
+    }
+
+    /// Returns the ratio between the norm and the modulus that no complex number exceeds.
+    pub(crate) fn max_ratio(self) -> f64 {
+        match self {
+            EscapeNorm::Manhattan => std::f64::consts::SQRT_2,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Returns the escape norm which name matches, or `None`.
+pub(crate) fn get_escape_norm_by_name(name: &str) -> Option<EscapeNorm> {
+    EscapeNorm::iter().find(|norm| {
+        norm.to_string()
+            .to_lowercase()
+            .starts_with(&name.to_lowercase())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_norms() {
+        // The point 1.5+1.5i is beyond a bailout of 2 with all the norms except the single parts.
+        let escaped = |norm: EscapeNorm| norm.escaped(1.5, 1.5, 2.0);
+        assert!(escaped(EscapeNorm::Euclidean));
+        assert!(!escaped(EscapeNorm::Max));
+        assert!(!escaped(EscapeNorm::Real));
+        assert!(!escaped(EscapeNorm::Imag));
+        assert!(escaped(EscapeNorm::Manhattan));
+
+        assert!(EscapeNorm::Imag.escaped(0.0, -2.0, 2.0));
+        assert!(!EscapeNorm::Real.escaped(0.0, -2.0, 2.0));
+        assert!(EscapeNorm::Max.escaped(-2.5, 0.1, 2.0));
+
+        // No norm exceeds the modulus multiplied by the maximum ratio.
+        for norm in EscapeNorm::iter() {
+            for (re, im) in [(1.0, 1.0), (-0.3, 2.0), (1.9, -0.2)] {
+                let modulus = f64::hypot(re, im);
+                assert!(!norm.escaped(re, im, modulus * norm.max_ratio() * 1.000001));
+            }
+        }
+
+        assert_eq!(get_escape_norm_by_name("man"), Some(EscapeNorm::Manhattan));
+        assert_eq!(EscapeNorm::Imag.index(), 3);
+    }
+}
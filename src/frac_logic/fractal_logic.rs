@@ -1,7 +1,7 @@
-use std::sync::mpsc::Sender;
+use std::{ops::Range, sync::mpsc::Sender};
 
 use crate::app::SlaveMessage;
-use crate::frac_logic::{CanvasCoords, RenderSettings};
+use crate::frac_logic::{CanvasCoords, Diverg, KnownMatrix, RenderSettings};
 use crate::fractals::{Fractal, FractalClos, FRACTALS};
 use crate::helpers::Vec2;
 
@@ -10,65 +10,207 @@ use rug::ops::CompleteRound;
 use rug::{Complex, Float};
 
 use super::gpu_util::SendSlaveMessage;
+use super::native::NativeRender;
+use super::perturbation::Perturbation;
+use super::subdivision::subdivided_diverg_matrix;
 
 const INITIAL_CANVAS_WIDTH: i32 = 5;
 /// Represents a matrix of divergence values [row][column]
-pub(crate) type DivergMatrix = Vec<Vec<i32>>;
+pub(crate) type DivergMatrix = Vec<Vec<Diverg>>;
 
 impl RenderSettings {
-    /// Returns a divergence matrix of the specified size.
+    /// Returns a function computing the divergence at the given canvas coordinates,
+    /// for a divergence matrix of the specified size.
+    fn get_point_renderer(&self, size: &Vec2<i32>) -> impl Fn(i32, i32) -> Diverg + Sync + '_ {
+        let cell_size = self.cell_size_from_height(size.y);
+        let perturbation = self
+            .use_perturbation(&cell_size)
+            .then(|| Perturbation::new(self, size, &cell_size));
+        let native = NativeRender::new(self, &cell_size);
+        let cell_size_f64 = cell_size.to_f64();
+
+        move |x: i32, y: i32| {
+            let coords = CanvasCoords::new(x, y);
+            // Points for which perturbation fails are computed directly.
+            if let Some(diverg) = perturbation
+                .as_ref()
+                .and_then(|p| p.diverg(&coords, self.max_iter))
+            {
+                return diverg;
+            }
+            if let Some(native) = &native {
+                return native.diverg(&coords);
+            }
+            let mut diverg =
+                (self.get_frac_clos())(self.coord_to_c_with_cell_size(coords, &cell_size), self);
+            diverg.dist = (diverg.dist as f64 / cell_size_f64) as f32;
+            diverg
+        }
+    }
+
+    /// Returns the given rows of a divergence matrix of the specified size, computed with the
+    /// Mariani–Silver subdivision if `subdivide` is true, and the number of computed points.
+    /// The points already `known` are not computed again.
     fn _get_diverg_matrix_with_status(
         &self,
         size: &Vec2<i32>,
+        rows: Range<i32>,
         sender: Option<&Sender<SlaveMessage>>,
-    ) -> DivergMatrix {
+        subdivide: bool,
+        known: Option<&KnownMatrix>,
+    ) -> (DivergMatrix, usize) {
         let half_x = size.x / 2;
         let half_y = size.y / 2;
-        let cell_size = self.cell_size_from_height(size.y);
-
-        let div_matrix = (-half_y..=-half_y + size.y - 1)
-            .into_par_iter()
-            .map(|y| {
-                // Before we start to render the line, we must check that the message
-                // pipe is not closed, this would mean the job is cancelled and we would need
-                // to exit without any result.
-                // To check if the message pipe is open, try to send a LineRender message.
-                if sender.send(SlaveMessage::LineRender).is_err() {
-                    return Default::default();
-                };
-
-                (-half_x..=-half_x + size.x)
-                    .into_par_iter()
-                    .map(|x| {
-                        (self.get_frac_clos())(
-                            self.coord_to_c_with_cell_size(CanvasCoords::new(x, y), &cell_size),
-                            self,
-                        )
-                    })
-                    .collect()
-            })
-            .collect();
+        let renderer = self.get_point_renderer(size);
+        let point = |x: i32, y: i32| {
+            known
+                .and_then(|known| known[(y + half_y) as usize][(x + half_x) as usize])
+                .unwrap_or_else(|| renderer(x, y))
+        };
 
-        // Send a message to the parent process indicating that the screenshot finished,
-        // and it should now wait for the result transfer through the `JoinHandle`.
-        let _ = sender.send(SlaveMessage::JobFinished);
+        if subdivide {
+            subdivided_diverg_matrix(
+                &Vec2::new(-half_x, -half_y + rows.start),
+                &Vec2::new(size.x + 1, rows.len() as i32),
+                &point,
+                &|a: &Diverg, b: &Diverg| self.same_coloring(a, b),
+                sender,
+            )
+        } else {
+            let div_matrix: DivergMatrix = (-half_y + rows.start..-half_y + rows.end)
+                .into_par_iter()
+                .map(|y| {
+                    // Before we start to render the line, we must check that the message
+                    // pipe is not closed, this would mean the job is cancelled and we would need
+                    // to exit without any result.
+                    // To check if the message pipe is open, try to send a LineRender message.
+                    if sender.send(SlaveMessage::LineRender).is_err() {
+                        return Default::default();
+                    };
 
-        div_matrix
+                    (-half_x..=-half_x + size.x)
+                        .into_par_iter()
+                        .map(|x| point(x, y))
+                        .collect()
+                })
+                .collect();
+            let computed = div_matrix.iter().map(Vec::len).sum();
+            (div_matrix, computed)
+        }
     }
 
-    /// Returns a divergence matrix, and send an update to the channel
-    /// after each line is rendered.
+    /// Returns the given rows of a divergence matrix, and send an update to the channel
+    /// after each line is rendered. The rows are the same as in the whole matrix.
     pub(crate) fn get_diverg_matrix_with_status(
         &self,
         size: &Vec2<i32>,
+        rows: Range<i32>,
         sender: &Sender<SlaveMessage>,
     ) -> DivergMatrix {
-        self._get_diverg_matrix_with_status(size, Some(sender))
+        self._get_diverg_matrix_with_status(
+            size,
+            rows,
+            Some(sender),
+            self.subdivision.captures,
+            None,
+        )
+        .0
     }
 
-    /// Returns a divergence matrix without sending any updates.
+    /// Returns a divergence matrix without sending any updates, where all the points are computed.
+    #[cfg(test)]
     pub(crate) fn get_diverg_matrix(&self, size: &Vec2<i32>) -> DivergMatrix {
-        self._get_diverg_matrix_with_status(size, None)
+        self._get_diverg_matrix_with_status(size, 0..size.y, None, false, None)
+            .0
+    }
+
+    /// Returns the divergence matrix of the canvas, and the fraction of its
+    /// points filled by the subdivision, if it is enabled for the canvas.
+    /// The points already `known`, for example from the tile cache, are not computed again.
+    pub(crate) fn get_canvas_diverg_matrix(
+        &self,
+        size: &Vec2<i32>,
+        known: Option<&KnownMatrix>,
+        sender: Option<&Sender<SlaveMessage>>,
+    ) -> (DivergMatrix, Option<f64>) {
+        let subdivide = self.subdivision.canvas;
+        let (div_matrix, computed) =
+            self._get_diverg_matrix_with_status(size, 0..size.y, sender, subdivide, known);
+        let total: usize = div_matrix.iter().map(Vec::len).sum();
+        let fill = subdivide.then(|| 1.0 - computed as f64 / total.max(1) as f64);
+        (div_matrix, fill)
+    }
+
+    /// Returns a preview of the canvas divergence matrix, rendered with a resolution divided
+    /// by `factor`, and pixel-doubled to the requested size.
+    pub(crate) fn get_preview_diverg_matrix(
+        &self,
+        size: &Vec2<i32>,
+        factor: i32,
+        sender: Option<&Sender<SlaveMessage>>,
+    ) -> DivergMatrix {
+        let preview_size = Vec2::new(
+            (size.x + factor - 1) / factor,
+            (size.y + factor - 1) / factor,
+        );
+        let (preview, _) = self.get_canvas_diverg_matrix(&preview_size, None, sender);
+        let (rows, columns) = (preview_size.y as usize, preview_size.x as usize + 1);
+
+        (0..size.y as usize)
+            .map(|y| {
+                let row = preview.get(y * rows / size.y as usize);
+                (0..=size.x as usize)
+                    .map(|x| {
+                        // The rows are missing if the render was cancelled.
+                        let mut diverg = row
+                            .and_then(|row| row.get(x * columns / (size.x as usize + 1)))
+                            .copied()
+                            .unwrap_or(Diverg::new(-1));
+                        // The distances are measured in preview cells.
+                        diverg.dist *= factor as f32;
+                        diverg
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the divergence matrix of the canvas after it moved by a whole number of cells,
+    /// where `previous` is the matrix before the move, and `shift` is the move in cells.
+    /// Only the points that were not in the previous matrix are computed.
+    pub(crate) fn get_shifted_diverg_matrix(
+        &self,
+        size: &Vec2<i32>,
+        previous: &DivergMatrix,
+        shift: &Vec2<i32>,
+        sender: Option<&Sender<SlaveMessage>>,
+    ) -> DivergMatrix {
+        let half_x = size.x / 2;
+        let half_y = size.y / 2;
+        let point = self.get_point_renderer(size);
+
+        (0..size.y)
+            .into_par_iter()
+            .map(|row| {
+                // Exit without any result if the job was cancelled, see `LineRender`.
+                if sender.send(SlaveMessage::LineRender).is_err() {
+                    return Default::default();
+                }
+                let previous_row = usize::try_from(row + shift.y)
+                    .ok()
+                    .and_then(|row| previous.get(row));
+                (0..=size.x)
+                    .into_par_iter()
+                    .map(|column| {
+                        usize::try_from(column + shift.x)
+                            .ok()
+                            .and_then(|column| previous_row?.get(column))
+                            .copied()
+                            .unwrap_or_else(|| point(column - half_x, row - half_y))
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
     /// Use the provided cell size to find the complex number
@@ -1,20 +1,46 @@
-use std::{sync::mpsc::Sender, time::Instant};
+use std::{ops::Range, sync::mpsc::Sender, time::Instant};
 
 use wgpu::util::DeviceExt;
 
 use crate::{app::SlaveMessage, frac_logic::gpu_rendering_tracker::msg_send, helpers::Vec2};
 
 use super::{
-    gpu_rendering_tracker::GpuRenderingTracker, DivergMatrix, ParamsBinding, RenderSettings,
+    gpu_rendering_tracker::GpuRenderingTracker,
+    params_binding::{complex_array, split_f32},
+    DivergMatrix, ParamsBinding, RenderSettings,
 };
 
 const GPU_JOB_TIMEOUT: u64 = 15;
+/// Below this cell size, the single precision shaders become pixelated, so the
+/// double-single shaders are used instead. Some margin is kept for the rounding
+/// errors that accumulate over the iterations.
+const DOUBLE_SINGLE_MAX_CELL_SIZE: f64 = f32::EPSILON as f64 * 64.0;
 
 impl RenderSettings {
+    /// Selects the single precision or the double-single shaders for a render
+    /// of the given size, which reloads the shader if it changed.
+    pub(crate) fn update_gpu_precision(&mut self, size: &Vec2<i32>) -> Result<(), String> {
+        let cell_size = self.cell_size_from_height(size.y);
+        self.wgpu_state
+            .set_double_single(cell_size < DOUBLE_SINGLE_MAX_CELL_SIZE)
+    }
+
     pub(crate) async fn get_gpu_diverg_matrix_async(
         &mut self,
         size: &Vec2<i32>,
         sender: Option<&Sender<SlaveMessage>>,
+    ) -> Result<DivergMatrix, String> {
+        self.get_gpu_diverg_rows_async(size, 0..size.y, sender)
+            .await
+    }
+
+    /// Returns the given rows of a divergence matrix of the specified size, which are
+    /// the same as in the whole matrix.
+    pub(crate) async fn get_gpu_diverg_rows_async(
+        &mut self,
+        size: &Vec2<i32>,
+        rows: Range<i32>,
+        sender: Option<&Sender<SlaveMessage>>,
     ) -> Result<DivergMatrix, String> {
         // The maximum buffer size
         let max_buf_size = self
@@ -28,6 +54,7 @@ impl RenderSettings {
         let mut tracker = GpuRenderingTracker::new(
             sender,
             size,
+            rows,
             max_buf_size,
             self.wgpu_state.get_adapter()?.get_info(),
             self.chunk_size_limit,
@@ -46,7 +73,19 @@ impl RenderSettings {
             ));
         }
 
+        self.update_gpu_precision(size)?;
         let cell_size = self.cell_size_from_height(size.y);
+        let pos = [split_f32(self.pos.real()), split_f32(self.pos.imag())];
+        let cell_size = split_f32(&cell_size);
+        let trap_shape_index = match self.trap_tracker() {
+            Some(_) => self.orbit_trap.shape.index(),
+            None => -1,
+        };
+        let trap_rotation = self.orbit_trap.rotation();
+        let interior_mode_index = match self.interior_tracker() {
+            Some(_) => self.interior.mode.index(),
+            None => 0,
+        };
 
         'a: while !tracker.render_finished() {
             msg_send(sender, "Beginning new render pass...")?;
@@ -90,8 +129,8 @@ impl RenderSettings {
                     contents: bytemuck::bytes_of(&ParamsBinding {
                         max_iter: self.max_iter,
                         size: [size.x, size.y],
-                        pos: [self.pos.real().to_f32(), self.pos.imag().to_f32()],
-                        cell_size: cell_size.to_f32(),
+                        pos: [pos[0][0], pos[1][0]],
+                        cell_size: cell_size[0],
                         y_offset: tracker.pass_first_line(),
                         julia_constant: [
                             self.julia_constant.real().to_f32(),
@@ -102,6 +141,24 @@ impl RenderSettings {
                             self.mandel_constant.imag().to_f32(),
                         ],
                         bailout: self.bailout,
+                        exponent: self.exponent as f32,
+                        newton_degree: self.newton_poly.degree() as i32,
+                        pos_lo: [pos[0][1], pos[1][1]],
+                        cell_size_lo: cell_size[1],
+                        one: 1.0,
+                        distance_estimation: self.use_distance_estimation() as i32,
+                        trap_shape: trap_shape_index,
+                        trap_center: [
+                            self.orbit_trap.center.re as f32,
+                            self.orbit_trap.center.im as f32,
+                        ],
+                        trap_size: self.orbit_trap.size as f32,
+                        trap_rotation: [trap_rotation.re as f32, trap_rotation.im as f32],
+                        interior_mode: interior_mode_index,
+                        escape_norm: self.escape_norm.index(),
+                        _padding: Default::default(),
+                        newton_coefs: complex_array(&self.newton_poly.coefs),
+                        newton_roots: complex_array(&self.newton_poly.roots),
                     }),
                     usage: wgpu::BufferUsages::UNIFORM,
                 },
@@ -237,7 +294,7 @@ impl RenderSettings {
 
             // Gets contents of buff
             let data = buffer_slice.get_mapped_range();
-            // Since contents are got in bytes, this converts these bytes back to i32
+            // Since contents are got in bytes, this converts these bytes back to `Diverg` structs
             tracker.send("Parsing output data")?;
             let lines_flat = bytemuck::cast_slice(&data).to_vec();
             let mut lines = lines_flat
@@ -258,3 +315,80 @@ impl RenderSettings {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use rug::{Complex, Float};
+
+    use strum::IntoEnumIterator;
+
+    use crate::frac_logic::{CanvasCoords, EscapeNorm};
+
+    use super::*;
+
+    #[test]
+    fn test_double_single_matches_cpu() {
+        let mut rs = RenderSettings::default();
+        // This test needs an adapter, which can be a software one such as lavapipe.
+        if let Err(err) = block_on(rs.initialize_gpu(None)) {
+            eprintln!("Skipping the GPU test: {err}");
+            return;
+        }
+
+        let size = Vec2::new(32, 20);
+        rs.canvas_size = CanvasCoords::new(size.x, size.y);
+        rs.max_iter = 200;
+        // Close to a Misiurewicz point, so that the points escape after various iteration counts.
+        rs.pos = Complex::with_val(rs.prec, (0, 1)) + Complex::with_val(rs.prec, (0, 1e-10 / 3.0));
+        // Far below the resolution of `f32`, but within the one of double-single numbers.
+        rs.set_width(Float::with_val(rs.prec, 1e-9));
+
+        let gpu = block_on(rs.get_gpu_diverg_matrix_async(&size, None)).unwrap();
+        let cpu = rs.get_diverg_matrix(&size);
+
+        // The CPU lines have an extra point on the right.
+        let mismatches = gpu
+            .iter()
+            .zip(&cpu)
+            .flat_map(|(gpu_line, cpu_line)| gpu_line.iter().zip(cpu_line))
+            .filter(|(gpu, cpu)| (gpu.iter - cpu.iter).abs() > 1)
+            .count();
+        // A few points with many iterations may differ, because of rounding errors.
+        assert!(mismatches < (size.x * size.y) as usize / 20);
+    }
+
+    #[test]
+    fn test_escape_norms_match_cpu() {
+        let mut rs = RenderSettings::default();
+        // This test needs an adapter, which can be a software one such as lavapipe.
+        if let Err(err) = block_on(rs.initialize_gpu(None)) {
+            eprintln!("Skipping the GPU test: {err}");
+            return;
+        }
+
+        let size = Vec2::new(32, 20);
+        rs.canvas_size = CanvasCoords::new(size.x, size.y);
+        rs.max_iter = 100;
+        rs.bailout = 3.0;
+        rs.set_width(Float::with_val(rs.prec, 3.0));
+
+        for norm in EscapeNorm::iter() {
+            rs.escape_norm = norm;
+            let gpu = block_on(rs.get_gpu_diverg_matrix_async(&size, None)).unwrap();
+            let cpu = rs.get_diverg_matrix(&size);
+
+            let mismatches = gpu
+                .iter()
+                .zip(&cpu)
+                .flat_map(|(gpu_line, cpu_line)| gpu_line.iter().zip(cpu_line))
+                .filter(|(gpu, cpu)| gpu.iter != cpu.iter)
+                .count();
+            // Only the points on the boundary of the set may differ, because of rounding errors.
+            assert!(
+                mismatches < (size.x * size.y) as usize / 50,
+                "{norm}: {mismatches} mismatches"
+            );
+        }
+    }
+}
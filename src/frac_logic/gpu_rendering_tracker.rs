@@ -1,4 +1,4 @@
-use std::mem::size_of;
+use std::{mem::size_of, ops::Range};
 use std::{
     sync::mpsc::Sender,
     time::{Duration, Instant},
@@ -10,11 +10,14 @@ use wgpu::AdapterInfo;
 use crate::{app::SlaveMessage, helpers::Vec2};
 
 use super::gpu_util::SendSlaveMessage;
+use super::Diverg;
 
 pub(crate) struct GpuRenderingTracker<'a> {
     current_pass: u32,
     sender: Option<&'a Sender<SlaveMessage>>,
     size: Vec2<i32>,
+    /// The lines of the divergence matrix which are rendered.
+    rows: Range<i32>,
     max_buf_size: u64,
     begin_time: Instant,
     adapter: AdapterInfo,
@@ -26,6 +29,7 @@ impl<'a> GpuRenderingTracker<'a> {
     pub(crate) fn new(
         sender: Option<&'a Sender<SlaveMessage>>,
         size: &Vec2<i32>,
+        rows: Range<i32>,
         max_buf_size: u64,
         adapter: AdapterInfo,
         chunk_size_limit: Option<i32>,
@@ -34,6 +38,7 @@ impl<'a> GpuRenderingTracker<'a> {
             sender,
             adapter,
             size: size.clone(),
+            rows,
             current_pass: 0,
             max_buf_size,
             begin_time: Instant::now(),
@@ -50,13 +55,13 @@ impl<'a> GpuRenderingTracker<'a> {
     /// Calculate the maximum number of lines that can be rendered per pass.
     pub(crate) fn max_lines_per_pass(&self) -> i32 {
         ((self.max_buf_size / self.output_buffer_line_size()) as i32)
-            .min(self.size.y)
+            .min(self.rows.len() as i32)
             .min(self.lines_per_chunk_limit)
     }
 
     /// Calculate the number of passes required to fininsh the render.
     pub(crate) fn pass_count(&self) -> u32 {
-        1.max((self.size.y as f32 / self.max_lines_per_pass() as f32).ceil() as u32)
+        1.max((self.rows.len() as f32 / self.max_lines_per_pass() as f32).ceil() as u32)
     }
 
     /// Calculate the average duration of a render pass.
@@ -83,13 +88,13 @@ impl<'a> GpuRenderingTracker<'a> {
     pub(crate) fn output_buffer_line_size(&self) -> u64 {
         // The byte size of one divergence line in the output buffer.
         // Max to 1 to prevent division by 0 is some rare cases where to app screen is very small.
-        self.size.x.max(1) as u64 * size_of::<u32>() as u64
+        self.size.x.max(1) as u64 * size_of::<Diverg>() as u64
     }
 
     /// Calculate the necessary size for the output buffer of the current pass.
     pub(crate) fn output_buffer_chunk_size(&self) -> u64 {
         // The size of the output buffer when the input lines are limited, see above.
-        self.output_buffer_line_size() * self.size.y.min(self.pass_line_count()) as u64
+        self.output_buffer_line_size() * self.pass_line_count() as u64
     }
 
     /// Renturns true if no render passes are left.
@@ -104,13 +109,13 @@ impl<'a> GpuRenderingTracker<'a> {
 
     /// Returns the y coordinate (0->size.y) of the first line of the current pass.
     pub(crate) fn pass_first_line(&self) -> i32 {
-        (self.current_pass as i32 - 1) * self.max_lines_per_pass()
+        self.rows.start + (self.current_pass as i32 - 1) * self.max_lines_per_pass()
     }
 
     /// Returns the y coordinate (0->size.y) of the last line of the current pass.
     pub(crate) fn pass_last_line(&self) -> i32 {
-        self.size
-            .y
+        self.rows
+            .end
             .min(self.pass_first_line() + self.max_lines_per_pass())
     }
 
@@ -0,0 +1,190 @@
+//! Contains the interior coloring settings, used to color the points that
+//! reach the maximum number of iterations instead of filling them with the void fill.
+//! https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set
+
+use rug::Complex;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::helpers::{NativeComplex, Real};
+
+use super::Diverg;
+
+/// Two terms closer than this distance are considered equal by the cycle detection.
+const PERIOD_EPSILON: f64 = 1e-9;
+
+type C64 = NativeComplex<f64>;
+
+/// The quantities used to color the interior. Their order must match
+/// `interior_value` in the shaders.
+#[derive(PartialEq, EnumIter, Debug, Display, Clone, Copy, Deserialize, Serialize)]
+pub(crate) enum InteriorMode {
+    /// The interior is filled with the void fill.
+    Flat,
+    /// The modulus of the last term.
+    Magnitude,
+    /// The argument of the last term.
+    Argument,
+    /// The period of the cycle the orbit is attracted to, or 0 if none was detected.
+    Period,
+    /// The index of the term of smallest modulus.
+    AtomDomain,
+}
+
+impl InteriorMode {
+    /// Returns the index of the mode, as used by the shaders.
+    pub(crate) fn index(self) -> i32 {
+        InteriorMode::iter().position(|mode| mode == self).unwrap() as i32
+    }
+
+    /// Returns the mode following this one, wrapping around.
+    pub(crate) fn next_mode(self) -> Self {
+        InteriorMode::iter()
+            .cycle()
+            .skip_while(|mode| *mode != self)
+            .nth(1)
+            .unwrap()
+    }
+}
+
+/// Returns the interior mode which name matches, or `None`.
+pub(crate) fn get_interior_mode_by_name(name: &str) -> Option<InteriorMode> {
+    InteriorMode::iter().find(|mode| {
+        mode.to_string()
+            .to_lowercase()
+            .starts_with(&name.to_lowercase())
+    })
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct InteriorSettings {
+    pub(crate) mode: InteriorMode,
+    /// The index of the palette used for the interior, independent from the exterior one.
+    pub(crate) palette_index: usize,
+}
+
+impl Default for InteriorSettings {
+    fn default() -> Self {
+        Self {
+            mode: InteriorMode::Flat,
+            palette_index: 0,
+        }
+    }
+}
+
+/// Keeps track of the quantities of an orbit used by the interior coloring.
+#[derive(Clone, Copy)]
+pub(crate) struct InteriorTracker {
+    mode: InteriorMode,
+    /// The number of terms tracked so far.
+    n: i32,
+    last: C64,
+    /// The smallest squared modulus of the terms so far, and the index of that term.
+    min_norm: f64,
+    domain: i32,
+    /// A previous term, compared with the next ones to detect a cycle. Like in
+    /// Brent's algorithm, it is replaced each time `n` reaches a power of two.
+    reference: C64,
+    reference_n: i32,
+    period: i32,
+}
+
+impl InteriorTracker {
+    pub(crate) fn new(mode: InteriorMode) -> Self {
+        Self {
+            mode,
+            n: 0,
+            last: C64::default(),
+            min_norm: f64::INFINITY,
+            domain: 0,
+            reference: C64::new(f64::INFINITY, f64::INFINITY),
+            reference_n: 0,
+            period: 0,
+        }
+    }
+
+    /// Updates the tracked quantities with a new term of the orbit.
+    pub(crate) fn track<T: Real>(&mut self, z: NativeComplex<T>) {
+        let z = C64::new(z.re.to_f64(), z.im.to_f64());
+        self.n += 1;
+        self.last = z;
+
+        let norm = z.norm_sqr();
+        if norm < self.min_norm {
+            self.min_norm = norm;
+            self.domain = self.n;
+        }
+        if self.period == 0 && (z - self.reference).norm_sqr() < PERIOD_EPSILON * PERIOD_EPSILON {
+            self.period = self.n - self.reference_n;
+        }
+        if self.n & (self.n - 1) == 0 {
+            self.reference = z;
+            self.reference_n = self.n;
+        }
+    }
+
+    /// Same as `track`, with an arbitrary precision number.
+    pub(crate) fn track_rug(&mut self, z: &Complex) {
+        self.track(C64::new(z.real().to_f64(), z.imag().to_f64()));
+    }
+
+    /// Returns the quantity of the selected mode.
+    pub(crate) fn value(&self) -> f32 {
+        match self.mode {
+            InteriorMode::Flat => 0.0,
+            InteriorMode::Magnitude => self.last.abs() as f32,
+            InteriorMode::Argument => self.last.im.atan2(self.last.re) as f32,
+            InteriorMode::Period => self.period as f32,
+            InteriorMode::AtomDomain => self.domain as f32,
+        }
+    }
+}
+
+/// Returns the divergence of a point that reached the maximum number of
+/// iterations, with its interior value if the interior is tracked.
+pub(crate) fn void_diverg(interior: &Option<InteriorTracker>) -> Diverg {
+    let mut diverg = Diverg::new(-1);
+    if let Some(interior) = interior {
+        diverg.interior = interior.value();
+    }
+    diverg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interior_tracker() {
+        // The orbit of -1 is 0, -1, 0, -1... and has a period of 2.
+        let mut tracker = InteriorTracker::new(InteriorMode::Period);
+        let mut z = C64::default();
+        for _ in 0..20 {
+            z = z * z + C64::new(-1.0, 0.0);
+            tracker.track(z);
+        }
+        assert_eq!(tracker.value(), 2.0);
+        tracker.mode = InteriorMode::AtomDomain;
+        assert_eq!(tracker.value(), 2.0);
+        tracker.mode = InteriorMode::Magnitude;
+        assert_eq!(tracker.value(), 0.0);
+
+        // The orbit of i ends in the cycle -1+i, -i.
+        let mut tracker = InteriorTracker::new(InteriorMode::Period);
+        let mut z = C64::default();
+        for _ in 0..21 {
+            z = z * z + C64::new(0.0, 1.0);
+            tracker.track(z);
+        }
+        assert_eq!(tracker.value(), 2.0);
+        tracker.mode = InteriorMode::Argument;
+        assert_eq!(tracker.value(), -std::f32::consts::FRAC_PI_2);
+
+        assert_eq!(
+            get_interior_mode_by_name("atom"),
+            Some(InteriorMode::AtomDomain)
+        );
+        assert_eq!(InteriorMode::Period.index(), 3);
+        assert_eq!(InteriorMode::AtomDomain.next_mode(), InteriorMode::Flat);
+    }
+}
@@ -1,17 +1,36 @@
 //! Contains all the logic required to render a divergence matrix with the GPU of the CPU.
 
+pub(crate) mod buddhabrot;
 mod canvas_coords;
+mod diverg;
+pub(crate) mod escape_norm;
 mod fractal_logic;
 pub(crate) mod gpu_render;
 mod gpu_rendering_tracker;
 pub(crate) mod gpu_util;
+pub(crate) mod interior;
+mod native;
+pub(crate) mod orbit_trap;
 mod params_binding;
+mod periodicity;
+mod perturbation;
 mod render_settings;
 mod render_settings_methods;
+mod subdivision;
+mod tile_cache;
 mod wgpu_state;
 
+pub(crate) use buddhabrot::{BuddhabrotSettings, DensityMode, Histogram};
 pub(crate) use canvas_coords::CanvasCoords;
+pub(crate) use diverg::{Diverg, NO_BASIN};
+pub(crate) use escape_norm::EscapeNorm;
 pub(crate) use fractal_logic::DivergMatrix;
+pub(crate) use interior::{void_diverg, InteriorMode, InteriorSettings, InteriorTracker};
+pub(crate) use native::NativeSettings;
+pub(crate) use orbit_trap::{OrbitTrap, TrapShape, TrapTracker};
 pub(crate) use params_binding::ParamsBinding;
+pub(crate) use periodicity::PeriodicityCheck;
 pub(crate) use render_settings::RenderSettings;
+pub(crate) use subdivision::SubdivisionSettings;
+pub(crate) use tile_cache::{complete_matrix, CacheView, KnownMatrix, TileCache};
 pub(crate) use wgpu_state::WgpuState;
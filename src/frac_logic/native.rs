@@ -0,0 +1,260 @@
+//! Contains the CPU rendering with native floating point types, which is much faster
+//! than `rug` and is used automatically when the configured precision is low enough.
+
+use rug::Float;
+
+use crate::fractals::NativeClos;
+use crate::helpers::{DoubleDouble, NativeComplex, Real};
+
+use super::{CanvasCoords, Diverg, EscapeNorm, InteriorTracker, RenderSettings, TrapTracker};
+
+/// The render settings used by the fractals, converted to a native type.
+pub(crate) struct NativeSettings<T: Real> {
+    pub(crate) max_iter: i32,
+    exponent: i32,
+    bailout: f64,
+    escape_norm: EscapeNorm,
+    pub(crate) julia_constant: NativeComplex<T>,
+    pub(crate) mandel_constant: NativeComplex<T>,
+    /// Whether the fractals should compute the distance estimation.
+    pub(crate) distance_estimation: bool,
+    /// The orbit trap tracker, copied by each point, if the orbit trap coloring is used.
+    pub(crate) trap_tracker: Option<TrapTracker>,
+    /// The interior tracker, copied by each point, if an interior coloring mode is used.
+    pub(crate) interior_tracker: Option<InteriorTracker>,
+    /// Whether the sequences that repeat themselves stop early, see `PeriodicityCheck`.
+    pub(crate) cycle_detection: bool,
+    /// Whether the Mandelbrot set skips the points of its main cardioid and period-2 bulb.
+    pub(crate) main_components_check: bool,
+}
+
+impl<T: Real> NativeSettings<T> {
+    fn new(rs: &RenderSettings) -> Self {
+        Self {
+            max_iter: rs.max_iter,
+            exponent: rs.exponent as i32,
+            bailout: rs.bailout as f64,
+            escape_norm: rs.escape_norm,
+            julia_constant: NativeComplex::from_rug(&rs.julia_constant),
+            mandel_constant: NativeComplex::from_rug(&rs.mandel_constant),
+            distance_estimation: rs.use_distance_estimation(),
+            trap_tracker: rs.trap_tracker(),
+            interior_tracker: rs.interior_tracker(),
+            cycle_detection: rs.use_interior_checks(),
+            main_components_check: rs.use_main_components_check(),
+        }
+    }
+
+    /// Returns true if a term of a sequence escaped, the same way as `RenderSettings::escaped`.
+    pub(crate) fn escaped(&self, z: NativeComplex<T>) -> bool {
+        self.escape_norm
+            .escaped(z.re.to_f64(), z.im.to_f64(), self.bailout)
+    }
+
+    /// Raises `z` to the configured exponent, the same way as `pow_exponent`.
+    pub(crate) fn pow_exponent(&self, z: NativeComplex<T>) -> NativeComplex<T> {
+        match self.exponent {
+            _ if z.is_zero() => z,
+            2 => z * z,
+            exp => z.powi(exp),
+        }
+    }
+
+    /// Multiplies the derivative `dz` by the derivative of `zᵈ`, the same way as `derive_exponent`.
+    pub(crate) fn derive_exponent(
+        &self,
+        dz: NativeComplex<T>,
+        z: NativeComplex<T>,
+    ) -> NativeComplex<T> {
+        let factor = match self.exponent {
+            2 => z,
+            exp => z.powi(exp - 1),
+        };
+        (factor * dz).scale(T::from_f64(self.exponent as f64))
+    }
+}
+
+/// Everything needed to render the points of a frame with a native type.
+pub(crate) struct NativeFrame<T: Real> {
+    get: NativeClos<T>,
+    settings: NativeSettings<T>,
+    pos: NativeComplex<T>,
+    cell_size: T,
+}
+
+impl<T: Real> NativeFrame<T> {
+    fn new(get: NativeClos<T>, rs: &RenderSettings, cell_size: &Float) -> Self {
+        Self {
+            get,
+            settings: NativeSettings::new(rs),
+            pos: NativeComplex::from_rug(&rs.pos),
+            cell_size: T::from_float(cell_size),
+        }
+    }
+
+    fn diverg(&self, coords: &CanvasCoords) -> Diverg {
+        let offset = NativeComplex::new(
+            T::from_f64(coords.x as f64) * self.cell_size,
+            T::from_f64(coords.y as f64) * self.cell_size,
+        );
+        let mut diverg = (self.get)(offset + self.pos, &self.settings);
+        diverg.dist = (diverg.dist as f64 / self.cell_size.to_f64()) as f32;
+        diverg
+    }
+}
+
+/// The native type used to render a frame.
+pub(crate) enum NativeRender {
+    F64(NativeFrame<f64>),
+    DoubleDouble(NativeFrame<DoubleDouble>),
+}
+
+impl NativeRender {
+    /// Returns the fastest native renderer that provides the configured precision,
+    /// or `None` if the frame must be rendered with `rug`.
+    pub(crate) fn new(rs: &RenderSettings, cell_size: &Float) -> Option<Self> {
+        let native = rs.get_frac_obj().native.as_ref()?;
+        // Non-integer exponents are only supported by `rug`.
+        if rs.exponent.fract() != 0.0 {
+            return None;
+        }
+
+        if rs.prec <= f64::PREC {
+            Some(Self::F64(NativeFrame::new(native.f64, rs, cell_size)))
+        } else if rs.prec <= DoubleDouble::PREC {
+            Some(Self::DoubleDouble(NativeFrame::new(
+                native.double_double,
+                rs,
+                cell_size,
+            )))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the divergence of the point at the given canvas coordinates.
+    pub(crate) fn diverg(&self, coords: &CanvasCoords) -> Diverg {
+        match self {
+            Self::F64(frame) => frame.diverg(coords),
+            Self::DoubleDouble(frame) => frame.diverg(coords),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::Complex;
+    use strum::IntoEnumIterator;
+
+    use crate::fractals::{get_frac_index_by_name, FRACTALS};
+    use crate::helpers::Vec2;
+
+    use super::*;
+
+    /// Returns the fraction of the points where the native and the `rug` renders disagree.
+    fn mismatch_ratio(rs: &RenderSettings, size: &Vec2<i32>) -> f64 {
+        let cell_size = rs.cell_size_from_height(size.y);
+        let native = NativeRender::new(rs, &cell_size).unwrap();
+
+        let mut mismatches = 0;
+        for y in -size.y / 2..size.y - size.y / 2 {
+            for x in -size.x / 2..size.x - size.x / 2 {
+                let coords = CanvasCoords::new(x, y);
+                let expected = (rs.get_frac_clos())(
+                    rs.coord_to_c_with_cell_size(coords.clone(), &cell_size),
+                    rs,
+                );
+                if (native.diverg(&coords).iter - expected.iter).abs() > 1 {
+                    mismatches += 1;
+                }
+            }
+        }
+        mismatches as f64 / (size.x * size.y) as f64
+    }
+
+    #[test]
+    fn test_native_matches_rug() {
+        let size = Vec2::new(24, 16);
+        // The repelling fixed point of the Julia set, which is on its boundary.
+        let julia_constant = RenderSettings::default().julia_constant;
+        let julia_fixed_point =
+            (Complex::with_val(106, 1 - 4 * julia_constant).sqrt() + 1) / 2;
+
+        // Shallow views with `f64`, and deep ones with double-doubles,
+        // all on the boundary of the fractals.
+        let views = [
+            (53, Complex::with_val(53, (-0.75, 0.1)), 0.05, "mandelbrot", 2.0),
+            (53, Complex::with_val(53, (0.34, 1.27)), 0.05, "mandelbrot", 3.0),
+            (53, Complex::with_val(53, (0.4, -0.2)), 0.5, "julia", 2.0),
+            (53, Complex::with_val(53, (1.75, 0.03)), 0.1, "burning", 2.0),
+            (106, Complex::with_val(106, (0, 1)), 1e-24, "mandelbrot", 2.0),
+            (106, julia_fixed_point, 1e-24, "julia", 2.0),
+            (106, Complex::with_val(106, (2, 0)), 1e-24, "burning", 2.0),
+        ];
+
+        for (prec, pos, width, frac, exponent) in views {
+            let mut rs = RenderSettings {
+                frac_index: get_frac_index_by_name(frac).unwrap(),
+                canvas_size: CanvasCoords::new(size.x, size.y),
+                ..Default::default()
+            };
+            rs.set_decimal_prec(prec);
+            rs.max_iter = 200;
+            rs.exponent = exponent;
+            rs.pos = pos;
+            rs.set_width(Float::with_val(prec, width));
+            // A few points with many iterations may differ, because of rounding errors.
+            assert!(
+                mismatch_ratio(&rs, &size) < 0.02,
+                "{} at {}",
+                FRACTALS[rs.frac_index].name,
+                rs.pos
+            );
+        }
+
+        // Non-integer exponents and high precisions are only supported by `rug`.
+        let mut rs = RenderSettings {
+            exponent: 2.5,
+            ..Default::default()
+        };
+        assert!(NativeRender::new(&rs, &rs.cell_size).is_none());
+        rs.exponent = 2.0;
+        rs.set_decimal_prec(128);
+        assert!(NativeRender::new(&rs, &rs.cell_size).is_none());
+    }
+
+    #[test]
+    fn test_escape_norms_match_rug() {
+        let size = Vec2::new(24, 16);
+        for norm in EscapeNorm::iter() {
+            for frac in ["mandelbrot", "julia", "burning"] {
+                let mut rs = RenderSettings {
+                    frac_index: get_frac_index_by_name(frac).unwrap(),
+                    canvas_size: CanvasCoords::new(size.x, size.y),
+                    bailout: 3.0,
+                    escape_norm: norm,
+                    ..Default::default()
+                };
+                rs.set_decimal_prec(53);
+                rs.max_iter = 100;
+                rs.pos = Complex::with_val(53, (-0.5, 0.2));
+                rs.set_width(Float::with_val(53, 3.0));
+                assert!(mismatch_ratio(&rs, &size) < 0.02, "{norm} with {frac}");
+            }
+        }
+
+        // The escaping terms of 1.1+1.1i with the different norms: 1.1+3.52i, then -10.08+8.84i.
+        let c = Complex::with_val(53, (1.1, 1.1));
+        let iter = |norm: EscapeNorm| {
+            let rs = RenderSettings {
+                bailout: 3.0,
+                escape_norm: norm,
+                ..Default::default()
+            };
+            (rs.get_frac_clos())(c.clone(), &rs).iter
+        };
+        assert_eq!(iter(EscapeNorm::Euclidean), 2);
+        assert_eq!(iter(EscapeNorm::Imag), 2);
+        assert_eq!(iter(EscapeNorm::Real), 3);
+    }
+}
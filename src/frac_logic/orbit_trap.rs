@@ -0,0 +1,182 @@
+//! Contains the orbit trap settings, used by the orbit trap coloring mode.
+//! https://en.wikipedia.org/wiki/Orbit_trap
+
+use rug::Complex;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::helpers::{polynomial::format_complex, NativeComplex, Real};
+
+/// The factor applied to the trap size when changing it from the canvas.
+pub(crate) const TRAP_SIZE_FACTOR: f64 = 1.25;
+/// The angle increment (in degrees) when changing it from the canvas.
+pub(crate) const TRAP_ANGLE_STEP: f64 = 15.0;
+
+type C64 = NativeComplex<f64>;
+
+/// The shapes of the orbit traps. Their order must match `trap_distance` in the shaders.
+#[derive(PartialEq, EnumIter, Debug, Display, Clone, Copy, Deserialize, Serialize)]
+pub(crate) enum TrapShape {
+    Point,
+    Line,
+    Cross,
+    Circle,
+    /// A cross whose arms have the width of the trap size, the other
+    /// points are colored by their iteration count.
+    PickoverStalk,
+}
+
+impl TrapShape {
+    /// Returns the index of the shape, as used by the shaders.
+    pub(crate) fn index(self) -> i32 {
+        TrapShape::iter().position(|shape| shape == self).unwrap() as i32
+    }
+}
+
+/// Returns the shape which name matches, or `None`.
+pub(crate) fn get_trap_shape_by_name(name: &str) -> Option<TrapShape> {
+    TrapShape::iter().find(|shape| {
+        shape
+            .to_string()
+            .to_lowercase()
+            .starts_with(&name.to_lowercase())
+    })
+}
+
+/// The geometry of the orbit trap.
+#[derive(Clone, Debug)]
+pub(crate) struct OrbitTrap {
+    pub(crate) shape: TrapShape,
+    /// The center of the point, cross, circle and stalks, or a point of the line.
+    pub(crate) center: C64,
+    /// The radius of the circle and the width of the stalks. For the other
+    /// shapes, the distance at which the colors start to fade.
+    pub(crate) size: f64,
+    /// The angle of the line and of the cross, in degrees.
+    pub(crate) angle: f64,
+}
+
+impl Default for OrbitTrap {
+    fn default() -> Self {
+        Self {
+            shape: TrapShape::Point,
+            center: C64::default(),
+            size: 0.5,
+            angle: 0.0,
+        }
+    }
+}
+
+impl OrbitTrap {
+    /// Multiplies or divides the size by `TRAP_SIZE_FACTOR`.
+    pub(crate) fn scale_size(&mut self, increase: bool) {
+        if increase {
+            self.size *= TRAP_SIZE_FACTOR;
+        } else {
+            self.size /= TRAP_SIZE_FACTOR;
+        }
+    }
+
+    /// Increments the angle by the given value, keeping it between 0 and 360 degrees.
+    pub(crate) fn increment_angle(&mut self, increment: f64) {
+        self.angle = (self.angle + increment).rem_euclid(360.0);
+    }
+
+    /// Returns the center formatted so that it can be parsed back with `parse_complex`.
+    pub(crate) fn format_center(&self) -> String {
+        format_complex(&Complex::with_val(53, (self.center.re, self.center.im)))
+    }
+
+    /// Returns the unit complex number that rotates the points by the opposite
+    /// of the angle, so that the line and the cross are aligned with the axes.
+    pub(crate) fn rotation(&self) -> C64 {
+        let (sin, cos) = (-self.angle.to_radians()).sin_cos();
+        C64::new(cos, sin)
+    }
+}
+
+/// Keeps track of the minimum distance between an orbit and the trap.
+#[derive(Clone, Copy)]
+pub(crate) struct TrapTracker {
+    shape: TrapShape,
+    center: C64,
+    size: f64,
+    rotation: C64,
+    /// The minimum distance between the orbit and the trap so far.
+    min: f64,
+}
+
+impl TrapTracker {
+    pub(crate) fn new(trap: &OrbitTrap) -> Self {
+        Self {
+            shape: trap.shape,
+            center: trap.center,
+            size: trap.size,
+            rotation: trap.rotation(),
+            min: f64::INFINITY,
+        }
+    }
+
+    /// Returns the distance between a point and the trap.
+    fn distance(&self, z: C64) -> f64 {
+        let offset = z - self.center;
+        let rotated = offset * self.rotation;
+        match self.shape {
+            TrapShape::Point => offset.abs(),
+            TrapShape::Line => rotated.im.abs(),
+            TrapShape::Cross | TrapShape::PickoverStalk => rotated.re.abs().min(rotated.im.abs()),
+            TrapShape::Circle => (offset.abs() - self.size).abs(),
+        }
+    }
+
+    /// Updates the minimum distance with a new term of the orbit.
+    pub(crate) fn track<T: Real>(&mut self, z: NativeComplex<T>) {
+        let z = C64::new(z.re.to_f64(), z.im.to_f64());
+        self.min = self.min.min(self.distance(z));
+    }
+
+    /// Same as `track`, with an arbitrary precision number.
+    pub(crate) fn track_rug(&mut self, z: &Complex) {
+        self.track(C64::new(z.real().to_f64(), z.imag().to_f64()));
+    }
+
+    /// Returns the minimum distance between the orbit and the trap.
+    pub(crate) fn min_distance(&self) -> f32 {
+        self.min as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trap_distance() {
+        let mut trap = OrbitTrap {
+            center: C64::new(1.0, 1.0),
+            size: 0.5,
+            ..Default::default()
+        };
+        let z = C64::new(2.0, 1.5);
+        let distance = |trap: &OrbitTrap| TrapTracker::new(trap).distance(z);
+
+        assert_eq!(distance(&trap), 1.25f64.sqrt());
+        trap.shape = TrapShape::Line;
+        assert_eq!(distance(&trap), 0.5);
+        trap.shape = TrapShape::Circle;
+        assert_eq!(distance(&trap), 1.25f64.sqrt() - 0.5);
+        trap.shape = TrapShape::Cross;
+        assert_eq!(distance(&trap), 0.5);
+
+        // A vertical line.
+        trap.shape = TrapShape::Line;
+        trap.increment_angle(90.0);
+        assert!((distance(&trap) - 1.0).abs() < 1e-12);
+
+        assert_eq!(
+            get_trap_shape_by_name("pick"),
+            Some(TrapShape::PickoverStalk)
+        );
+        assert_eq!(TrapShape::Circle.index(), 3);
+    }
+}
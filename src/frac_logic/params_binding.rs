@@ -1,3 +1,7 @@
+use rug::{Complex, Float};
+
+use crate::helpers::polynomial::MAX_POLY_DEGREE;
+
 #[derive(bytemuck::NoUninit, Clone, Copy)]
 #[repr(C)]
 pub(crate) struct ParamsBinding {
@@ -9,4 +13,40 @@ pub(crate) struct ParamsBinding {
     pub(crate) bailout: f32,              // 4 bytes (moved next to `cell_size` for alignment)
     pub(crate) julia_constant: [f32; 2],  // 8 bytes
     pub(crate) mandel_constant: [f32; 2], // 8 bytes
+    pub(crate) exponent: f32,             // 4 bytes
+    pub(crate) newton_degree: i32,        // 4 bytes
+    pub(crate) pos_lo: [f32; 2],          // 8 bytes, low parts of the double-single position
+    pub(crate) cell_size_lo: f32,         // 4 bytes
+    pub(crate) one: f32,                  // 4 bytes, always 1
+    pub(crate) distance_estimation: i32,  // 4 bytes, 1 to compute the distance estimation
+    pub(crate) trap_shape: i32,           // 4 bytes, the index of the trap shape, or -1 if disabled
+    pub(crate) trap_center: [f32; 2],     // 8 bytes
+    pub(crate) trap_size: f32,            // 4 bytes
+    pub(crate) trap_rotation: [f32; 2],   // 8 bytes, cosine and sine of the rotation
+    pub(crate) interior_mode: i32,        // 4 bytes, the index of the interior mode, 0 if flat
+    pub(crate) escape_norm: i32,          // 4 bytes, the index of the escape norm
+    pub(crate) _padding: i32,             // 4 bytes, the following arrays must be aligned to 16
+    // One complex per 16 bytes element, only the first two values are used
+    pub(crate) newton_coefs: [[f32; 4]; MAX_POLY_DEGREE + 1],
+    pub(crate) newton_roots: [[f32; 4]; MAX_POLY_DEGREE],
+}
+
+/// Splits a number in two `f32` whose sum is as close as possible to it,
+/// as used by the double-single shaders.
+pub(crate) fn split_f32(value: &Float) -> [f32; 2] {
+    let hi = value.to_f32();
+    if !hi.is_finite() {
+        return [hi, 0.0];
+    }
+    [hi, Float::with_val(value.prec(), value - hi).to_f32()]
+}
+
+/// Converts a list of complex numbers to an array that can be used in the uniform buffer.
+pub(crate) fn complex_array<const N: usize>(values: &[Complex]) -> [[f32; 4]; N] {
+    let mut array = [[0.0; 4]; N];
+    for (elem, value) in array.iter_mut().zip(values) {
+        elem[0] = value.real().to_f32();
+        elem[1] = value.imag().to_f32();
+    }
+    array
 }
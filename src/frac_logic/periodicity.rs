@@ -0,0 +1,100 @@
+//! Contains the periodicity checking, which stops the iteration of the points
+//! whose sequence repeats itself, since they will never escape.
+//! https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set#Periodicity_checking
+
+use super::RenderSettings;
+
+/// Detects when a sequence repeats itself exactly, with Brent's algorithm: each term is
+/// compared with a saved one, which is replaced each time the index reaches a power of two.
+/// The iterations are deterministic, so a sequence that repeated itself once will never
+/// escape, and the point can be skipped without changing its result.
+pub(crate) struct PeriodicityCheck<Z> {
+    saved: Option<Z>,
+}
+
+impl<Z: PartialEq + Clone> PeriodicityCheck<Z> {
+    pub(crate) fn new() -> Self {
+        Self { saved: None }
+    }
+
+    /// Returns true if the term of index `n` is equal to the saved one.
+    pub(crate) fn repeats(&mut self, z: &Z, n: i32) -> bool {
+        if self.saved.as_ref() == Some(z) {
+            return true;
+        }
+        if n & (n - 1) == 0 {
+            self.saved = Some(z.clone());
+        }
+        false
+    }
+}
+
+impl RenderSettings {
+    /// Returns true if the points known to never escape can stop before the maximum number
+    /// of iterations, which is not the case when the interior is colored from their sequences.
+    pub(crate) fn use_interior_checks(&self) -> bool {
+        self.interior_tracker().is_none()
+    }
+
+    /// Returns true if the Mandelbrot set can skip the points of its main cardioid and
+    /// of its period-2 bulb, whose shapes are only known for the default formula.
+    pub(crate) fn use_main_components_check(&self) -> bool {
+        self.use_interior_checks() && self.exponent == 2.0 && self.mandel_constant.is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::Float;
+
+    use super::*;
+    use crate::frac_logic::{native::NativeRender, CanvasCoords, InteriorMode};
+    use crate::fractals::{get_frac_index_by_name, FRACTALS};
+
+    #[test]
+    fn test_periodicity_check() {
+        // The sequence of -1 is 0, -1, 0, -1...
+        let mut check = PeriodicityCheck::new();
+        assert!(!check.repeats(&-1.0, 1));
+        assert!(!check.repeats(&0.0, 2));
+        assert!(!check.repeats(&-1.0, 3));
+        assert!(check.repeats(&0.0, 4));
+    }
+
+    #[test]
+    fn test_interior_checks_do_not_change_results() {
+        for frac in ["mandelbrot", "julia", "burning", "tricorn", "celtic"] {
+            for prec in [53, 106, 128] {
+                let mut rs = RenderSettings {
+                    frac_index: get_frac_index_by_name(frac).unwrap(),
+                    canvas_size: CanvasCoords::new(20, 20),
+                    max_iter: 300,
+                    ..Default::default()
+                };
+                rs.set_decimal_prec(prec);
+                rs.set_width(Float::with_val(prec, 4.0));
+                // The interior coloring disables the checks, but not the escape.
+                let mut unchecked = rs.clone();
+                unchecked.interior.mode = InteriorMode::Period;
+                assert!(rs.use_interior_checks() && !unchecked.use_interior_checks());
+
+                let native = NativeRender::new(&rs, &rs.cell_size);
+                let native_unchecked = NativeRender::new(&unchecked, &unchecked.cell_size);
+                for coords in
+                    (-10..10).flat_map(|x| (-10..10).map(move |y| CanvasCoords::new(x, y)))
+                {
+                    let c = rs.coord_to_c(coords.clone());
+                    assert_eq!(
+                        (rs.get_frac_clos())(c.clone(), &rs).iter,
+                        (unchecked.get_frac_clos())(c, &unchecked).iter,
+                        "{} at {prec} bits",
+                        FRACTALS[rs.frac_index].name
+                    );
+                    if let (Some(native), Some(unchecked)) = (&native, &native_unchecked) {
+                        assert_eq!(native.diverg(&coords).iter, unchecked.diverg(&coords).iter);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! Contains the perturbation theory renderer, used for deep zooms in the Mandelbrot set.
+//! A single reference orbit is computed with arbitrary precision, and the orbits of the
+//! other points are computed as small `f64` deltas from it, which is much faster than
+//! iterating each point with `rug`.
+//! https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set#Perturbation_theory_and_series_approximation
+
+use rayon::prelude::*;
+use rug::ops::PowAssign;
+use rug::{Complex, Float};
+
+use crate::fractals::{in_main_components, MANDELBROT};
+use crate::helpers::{NativeComplex, Vec2};
+
+use super::{
+    void_diverg, CanvasCoords, Diverg, EscapeNorm, InteriorTracker, RenderSettings, TrapTracker,
+};
+
+/// Perturbation is used automatically when the cell size is below this value,
+/// from where rendering with `rug` starts to be really slow.
+const PERTURBATION_MAX_CELL_SIZE: f64 = 1e-13;
+/// Below this cell size, the deltas cannot be represented by an `f64` anymore.
+const PERTURBATION_MIN_CELL_SIZE: f64 = 1e-290;
+/// The number of points tried along each axis when looking for a reference point.
+const REFERENCE_PROBES: i32 = 9;
+/// The series approximation is used as long as its third order term
+/// is this much smaller than the first order term.
+const SA_TOLERANCE: f64 = 1e-12;
+
+type C64 = NativeComplex<f64>;
+
+/// Returns `(Z + δ)ᵈ - Zᵈ`, without computing `(Z + δ)ᵈ`, which would lose the precision of `δ`.
+fn perturb(z: C64, delta: C64, exponent: i32) -> C64 {
+    if exponent == 2 {
+        return (z.scale(2.0) + delta) * delta;
+    }
+
+    // Expand the binomial (Z + δ)ᵈ - Zᵈ = Σₖ C(d, k) Zᵈ⁻ᵏ δᵏ for k from 1 to d,
+    // with Horner's method on δ, starting from the highest power.
+    let mut sum = C64::one();
+    let mut z_pow = C64::one();
+    let mut binomial = 1.0;
+    for k in (1..exponent).rev() {
+        z_pow = z_pow * z;
+        binomial = binomial * (k + 1) as f64 / (exponent - k) as f64;
+        sum = sum * delta + z_pow.scale(binomial);
+    }
+    sum * delta
+}
+
+/// Everything needed to render the points of a frame with perturbation.
+pub(crate) struct Perturbation {
+    /// The canvas coordinates of the reference point.
+    reference: Vec2<i32>,
+    /// The orbit of the reference point, until it escapes or reaches the maximum iteration count.
+    orbit: Vec<C64>,
+    exponent: i32,
+    bailout: f64,
+    escape_norm: EscapeNorm,
+    cell_size: f64,
+    /// The distance from the reference to the farthest point of the frame.
+    /// The series coefficients are scaled by its powers so that they remain in the `f64` range.
+    radius: f64,
+    /// The number of iterations skipped with the series approximation.
+    skipped: usize,
+    /// The scaled coefficients of the series approximation, after the skipped iterations.
+    series: [C64; 3],
+    /// Whether the derivatives of the points are computed, for distance estimation.
+    distance_estimation: bool,
+    /// The orbit trap tracker, copied by each point, if the orbit trap coloring is used.
+    trap_tracker: Option<TrapTracker>,
+    /// The interior tracker, copied by each point, if an interior coloring mode is used.
+    interior_tracker: Option<InteriorTracker>,
+    /// Whether the points of the main cardioid and of the period-2 bulb are skipped.
+    main_components_check: bool,
+}
+
+impl RenderSettings {
+    /// Returns true if the frame should be rendered with perturbation.
+    pub(crate) fn use_perturbation(&self, cell_size: &Float) -> bool {
+        self.perturbation
+            && self.get_frac_obj().name == MANDELBROT.name
+            // Perturbation relies on the binomial expansion of (Z + δ)ᵈ.
+            && self.exponent.fract() == 0.0
+            && self.exponent >= 2.0
+            // Rebasing assumes that the orbits start from zero.
+            && self.mandel_constant.is_zero()
+            && (PERTURBATION_MIN_CELL_SIZE..PERTURBATION_MAX_CELL_SIZE)
+                .contains(&cell_size.to_f64())
+    }
+
+    /// Returns the canvas coordinates of a good reference point: the center of the canvas
+    /// if it does not escape, else the point of a coarse grid with the most iterations.
+    fn find_reference(&self, size: &Vec2<i32>, cell_size: &Float) -> Vec2<i32> {
+        let iter = |coords: &Vec2<i32>| {
+            let diverg = (MANDELBROT.get)(
+                self.coord_to_c_with_cell_size(CanvasCoords::new(coords.x, coords.y), cell_size),
+                self,
+            );
+            // Points that never escape are the best references.
+            if diverg.is_void() {
+                i32::MAX
+            } else {
+                diverg.iter
+            }
+        };
+
+        let center = Vec2::new(0, 0);
+        if iter(&center) == i32::MAX {
+            return center;
+        }
+
+        let probe = |i: i32, len: i32| -len / 2 + i * len / (REFERENCE_PROBES - 1);
+        (0..REFERENCE_PROBES * REFERENCE_PROBES)
+            .into_par_iter()
+            .map(|i| {
+                Vec2::new(
+                    probe(i % REFERENCE_PROBES, size.x),
+                    probe(i / REFERENCE_PROBES, size.y),
+                )
+            })
+            .chain([center])
+            .max_by_key(iter)
+            .unwrap()
+    }
+}
+
+impl Perturbation {
+    /// Compute the reference orbit and the series approximation for a frame.
+    pub(crate) fn new(rs: &RenderSettings, size: &Vec2<i32>, cell_size: &Float) -> Self {
+        let exponent = rs.exponent as i32;
+        let reference = rs.find_reference(size, cell_size);
+
+        // Compute the reference orbit with arbitrary precision.
+        let c = rs.coord_to_c_with_cell_size(
+            CanvasCoords::new(reference.x, reference.y),
+            cell_size,
+        );
+        let mut z = Complex::new(rs.prec);
+        let mut orbit = vec![C64::from_rug(&z)];
+        while orbit.len() <= rs.max_iter as usize {
+            z.pow_assign(exponent);
+            z += &c;
+            orbit.push(C64::from_rug(&z));
+            if rs.escaped(&z) {
+                break;
+            }
+        }
+
+        // The frame corners are the farthest points from the reference.
+        let cell_size = cell_size.to_f64();
+        let radius = [-size.x / 2, size.x - size.x / 2]
+            .into_iter()
+            .flat_map(|x| [-size.y / 2, size.y - size.y / 2].map(|y| (x, y)))
+            .map(|(x, y)| {
+                C64::new(
+                    (x - reference.x) as f64 * cell_size,
+                    (y - reference.y) as f64 * cell_size,
+                )
+                .abs()
+            })
+            .fold(0.0, f64::max);
+
+        let mut perturbation = Self {
+            reference,
+            orbit,
+            exponent,
+            bailout: rs.bailout as f64,
+            escape_norm: rs.escape_norm,
+            cell_size,
+            radius,
+            skipped: 0,
+            series: [C64::default(); 3],
+            distance_estimation: rs.use_distance_estimation(),
+            trap_tracker: rs.trap_tracker(),
+            interior_tracker: rs.interior_tracker(),
+            main_components_check: rs.use_main_components_check(),
+        };
+        // The orbit traps and the interior coloring need every term of the orbits,
+        // none can be skipped.
+        if perturbation.trap_tracker.is_none() && perturbation.interior_tracker.is_none() {
+            perturbation.series_approximation();
+        }
+        perturbation
+    }
+
+    /// Find how many iterations can be skipped by approximating the deltas with a
+    /// third degree polynomial of `δc`: `δzₙ ≈ Aₙδc + Bₙδc² + Cₙδc³`.
+    /// The coefficients are stored multiplied by the powers of the radius: `(Aₙr, Bₙr², Cₙr³)`.
+    fn series_approximation(&mut self) {
+        let d = self.exponent;
+        let binomial2 = (d * (d - 1) / 2) as f64;
+        let binomial3 = (d * (d - 1) * (d - 2) / 6) as f64;
+
+        let [mut a, mut b, mut c] = self.series;
+        for n in 0..self.orbit.len() - 1 {
+            let z = self.orbit[n];
+            // The derivative of Zᵈ, and the next terms of the expansion.
+            let z1 = z.powi(d - 1).scale(d as f64);
+            let z2 = z.powi(d - 2).scale(binomial2);
+            let z3 = if d >= 3 {
+                z.powi(d - 3).scale(binomial3)
+            } else {
+                C64::default()
+            };
+
+            let next_a = z1 * a + C64::new(self.radius, 0.0);
+            let next_b = z1 * b + z2 * a * a;
+            let next_c = z1 * c + z2 * a * b.scale(2.0) + z3 * a * a * a;
+
+            // Stop when the approximation is not accurate anymore,
+            // or when some points of the frame could escape.
+            let max_modulus =
+                self.orbit[n + 1].abs() + next_a.abs() + next_b.abs() + next_c.abs();
+            if next_c.abs() > SA_TOLERANCE * next_a.abs()
+                || max_modulus * self.escape_norm.max_ratio() >= self.bailout
+            {
+                break;
+            }
+
+            (a, b, c) = (next_a, next_b, next_c);
+            self.skipped = n + 1;
+            self.series = [a, b, c];
+        }
+    }
+
+    /// Returns the divergence of the point at the given canvas coordinates,
+    /// or `None` if perturbation failed for this point and it must be computed directly.
+    pub(crate) fn diverg(&self, coords: &CanvasCoords, max_iter: i32) -> Option<Diverg> {
+        let dc = C64::new(
+            (coords.x - self.reference.x) as f64 * self.cell_size,
+            (coords.y - self.reference.y) as f64 * self.cell_size,
+        );
+        // The second term of the reference orbit is the reference point itself.
+        if self.main_components_check && in_main_components(self.orbit[1] + dc) {
+            return Some(Diverg::new(-1));
+        }
+
+        // Start from the series approximation.
+        let u = if self.radius == 0.0 {
+            C64::default()
+        } else {
+            dc.scale(1.0 / self.radius)
+        };
+        let [a, b, c] = self.series;
+        let mut dz = a * u + b * u * u + c * u * u * u;
+        // The derivative of the points with respect to `c`, which is the
+        // derivative of the series with respect to `δc`.
+        let mut der = self.distance_estimation.then(|| {
+            if self.radius == 0.0 {
+                C64::default()
+            } else {
+                (a + b * u.scale(2.0) + c * u * u.scale(3.0)).scale(1.0 / self.radius)
+            }
+        });
+
+        let mut trap = self.trap_tracker;
+        let mut interior = self.interior_tracker;
+
+        let mut m = self.skipped;
+        let mut n = self.skipped as i32;
+        while n < max_iter {
+            let z = self.orbit[m] + dz;
+            // The starting point is not part of the tracked orbit, like in the direct rendering.
+            if let Some(trap) = trap.as_mut().filter(|_| n > 0) {
+                trap.track(z);
+            }
+            if let Some(interior) = interior.as_mut().filter(|_| n > 0) {
+                interior.track(z);
+            }
+            if self.escape_norm.escaped(z.re, z.im, self.bailout) {
+                let mag = z.abs();
+                let mut diverg = Diverg::with_mag(n, mag as f32);
+                // The distance is converted to cells here, since it may be too small for an `f32`.
+                if let Some(der) = der {
+                    diverg.dist = (mag * mag.ln() / der.abs() / self.cell_size) as f32;
+                }
+                if let Some(trap) = &trap {
+                    diverg.trap = trap.min_distance();
+                }
+                return Some(diverg);
+            }
+
+            // When the delta is bigger than the point itself, restart from the beginning
+            // of the reference orbit, which avoids the precision loss causing glitches.
+            if z.norm_sqr() < dz.norm_sqr() {
+                dz = z;
+                m = 0;
+            }
+
+            // The reference escaped before the point, the result would be wrong.
+            if m + 1 >= self.orbit.len() {
+                return None;
+            }
+
+            if let Some(der) = &mut der {
+                *der = z.powi(self.exponent - 1).scale(self.exponent as f64) * *der + C64::one();
+            }
+            dz = perturb(self.orbit[m], dz, self.exponent) + dc;
+            m += 1;
+            n += 1;
+        }
+
+        // The last term is not tracked by the loop.
+        if let Some(interior) = &mut interior {
+            interior.track(self.orbit[m] + dz);
+        }
+        Some(void_diverg(&interior))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that most points are rendered with perturbation, with the
+    /// same number of iterations as the direct rendering.
+    fn assert_matches_direct_rendering(rs: &RenderSettings, size: &Vec2<i32>) {
+        let cell_size = rs.cell_size_from_height(size.y);
+        assert!(rs.use_perturbation(&cell_size));
+        let perturbation = Perturbation::new(rs, size, &cell_size);
+
+        let mut served = 0;
+        for y in -size.y / 2..size.y - size.y / 2 {
+            for x in -size.x / 2..size.x - size.x / 2 {
+                let coords = CanvasCoords::new(x, y);
+                let direct =
+                    (MANDELBROT.get)(rs.coord_to_c_with_cell_size(coords.clone(), &cell_size), rs);
+                // Glitched points are computed directly.
+                if let Some(diverg) = perturbation.diverg(&coords, rs.max_iter) {
+                    assert_eq!(diverg.iter, direct.iter, "at {x}, {y}");
+                    served += 1;
+                }
+            }
+        }
+        assert!(served * 10 > size.x * size.y * 9);
+    }
+
+    #[test]
+    fn test_perturb() {
+        let z = C64::new(0.3, -0.7);
+        let delta = C64::new(1e-3, 2e-3);
+        for exponent in 2..6 {
+            let expected = (z + delta).powi(exponent) - z.powi(exponent);
+            assert!((perturb(z, delta, exponent) - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_perturbation_matches_direct_rendering() {
+        let mut rs = RenderSettings::default();
+        let size = Vec2::new(24, 16);
+        rs.canvas_size = CanvasCoords::new(size.x, size.y);
+        rs.set_decimal_prec(128);
+        rs.max_iter = 1000;
+        // A Misiurewicz point, which never escapes but has escaping points all around it.
+        rs.pos = Complex::with_val(128, (0, 1));
+        rs.set_width(Float::with_val(128, 1e-20));
+        assert_matches_direct_rendering(&rs, &size);
+
+        // Move the center outside of the set, so that the points must be rebased.
+        rs.pos += Complex::with_val(128, (3e-19, 1e-19));
+        assert_matches_direct_rendering(&rs, &size);
+
+        // Higher exponents use the generic expansion.
+        rs.exponent = 3.0;
+        rs.pos = Complex::with_val(128, (0.34062501931660666, 1.2712298784187062));
+        rs.set_width(Float::with_val(128, 1e-14));
+        assert_matches_direct_rendering(&rs, &size);
+    }
+}
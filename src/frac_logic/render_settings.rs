@@ -4,8 +4,11 @@ use image::ImageFormat;
 use rug::{Complex, Float};
 
 use crate::app_state::hsl_settings::HSLSettings;
-use crate::frac_logic::CanvasCoords;
-use crate::fractals::FRACTALS;
+use crate::frac_logic::{
+    BuddhabrotSettings, CanvasCoords, EscapeNorm, InteriorSettings, OrbitTrap, SubdivisionSettings,
+};
+use crate::fractals::{Formula, FRACTALS};
+use crate::helpers::Polynomial;
 
 use super::WgpuState;
 
@@ -15,6 +18,9 @@ const DEFAULT_JULIA_CONSTANT: (f32, f32) = (-9.9418604e-1, 2.61627e-1);
 const DEFAULT_MANDEL_CONSTANT: (f32, f32) = (0.0, 0.0);
 const DEFAULT_SMOOTHNESS: i32 = 7;
 const DEFAULT_BAILOUT: f32 = 2.0;
+const DEFAULT_EXPONENT: f64 = 2.0;
+const DEFAULT_DISTANCE_THRESHOLD: f64 = 0.5;
+const DEFAULT_REFINEMENT: [i32; 2] = [8, 2];
 
 /// Used to group values related to fractal rendering logic.
 #[derive(Clone, Debug)]
@@ -36,14 +42,41 @@ pub(crate) struct RenderSettings {
     pub(crate) palette_index: usize,
     pub(crate) color_scheme_offset: i32,
     pub(crate) void_fill_index: usize,
+    /// The index of the selected coloring mode (banded or smooth).
+    pub(crate) coloring_mode_index: usize,
+    /// With distance estimation, the points closer to the set than this
+    /// distance (in cells) are colored as the set.
+    pub(crate) distance_threshold: f64,
+    /// The trap used by the orbit trap coloring mode.
+    pub(crate) orbit_trap: OrbitTrap,
+    /// The coloring of the points that reach the maximum number of iterations.
+    pub(crate) interior: InteriorSettings,
     pub(crate) wgpu_state: WgpuState,
     pub(crate) image_format: ImageFormat,
     pub(crate) julia_constant: Complex,
     pub(crate) mandel_constant: Complex,
+    /// The sequences escape when the escape norm of a term reaches the bailout.
     pub(crate) bailout: f32,
+    pub(crate) escape_norm: EscapeNorm,
+    /// The exponent `d` used in the `Uₙᵈ` term of the fractal formulas.
+    pub(crate) exponent: f64,
+    /// The polynomial whose roots are searched by the Newton fractal.
+    pub(crate) newton_poly: Polynomial,
+    /// The user-defined formula iterated by the Formula pseudo-fractal.
+    pub(crate) formula: Formula,
     pub(crate) smoothness: i32,
+    /// Whether or not to use perturbation for deep zooms in the Mandelbrot set.
+    pub(crate) perturbation: bool,
+    /// Whether the CPU renders use the Mariani–Silver subdivision.
+    pub(crate) subdivision: SubdivisionSettings,
+    /// The downscaling factors of the previews rendered before the canvas in CPU mode,
+    /// from the coarsest one. The canvas is rendered directly if it is empty.
+    pub(crate) refinement: Vec<i32>,
     /// The limit of size (in lines) for a render pass.
     pub(crate) chunk_size_limit: Option<i32>,
+    /// The Buddhabrot and Nebulabrot settings, used instead of the divergence
+    /// matrix when a density mode is selected.
+    pub(crate) buddhabrot: BuddhabrotSettings,
 }
 
 impl Default for RenderSettings {
@@ -59,12 +92,24 @@ impl Default for RenderSettings {
             color_scheme_offset: Default::default(),
             palette_index: 4,
             void_fill_index: Default::default(),
+            coloring_mode_index: Default::default(),
+            distance_threshold: DEFAULT_DISTANCE_THRESHOLD,
+            orbit_trap: OrbitTrap::default(),
+            interior: InteriorSettings::default(),
             wgpu_state: WgpuState::default(),
             julia_constant: Complex::with_val(DF_PREC_GPU, DEFAULT_JULIA_CONSTANT),
             mandel_constant: Complex::with_val(DF_PREC_GPU, DEFAULT_MANDEL_CONSTANT),
             bailout: DEFAULT_BAILOUT,
+            escape_norm: EscapeNorm::Euclidean,
+            exponent: DEFAULT_EXPONENT,
+            newton_poly: Polynomial::default(),
+            formula: Formula::default(),
             smoothness: DEFAULT_SMOOTHNESS,
+            perturbation: true,
+            subdivision: SubdivisionSettings::default(),
+            refinement: DEFAULT_REFINEMENT.to_vec(),
             chunk_size_limit: None,
+            buddhabrot: BuddhabrotSettings::default(),
             hsl_settings: Default::default(),
         }
     }
@@ -1,19 +1,24 @@
 //! Contains the `RenderSettings` methods.
 
+use std::f32::consts::TAU;
 use std::sync::mpsc::Sender;
 
 use rand::{thread_rng, Rng};
 use ratatui::style::Color;
-use rug::Float;
+use rug::{Complex, Float};
+use strum::IntoEnumIterator;
 
 use crate::app::SlaveMessage;
 use crate::app_state::hsl_settings::MAX_HSL_VALUE;
 use crate::colors::{self, Palette, COLORS};
+use crate::commands::exponent::{MAX_EXPONENT, MIN_EXPONENT};
 use crate::commands::max_iter::{MAX_MAX_ITER, MIN_MAX_ITER};
 use crate::commands::prec::{MAX_DECIMAL_PREC, MIN_DECIMAL_PREC};
-use crate::helpers::{decrement_wrap, increment_wrap, void_fills, VoidFill};
+use crate::helpers::{decrement_wrap, increment_wrap, void_fills, ColoringMode, VoidFill};
 
-use super::RenderSettings;
+use super::{
+    Diverg, InteriorMode, InteriorTracker, RenderSettings, TrapShape, TrapTracker, NO_BASIN,
+};
 
 const DF_PREC_CPU: u32 = 32;
 const DF_MAX_ITER_CPU: i32 = 64;
@@ -63,12 +68,18 @@ impl RenderSettings {
         self.max_iter = max_iter
     }
 
+    /// Increment positively or negatively the exponent, while staying within the fixed bounds.
+    pub(crate) fn increment_exponent(&mut self, increment: f64) {
+        self.exponent = (self.exponent + increment).clamp(MIN_EXPONENT, MAX_EXPONENT);
+    }
+
     /// Changes the selected fractal. Will update the GPU render pipeline if GPU mode
     /// is enabled, if then an error is met, GPU mode will be disabled and an error message will be
     /// returned. Note that this method will never fail, even though it can return an error
     /// message.
     pub(crate) fn select_fractal(&mut self, frac_i: usize) -> Result<(), String> {
         self.frac_index = frac_i;
+        self.wgpu_state.formula_shader = self.formula.shader_source();
         if self.wgpu_state.use_gpu {
             if let Err(err) = self.wgpu_state.set_cs(self.get_frac_obj().name) {
                 self.wgpu_state.use_gpu = false;
@@ -85,6 +96,7 @@ impl RenderSettings {
         &mut self,
         sender: Option<&Sender<SlaveMessage>>,
     ) -> Result<(), String> {
+        self.wgpu_state.formula_shader = self.formula.shader_source();
         self.wgpu_state
             .initialize(self.get_frac_obj().name, sender)
             .await
@@ -95,14 +107,75 @@ impl RenderSettings {
         &COLORS[self.palette_index]
     }
 
-    /// Returns a color corresponding to the given iteration count, using
+    /// Returns the palette used by the interior coloring modes.
+    pub(crate) fn get_interior_palette(&self) -> &'static Palette {
+        &COLORS[self.interior.palette_index]
+    }
+
+    /// Returns the selected coloring mode.
+    pub(crate) fn coloring_mode(&self) -> ColoringMode {
+        ColoringMode::iter()
+            .nth(self.coloring_mode_index)
+            .unwrap_or(ColoringMode::Banded)
+    }
+
+    /// Returns true if the fractals should compute the distance estimation.
+    /// The derivative of `zᵈ` is not meaningful for exponents up to 1.
+    pub(crate) fn use_distance_estimation(&self) -> bool {
+        self.coloring_mode() == ColoringMode::Distance
+            && self.get_frac_obj().distance_estimation
+            && self.exponent > 1.0
+    }
+
+    /// Returns a tracker for the orbit trap, if the orbit trap coloring mode is selected.
+    pub(crate) fn trap_tracker(&self) -> Option<TrapTracker> {
+        (self.coloring_mode() == ColoringMode::OrbitTrap)
+            .then(|| TrapTracker::new(&self.orbit_trap))
+    }
+
+    /// Returns true if a term of a sequence escaped, the same way as the native
+    /// types and the shaders.
+    pub(crate) fn escaped(&self, z: &Complex) -> bool {
+        self.escape_norm
+            .escaped(z.real().to_f64(), z.imag().to_f64(), self.bailout as f64)
+    }
+
+    /// Returns true if the points that do not escape are colored by the interior coloring
+    /// mode, rather than filled with the void fill.
+    pub(crate) fn use_interior_coloring(&self) -> bool {
+        self.interior.mode != InteriorMode::Flat && self.get_frac_obj().interior_coloring
+    }
+
+    /// Returns a tracker for the interior, if an interior coloring mode is used.
+    pub(crate) fn interior_tracker(&self) -> Option<InteriorTracker> {
+        self.use_interior_coloring()
+            .then(|| InteriorTracker::new(self.interior.mode))
+    }
+
+    /// Returns the number of convergence basins of the selected fractal.
+    pub(crate) fn basin_count(&self) -> i32 {
+        self.newton_poly.degree() as i32
+    }
+
+    /// Returns a color corresponding to the given divergence, using
     /// the currently selected color palette or hsl mode.
-    pub(crate) fn color_from_div(&self, diverg: &i32) -> Color {
-        let palette = self.get_palette();
+    pub(crate) fn color_from_div(&self, diverg: &Diverg) -> Color {
         let mut rng = thread_rng();
         let void_fills_ = void_fills();
+        let distance = diverg.basin == NO_BASIN && self.use_distance_estimation();
+
+        if diverg.is_void() && self.use_interior_coloring() {
+            return colors::palette_color(
+                self.interior_value(diverg),
+                0,
+                self.get_interior_palette(),
+                self.smoothness,
+            );
+        }
 
-        if *diverg == -1 {
+        // With distance estimation, the points that are close enough
+        // to the set are colored as the set, which gives crisp boundaries.
+        if diverg.is_void() || (distance && (diverg.dist as f64) < self.distance_threshold) {
             // Return void color
 
             return match void_fills_[self.void_fill_index] {
@@ -110,7 +183,7 @@ impl RenderSettings {
                 VoidFill::Black => BLACK,
                 VoidFill::White => WHITE,
                 // Same as if the div was 0
-                VoidFill::ColorScheme => self.color_from_div(&0),
+                VoidFill::ColorScheme => self.color_from_value(0.0),
                 VoidFill::RGBNoise => Color::Rgb(
                     rng.gen_range(0..255),
                     rng.gen_range(0..255),
@@ -121,19 +194,51 @@ impl RenderSettings {
                 VoidFill::BlueNoise => Color::Rgb(0, 0, rng.gen_range(0..255)),
             };
         }
+
+        if diverg.basin != NO_BASIN {
+            return self.color_from_basin(diverg);
+        }
+
+        if distance {
+            return self.color_from_value(self.distance_value(diverg));
+        }
+        if self.coloring_mode() == ColoringMode::OrbitTrap {
+            return self.color_from_value(self.trap_value(diverg));
+        }
+        self.color_from_value(self.color_iter(diverg))
+    }
+
+    /// Returns true if the two points are colored from the same values by `color_from_div`,
+    /// which is how the subdivision decides that a rectangle can be filled.
+    pub(crate) fn same_coloring(&self, a: &Diverg, b: &Diverg) -> bool {
+        if a.iter != b.iter || a.basin != b.basin {
+            return false;
+        }
+        if a.is_void() {
+            return !self.use_interior_coloring() || a.interior == b.interior;
+        }
+        // The basins and the bands are only colored by their iteration count, and
+        // the fields that are not used by the coloring mode are left to zero.
+        a.basin != NO_BASIN
+            || self.coloring_mode() == ColoringMode::Banded
+            || (a.mag == b.mag && a.dist == b.dist && a.trap == b.trap)
+    }
+
+    /// Returns the color at the given position of the palette, or of the hue circle in hsl mode.
+    fn color_from_value(&self, value: f32) -> Color {
         // If hsl mode is disabled, get the color using the palette
         if !self.hsl_settings.enabled {
             return colors::palette_color(
-                *diverg,
+                value,
                 self.color_scheme_offset,
-                palette,
+                self.get_palette(),
                 self.smoothness,
             );
         }
 
         Color::from_hsl(
             // I tried to implement a logarithmic scale, this is a draft implementation
-            (*diverg as f64 / 10.0f64.powf(self.hsl_settings.smoothness as f64/30.0) * 30.0
+            (value as f64 / 10.0f64.powf(self.hsl_settings.smoothness as f64/30.0) * 30.0
                 // The transifion from an offset of 100 and an offset of 0 should not
                 // be visible, it should make a complete loop
                 + self.hsl_settings.hue_offset as f64 * 3.6)
@@ -143,4 +248,202 @@ impl RenderSettings {
             self.hsl_settings.lum as f64 / MAX_HSL_VALUE as f64 * 100.0,
         )
     }
+
+    /// Returns the position in the palette of a point colored by its estimated
+    /// distance to the set: the color changes each time the distance doubles.
+    fn distance_value(&self, diverg: &Diverg) -> f32 {
+        if !diverg.dist.is_finite() {
+            return 0.0;
+        }
+        (1.0 + diverg.dist.max(0.0)).log2() * self.smoothness as f32
+    }
+
+    /// Returns the position in the palette of a point colored by the minimum distance
+    /// between its orbit and the trap: the closer the orbit, the further in the palette.
+    fn trap_value(&self, diverg: &Diverg) -> f32 {
+        let dist = diverg.trap as f64;
+        let size = self.orbit_trap.size;
+        // Outside of the stalks, the points are colored by their iteration count.
+        if self.orbit_trap.shape == TrapShape::PickoverStalk && dist >= size {
+            return self.color_iter(diverg);
+        }
+        let value = (1.0 + size / dist).log2() * self.smoothness as f64;
+        if !value.is_finite() {
+            return 0.0;
+        }
+        value as f32
+    }
+
+    /// Returns the position in the interior palette of a point that did not escape. The
+    /// magnitude and the argument span the palette once, while each period or atom domain
+    /// gets its own color.
+    fn interior_value(&self, diverg: &Diverg) -> f32 {
+        let palette_len =
+            (self.get_interior_palette().colors.len() as i32 * self.smoothness) as f32;
+        let value = match self.interior.mode {
+            InteriorMode::Flat => 0.0,
+            // The orbits that do not escape stay within the bailout.
+            InteriorMode::Magnitude => diverg.interior / self.bailout * palette_len,
+            InteriorMode::Argument => (diverg.interior / TAU + 0.5) * palette_len,
+            InteriorMode::Period | InteriorMode::AtomDomain => {
+                diverg.interior * self.smoothness as f32
+            }
+        };
+        if !value.is_finite() {
+            return 0.0;
+        }
+        value.max(0.0)
+    }
+
+    /// Returns the iteration count used to color a divergent point. In smooth mode, the
+    /// fractional part is deduced from the final modulus of the point, so that the
+    /// count varies continuously across the plane.
+    /// https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set#Continuous_(smooth)_coloring
+    pub(crate) fn color_iter(&self, diverg: &Diverg) -> f32 {
+        let iter = diverg.iter as f32;
+        if self.coloring_mode() == ColoringMode::Banded {
+            return iter;
+        }
+
+        // Far from the origin, each iteration raises the modulus to the power d, so
+        // log_d(ln|z|) grows by one per iteration: ν = n + 1 - log_d(ln|z| / ln(bailout)).
+        let degree = self.exponent;
+        let bailout = self.bailout as f64;
+        let mag = diverg.mag as f64;
+        if degree <= 1.0 || bailout <= 1.0 || mag <= 1.0 {
+            return iter;
+        }
+        let smooth = iter as f64 + 1.0 - (mag.ln() / bailout.ln()).ln() / degree.ln();
+        if !smooth.is_finite() {
+            return iter;
+        }
+        smooth.max(0.0) as f32
+    }
+
+    /// Returns a color corresponding to the basin a point converged to,
+    /// shaded by the number of iterations.
+    fn color_from_basin(&self, diverg: &Diverg) -> Color {
+        if !self.hsl_settings.enabled {
+            return colors::basin_color(
+                diverg.basin,
+                self.basin_count(),
+                diverg.iter,
+                self.color_scheme_offset,
+                self.get_palette(),
+                self.smoothness,
+            );
+        }
+
+        // The basins are spread evenly on the hue circle, and the shading lowers the luminosity.
+        let shade = colors::basin_shade(diverg.iter, self.hsl_settings.smoothness.max(1));
+        Color::from_hsl(
+            (diverg.basin as f64 * 360.0 / self.basin_count().max(1) as f64
+                + self.hsl_settings.hue_offset as f64 * 3.6)
+                % 360.0,
+            self.hsl_settings.saturation as f64 / MAX_HSL_VALUE as f64 * 100.0,
+            self.hsl_settings.lum as f64 / MAX_HSL_VALUE as f64 * 100.0 * (1.0 - shade as f64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractals::MANDELBROT;
+    use crate::helpers::coloring_modes;
+
+    #[test]
+    fn test_smooth_color_iter() {
+        let mut rs = RenderSettings {
+            coloring_mode_index: coloring_modes()
+                .iter()
+                .position(|cm| *cm == ColoringMode::Smooth)
+                .unwrap(),
+            ..Default::default()
+        };
+
+        // Walk along the real axis, away from the set: the integer counts change by
+        // steps of one, while the smooth counts should only change slightly between
+        // neighbouring points.
+        let mut previous: Option<f32> = None;
+        for i in 0..=2000 {
+            let c = Complex::with_val(rs.prec, (0.3 + i as f64 * 1e-3, 0.0));
+            let diverg = (MANDELBROT.get)(c, &rs);
+            let smooth = rs.color_iter(&diverg);
+            assert!(smooth > diverg.iter as f32 - 1.0 && smooth <= diverg.iter as f32 + 1.0);
+            if let Some(previous) = previous {
+                assert!((smooth - previous).abs() < 0.5);
+            }
+            previous = Some(smooth);
+        }
+
+        // Banded mode keeps the integer counts.
+        rs.coloring_mode_index = 0;
+        assert_eq!(rs.color_iter(&Diverg::with_mag(5, 3.0)), 5.0);
+    }
+
+    #[test]
+    fn test_distance_estimation() {
+        let rs = RenderSettings {
+            coloring_mode_index: coloring_modes()
+                .iter()
+                .position(|cm| *cm == ColoringMode::Distance)
+                .unwrap(),
+            void_fill_index: void_fills()
+                .iter()
+                .position(|vf| *vf == VoidFill::Black)
+                .unwrap(),
+            ..Default::default()
+        };
+        assert!(rs.use_distance_estimation());
+
+        // The Mandelbrot set meets the positive real axis at 1/4.
+        for x in [0.3, 0.5, 1.0, 2.0] {
+            let c = Complex::with_val(rs.prec, (x, 0.0));
+            let dist = (MANDELBROT.get)(c, &rs).dist as f64;
+            // The estimation is within a constant factor of the actual distance.
+            let actual = x - 0.25;
+            assert!(dist < actual && dist > actual / 8.0);
+        }
+
+        // The points closer to the set than the threshold are colored as the set.
+        let mut diverg = Diverg::with_mag(5, 3.0);
+        diverg.dist = rs.distance_threshold as f32 / 2.0;
+        assert_eq!(rs.color_from_div(&diverg), BLACK);
+        diverg.dist = rs.distance_threshold as f32 * 2.0;
+        assert_ne!(rs.color_from_div(&diverg), BLACK);
+    }
+
+    #[test]
+    fn test_orbit_trap() {
+        let mut rs = RenderSettings {
+            coloring_mode_index: coloring_modes()
+                .iter()
+                .position(|cm| *cm == ColoringMode::OrbitTrap)
+                .unwrap(),
+            ..Default::default()
+        };
+        let trap = |rs: &RenderSettings, x: f64| {
+            (MANDELBROT.get)(Complex::with_val(rs.prec, (x, 0.0)), rs).trap
+        };
+
+        // The orbit of 1 is 1, 2, 5, and the one of 0.5 is 0.5, 0.75, 1.0625...
+        assert_eq!(trap(&rs, 1.0), 1.0);
+        assert_eq!(trap(&rs, 0.5), 0.5);
+        rs.orbit_trap.shape = TrapShape::Circle;
+        rs.orbit_trap.size = 2.0;
+        assert_eq!(trap(&rs, 1.0), 0.0);
+
+        // The points whose orbit comes closer to the trap get a higher value.
+        let mut near = Diverg::with_mag(3, 5.0);
+        near.trap = 0.01;
+        let mut far = near;
+        far.trap = 1.0;
+        assert!(rs.trap_value(&near) > rs.trap_value(&far));
+
+        // Outside of the stalks, the points are colored by their iteration count.
+        rs.orbit_trap.shape = TrapShape::PickoverStalk;
+        far.trap = 3.0;
+        assert_eq!(rs.trap_value(&far), rs.color_iter(&far));
+    }
 }
@@ -0,0 +1,275 @@
+//! Contains the Mariani–Silver subdivision, which computes the border of a rectangle,
+//! and fills it without computing its inside when all the border points have the same result.
+//! https://en.wikibooks.org/wiki/Fractals/Iterations_in_the_complex_plane/Mariani-Silver_algorithm
+
+use std::sync::mpsc::Sender;
+
+use rayon::prelude::*;
+
+use crate::app::SlaveMessage;
+use crate::helpers::Vec2;
+
+use super::{gpu_util::SendSlaveMessage, Diverg, DivergMatrix};
+
+/// The size of the square tiles rendered in parallel, each one is subdivided independently.
+const TILE_SIZE: i32 = 32;
+/// The rectangles with a side smaller than this are computed point by point.
+const MIN_RECT_SIZE: i32 = 4;
+
+#[derive(Clone, Debug)]
+pub(crate) struct SubdivisionSettings {
+    /// Whether the canvas is rendered with subdivision in CPU mode.
+    pub(crate) canvas: bool,
+    /// Whether the captures are rendered with subdivision in CPU mode.
+    /// It is disabled by default, since the filled points are approximated.
+    pub(crate) captures: bool,
+}
+
+impl Default for SubdivisionSettings {
+    fn default() -> Self {
+        Self {
+            canvas: true,
+            captures: false,
+        }
+    }
+}
+
+/// A tile of the divergence matrix, whose points are computed when they are first needed.
+struct Tile<'a, F, U> {
+    point: &'a F,
+    /// Returns true if two points have the same color, so that the points between them can be filled.
+    same_color: &'a U,
+    /// The canvas coordinates of the first point of the tile.
+    origin: Vec2<i32>,
+    width: i32,
+    points: Vec<Option<Diverg>>,
+    /// The number of points computed so far, the others were filled.
+    computed: usize,
+}
+
+impl<'a, F: Fn(i32, i32) -> Diverg, U: Fn(&Diverg, &Diverg) -> bool> Tile<'a, F, U> {
+    fn new(point: &'a F, same_color: &'a U, origin: Vec2<i32>, size: Vec2<i32>) -> Self {
+        Self {
+            point,
+            same_color,
+            origin,
+            width: size.x,
+            points: vec![None; (size.x * size.y) as usize],
+            computed: 0,
+        }
+    }
+
+    /// Returns the divergence of a point of the tile, and computes it if needed.
+    fn get(&mut self, x: i32, y: i32) -> Diverg {
+        let index = (y * self.width + x) as usize;
+        if let Some(diverg) = self.points[index] {
+            return diverg;
+        }
+        let diverg = (self.point)(self.origin.x + x, self.origin.y + y);
+        self.points[index] = Some(diverg);
+        self.computed += 1;
+        diverg
+    }
+
+    /// Renders the rectangle between the given corners, included.
+    fn render_rect(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        // Compute the whole border, and check if all its points have the same result.
+        let first = self.get(x0, y0);
+        let mut uniform = true;
+        for x in x0..=x1 {
+            for y in [y0, y1] {
+                let diverg = self.get(x, y);
+                uniform &= (self.same_color)(&diverg, &first);
+            }
+        }
+        for y in y0 + 1..y1 {
+            for x in [x0, x1] {
+                let diverg = self.get(x, y);
+                uniform &= (self.same_color)(&diverg, &first);
+            }
+        }
+
+        if uniform || x1 - x0 < MIN_RECT_SIZE || y1 - y0 < MIN_RECT_SIZE {
+            for y in y0 + 1..y1 {
+                for x in x0 + 1..x1 {
+                    if uniform {
+                        self.points[(y * self.width + x) as usize] = Some(first);
+                    } else {
+                        self.get(x, y);
+                    }
+                }
+            }
+        } else if x1 - x0 >= y1 - y0 {
+            // Split the longest side, the middle line is shared by the two halves.
+            let mid = (x0 + x1) / 2;
+            self.render_rect(x0, y0, mid, y1);
+            self.render_rect(mid, y0, x1, y1);
+        } else {
+            let mid = (y0 + y1) / 2;
+            self.render_rect(x0, y0, x1, mid);
+            self.render_rect(x0, mid, x1, y1);
+        }
+    }
+
+    /// Returns the given row of the tile, once it is rendered.
+    fn row(&self, y: i32) -> impl Iterator<Item = Diverg> + '_ {
+        let start = (y * self.width) as usize;
+        self.points[start..start + self.width as usize]
+            .iter()
+            .map(|diverg| diverg.expect("All the points of the tile should be rendered"))
+    }
+}
+
+/// Returns a divergence matrix of the given size, where `point` computes the divergence at the
+/// given canvas coordinates, and `origin` are the coordinates of the first point of the matrix.
+/// The rectangles are filled when `same_color` is true for all the points of their border.
+/// Also returns the number of points that were computed instead of filled.
+pub(crate) fn subdivided_diverg_matrix<F, U>(
+    origin: &Vec2<i32>,
+    size: &Vec2<i32>,
+    point: &F,
+    same_color: &U,
+    sender: Option<&Sender<SlaveMessage>>,
+) -> (DivergMatrix, usize)
+where
+    F: Fn(i32, i32) -> Diverg + Sync,
+    U: Fn(&Diverg, &Diverg) -> bool + Sync,
+{
+    let band_count = (size.y + TILE_SIZE - 1) / TILE_SIZE;
+    let tile_count = (size.x + TILE_SIZE - 1) / TILE_SIZE;
+
+    let bands: Vec<(DivergMatrix, usize)> = (0..band_count)
+        .into_par_iter()
+        .map(|band| {
+            let top = band * TILE_SIZE;
+            let height = TILE_SIZE.min(size.y - top);
+            // Exit without any result if the job was cancelled, see `LineRender`.
+            if sender.send(SlaveMessage::LineRender).is_err() {
+                return Default::default();
+            }
+
+            let tiles: Vec<Tile<F, U>> = (0..tile_count)
+                .into_par_iter()
+                .map(|tile| {
+                    let left = tile * TILE_SIZE;
+                    let width = TILE_SIZE.min(size.x - left);
+                    let mut tile = Tile::new(
+                        point,
+                        same_color,
+                        Vec2::new(origin.x + left, origin.y + top),
+                        Vec2::new(width, height),
+                    );
+                    tile.render_rect(0, 0, width - 1, height - 1);
+                    tile
+                })
+                .collect();
+
+            // The progression is counted in lines.
+            for _ in 1..height {
+                let _ = sender.send(SlaveMessage::LineRender);
+            }
+            let rows = (0..height)
+                .map(|y| tiles.iter().flat_map(|tile| tile.row(y)).collect())
+                .collect();
+            (rows, tiles.iter().map(|tile| tile.computed).sum())
+        })
+        .collect();
+
+    let computed = bands.iter().map(|(_, computed)| computed).sum();
+    let matrix = bands.into_iter().flat_map(|(rows, _)| rows).collect();
+    (matrix, computed)
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::Float;
+
+    use super::*;
+    use crate::frac_logic::{CanvasCoords, InteriorMode, RenderSettings};
+    use crate::helpers::{coloring_modes, ColoringMode};
+
+    #[test]
+    fn test_subdivision() {
+        // A uniform matrix only needs the borders of the tiles.
+        let size = Vec2::new(70, 40);
+        let (matrix, computed) = subdivided_diverg_matrix(
+            &Vec2::new(-35, -20),
+            &size,
+            &|_, _| Diverg::new(3),
+            &Diverg::eq,
+            None,
+        );
+        assert_eq!(matrix.len(), 40);
+        assert!(matrix.iter().all(|row| row.len() == 70));
+        assert!(matrix.iter().flatten().all(|diverg| diverg.iter == 3));
+        assert!(computed < 70 * 40 / 4);
+
+        // The points are passed their canvas coordinates.
+        let (matrix, _) = subdivided_diverg_matrix(
+            &Vec2::new(-3, 5),
+            &size,
+            &|x, y| Diverg::new(x * y),
+            &Diverg::eq,
+            None,
+        );
+        assert_eq!(matrix[2][10].iter, 7 * 7);
+
+        // Almost all the points of a Mandelbrot set frame are the same as without subdivision.
+        let size = Vec2::new(128, 64);
+        let mut rs = RenderSettings {
+            canvas_size: CanvasCoords::new(size.x, size.y),
+            max_iter: 64,
+            ..Default::default()
+        };
+        rs.set_width(Float::with_val(rs.prec, 3.0));
+        let exact = rs.get_diverg_matrix(&size);
+        let (matrix, fill) = rs.get_canvas_diverg_matrix(&size, None, None);
+        assert!(fill.unwrap() > 0.2);
+        let mismatches = exact
+            .iter()
+            .flatten()
+            .zip(matrix.iter().flatten())
+            .filter(|(exact, diverg)| exact.iter != diverg.iter)
+            .count();
+        assert!(mismatches * 100 < size.x as usize * size.y as usize);
+
+        rs.subdivision.canvas = false;
+        assert_eq!(
+            rs.get_canvas_diverg_matrix(&size, None, None),
+            (exact, None)
+        );
+    }
+
+    #[test]
+    fn test_subdivision_coloring() {
+        let size = Vec2::new(128, 64);
+        let mut rs = RenderSettings {
+            canvas_size: CanvasCoords::new(size.x, size.y),
+            max_iter: 64,
+            coloring_mode_index: coloring_modes()
+                .iter()
+                .position(|cm| *cm == ColoringMode::Smooth)
+                .unwrap(),
+            ..Default::default()
+        };
+        rs.set_width(Float::with_val(rs.prec, 3.0));
+
+        // The escaping points have different smooth colors, only the void can be filled,
+        // unless it is colored too.
+        for mode in [InteriorMode::Flat, InteriorMode::Magnitude] {
+            rs.interior.mode = mode;
+            let colors = |matrix: DivergMatrix| -> Vec<_> {
+                matrix
+                    .iter()
+                    .flatten()
+                    .map(|diverg| rs.color_from_div(diverg))
+                    .collect()
+            };
+            let (matrix, fill) = rs.get_canvas_diverg_matrix(&size, None, None);
+            if mode == InteriorMode::Flat {
+                assert!(fill.unwrap() > 0.1);
+            }
+            assert_eq!(colors(matrix), colors(rs.get_diverg_matrix(&size)));
+        }
+    }
+}
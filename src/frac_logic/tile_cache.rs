@@ -0,0 +1,396 @@
+//! Contains the tile cache, which keeps the divergence of the recently rendered points,
+//! so that going back to a previous view does not compute them again.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+
+use rug::{Float, Integer};
+
+use crate::helpers::Vec2;
+
+use super::{Diverg, DivergMatrix, RenderSettings};
+
+/// The size of the square tiles stored in the cache, in points.
+const TILE_SIZE: i32 = 32;
+/// The default size of the cache, in megabytes.
+pub(crate) const DEFAULT_CACHE_SIZE: usize = 64;
+/// The number of significant bits of the cell size used to identify a zoom level,
+/// so that zooming back out finds the same level despite the rounding errors.
+const CELL_SIZE_PREC: u32 = 40;
+/// The points of two views are considered aligned if they are closer than 1/1024 cell.
+const CELL_OFFSET_STEPS: i32 = 1024;
+
+/// A divergence matrix where the points that are not known yet are `None`.
+pub(crate) type KnownMatrix = Vec<Vec<Option<Diverg>>>;
+
+/// Identifies a tile, by the hash of its view and its position in the grid of the view.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TileKey {
+    view: u64,
+    x: Integer,
+    y: Integer,
+}
+
+struct Tile {
+    points: Vec<Option<Diverg>>,
+    /// The value of the cache clock the last time the tile was used.
+    last_use: u64,
+}
+
+/// The position of a divergence matrix in the grid of points of its view.
+#[derive(Clone, Debug)]
+pub(crate) struct CacheView {
+    /// The hash of the settings which change the divergence of the points, and of the zoom level.
+    key: u64,
+    /// The index of the tile containing the first point of the matrix.
+    tile: Vec2<Integer>,
+    /// The position of the first point of the matrix in its tile.
+    offset: Vec2<i32>,
+}
+
+impl CacheView {
+    /// Returns the keys of the tiles covering a matrix of the given size, with the position
+    /// of their first point in the matrix, which is negative if it is outside of it.
+    fn tiles(
+        &self,
+        rows: usize,
+        columns: usize,
+    ) -> impl Iterator<Item = (TileKey, Vec2<i32>)> + '_ {
+        let count = |offset: i32, len: usize| (offset + len as i32 + TILE_SIZE - 1) / TILE_SIZE;
+        let (count_x, count_y) = (count(self.offset.x, columns), count(self.offset.y, rows));
+        (0..count_y).flat_map(move |y| {
+            (0..count_x).map(move |x| {
+                let key = TileKey {
+                    view: self.key,
+                    x: Integer::from(&self.tile.x + x),
+                    y: Integer::from(&self.tile.y + y),
+                };
+                let origin =
+                    Vec2::new(x * TILE_SIZE - self.offset.x, y * TILE_SIZE - self.offset.y);
+                (key, origin)
+            })
+        })
+    }
+}
+
+/// Returns the points of a tile which are in a matrix of the given size, as
+/// their coordinates in the matrix and their index in the tile.
+fn tile_points(
+    origin: &Vec2<i32>,
+    rows: usize,
+    columns: usize,
+) -> impl Iterator<Item = (usize, usize, usize)> {
+    let range = |origin: i32, len: usize| origin.max(0)..(origin + TILE_SIZE).min(len as i32);
+    let (range_x, range_y) = (range(origin.x, columns), range(origin.y, rows));
+    let origin = origin.clone();
+    range_y.flat_map(move |y| {
+        range_x.clone().map(move |x| {
+            let index = (y - origin.y) * TILE_SIZE + x - origin.x;
+            (y as usize, x as usize, index as usize)
+        })
+    })
+}
+
+/// Returns the complete divergence matrix, if all the points are known.
+pub(crate) fn complete_matrix(known: &KnownMatrix) -> Option<DivergMatrix> {
+    known
+        .iter()
+        .map(|row| row.iter().copied().collect())
+        .collect()
+}
+
+/// A size-bounded cache of the tiles of the recently rendered divergence matrices,
+/// which removes the least recently used tiles when it is full.
+pub(crate) struct TileCache {
+    tiles: HashMap<TileKey, Tile>,
+    /// The maximum size of the cache in megabytes, 0 disables it.
+    size: usize,
+    /// Incremented each time the cache is used, to find the least recently used tiles.
+    clock: u64,
+    /// The number of tiles found in the cache since it was created.
+    pub(crate) hits: usize,
+    /// The number of tiles that were missing or incomplete since the cache was created.
+    pub(crate) misses: usize,
+}
+
+impl Default for TileCache {
+    fn default() -> Self {
+        Self {
+            tiles: HashMap::new(),
+            size: DEFAULT_CACHE_SIZE,
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl TileCache {
+    /// The memory used by a tile, in bytes.
+    const TILE_BYTES: usize = (TILE_SIZE * TILE_SIZE) as usize * size_of::<Option<Diverg>>();
+
+    /// Returns the maximum size of the cache in megabytes.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the cache is enabled, that is its size is not 0.
+    pub(crate) fn enabled(&self) -> bool {
+        self.size > 0
+    }
+
+    /// Returns the number of tiles in the cache.
+    pub(crate) fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns the memory used by the tiles of the cache, in bytes.
+    pub(crate) fn used_bytes(&self) -> usize {
+        self.tiles.len() * Self::TILE_BYTES
+    }
+
+    /// Returns the maximum number of tiles in the cache.
+    fn capacity(&self) -> usize {
+        self.size * 1024 * 1024 / Self::TILE_BYTES
+    }
+
+    /// Sets the maximum size of the cache in megabytes, and removes the tiles that no longer fit.
+    pub(crate) fn resize(&mut self, size: usize) {
+        self.size = size;
+        self.evict();
+    }
+
+    /// Removes all the tiles from the cache.
+    pub(crate) fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Returns the known points of a matrix of the given size, and
+    /// the number of its tiles found in the cache and missing.
+    pub(crate) fn lookup(
+        &mut self,
+        view: &CacheView,
+        rows: usize,
+        columns: usize,
+    ) -> (KnownMatrix, usize, usize) {
+        self.clock += 1;
+        let mut known = vec![vec![None; columns]; rows];
+        let (mut hits, mut misses) = (0, 0);
+
+        for (key, origin) in view.tiles(rows, columns) {
+            let Some(tile) = self.tiles.get_mut(&key) else {
+                misses += 1;
+                continue;
+            };
+            tile.last_use = self.clock;
+            let mut complete = true;
+            for (y, x, index) in tile_points(&origin, rows, columns) {
+                known[y][x] = tile.points[index];
+                complete &= tile.points[index].is_some();
+            }
+            if complete {
+                hits += 1;
+            } else {
+                misses += 1;
+            }
+        }
+
+        self.hits += hits;
+        self.misses += misses;
+        (known, hits, misses)
+    }
+
+    /// Stores the points of a rendered divergence matrix in the cache.
+    pub(crate) fn store(&mut self, view: &CacheView, matrix: &DivergMatrix) {
+        if !self.enabled() {
+            return;
+        }
+        self.clock += 1;
+        let rows = matrix.len();
+        let columns = matrix.first().map_or(0, Vec::len);
+
+        for (key, origin) in view.tiles(rows, columns) {
+            let tile = self.tiles.entry(key).or_insert_with(|| Tile {
+                points: vec![None; (TILE_SIZE * TILE_SIZE) as usize],
+                last_use: 0,
+            });
+            tile.last_use = self.clock;
+            for (y, x, index) in tile_points(&origin, rows, columns) {
+                if let Some(diverg) = matrix[y].get(x) {
+                    tile.points[index] = Some(*diverg);
+                }
+            }
+        }
+        self.evict();
+    }
+
+    /// Removes the least recently used tiles until the cache fits in its size.
+    fn evict(&mut self) {
+        let excess = self.tiles.len().saturating_sub(self.capacity());
+        if excess == 0 {
+            return;
+        }
+        let mut tiles: Vec<(u64, TileKey)> = self
+            .tiles
+            .iter()
+            .map(|(key, tile)| (tile.last_use, key.clone()))
+            .collect();
+        tiles.sort_unstable_by_key(|(last_use, _)| *last_use);
+        for (_, key) in tiles.into_iter().take(excess) {
+            self.tiles.remove(&key);
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Returns the position of a divergence matrix of the given size in the grid of points
+    /// of its view, which is shared by all the matrices rendered with the same settings
+    /// and zoom level, and whose points are aligned.
+    pub(crate) fn cache_view(&self, size: &Vec2<i32>) -> CacheView {
+        let cell_size = self.cell_size_from_height(size.y);
+        let mut hasher = DefaultHasher::new();
+        // Everything which changes the divergence of the points, except the position.
+        format!(
+            "{} {} {} {} {} {:?} {:?} {:?} {:?} {:?}",
+            self.frac_index,
+            self.max_iter,
+            self.prec,
+            self.exponent,
+            self.bailout,
+            self.escape_norm,
+            self.julia_constant,
+            self.mandel_constant,
+            self.newton_poly,
+            self.formula,
+        )
+        .hash(&mut hasher);
+        // The points filled by the subdivision also depend on the coloring mode, see
+        // `same_coloring`, which otherwise only reads the settings hashed above.
+        format!(
+            "{} {:?} {:?} {} {:?}",
+            self.use_distance_estimation(),
+            self.trap_tracker().map(|_| &self.orbit_trap),
+            self.interior_tracker().map(|_| self.interior.mode),
+            self.perturbation,
+            self.subdivision.canvas.then(|| self.coloring_mode()),
+        )
+        .hash(&mut hasher);
+        Float::with_val(CELL_SIZE_PREC, &cell_size)
+            .to_string_radix(16, None)
+            .hash(&mut hasher);
+
+        // The index of the middle point in the grid, and its offset from the grid point.
+        let index = |coord: &Float| {
+            let ratio = Float::with_val(self.prec + 32, coord / &cell_size);
+            let index = ratio.clone().round();
+            let offset = Float::with_val(32, (ratio - &index) * CELL_OFFSET_STEPS);
+            (
+                index.to_integer().unwrap_or_default(),
+                offset.to_f64().round() as i32,
+            )
+        };
+        let (middle_x, offset_x) = index(self.pos.real());
+        let (middle_y, offset_y) = index(self.pos.imag());
+        (offset_x, offset_y).hash(&mut hasher);
+
+        let tile = |middle: Integer, half: i32| {
+            let (tile, offset) = (middle - half).div_rem_floor(TILE_SIZE.into());
+            (tile, offset.to_i32().unwrap_or_default())
+        };
+        let (tile_x, offset_x) = tile(middle_x, size.x / 2);
+        let (tile_y, offset_y) = tile(middle_y, size.y / 2);
+        CacheView {
+            key: hasher.finish(),
+            tile: Vec2::new(tile_x, tile_y),
+            offset: Vec2::new(offset_x, offset_y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::Complex;
+
+    use super::*;
+    use crate::frac_logic::CanvasCoords;
+    use crate::helpers::ZoomDirection;
+    use crate::AppState;
+
+    #[test]
+    fn test_tile_cache() {
+        let size = Vec2::new(70, 40);
+        let mut state = AppState::default();
+        state.render_settings.canvas_size = CanvasCoords::new(size.x, size.y);
+        state.render_settings.reset_cell_size();
+        let rs = &state.render_settings;
+        let matrix = rs.get_diverg_matrix(&size);
+
+        let mut cache = TileCache::default();
+        let view = rs.cache_view(&size);
+        let (known, hits, tiles) = cache.lookup(&view, 40, 71);
+        assert_eq!(hits, 0);
+        assert!(tiles >= 3 * 2);
+        assert!(known.iter().flatten().all(Option::is_none));
+        cache.store(&view, &matrix);
+
+        // Zooming in and back out finds the same view.
+        state.zoom(ZoomDirection::In);
+        assert_ne!(state.render_settings.cache_view(&size).key, view.key);
+        state.zoom(ZoomDirection::Out);
+        let view = state.render_settings.cache_view(&size);
+        let (known, hits, misses) = cache.lookup(&view, 40, 71);
+        assert_eq!((hits, misses), (tiles, 0));
+        assert_eq!(complete_matrix(&known), Some(matrix.clone()));
+
+        // After a move by whole cells, the points that remain visible are found.
+        state.pan(10, -5);
+        let view = state.render_settings.cache_view(&size);
+        let (known, _, misses) = cache.lookup(&view, 40, 71);
+        assert!(misses > 0);
+        assert_eq!(known[5][0], Some(matrix[0][10]));
+        assert_eq!(known[39][60], Some(matrix[34][70]));
+        assert_eq!(known[4][0], None);
+        assert_eq!(known[5][61], None);
+
+        // Other settings or misaligned points are different views.
+        let rs = &mut state.render_settings;
+        rs.max_iter += 1;
+        assert_ne!(rs.cache_view(&size).key, view.key);
+        rs.max_iter -= 1;
+        // The subdivided points depend on the coloring mode.
+        rs.coloring_mode_index = 1;
+        assert_ne!(rs.cache_view(&size).key, view.key);
+        rs.subdivision.canvas = false;
+        let unsubdivided = rs.cache_view(&size).key;
+        rs.coloring_mode_index = 0;
+        assert_eq!(rs.cache_view(&size).key, unsubdivided);
+        rs.subdivision.canvas = true;
+        rs.pos += Complex::with_val(rs.prec, (&rs.cell_size / 2u32, 0));
+        assert_ne!(rs.cache_view(&size).key, view.key);
+
+        // The least recently used tiles are removed when the cache is full.
+        let mut cache = TileCache {
+            size: 1,
+            ..Default::default()
+        };
+        let views: Vec<CacheView> = (0..8)
+            .map(|i| {
+                rs.max_iter = 100 + i;
+                rs.cache_view(&size)
+            })
+            .collect();
+        for view in &views {
+            cache.store(view, &matrix);
+        }
+        assert_eq!(cache.len(), cache.capacity());
+        assert_eq!(cache.lookup(&views[7], 40, 71).2, 0);
+        assert!(cache.lookup(&views[0], 40, 71).2 > 0);
+
+        cache.resize(0);
+        assert_eq!(cache.len(), 0);
+        cache.store(&view, &matrix);
+        assert_eq!(cache.len(), 0);
+    }
+}
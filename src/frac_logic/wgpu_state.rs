@@ -1,6 +1,9 @@
-use std::{fmt::Debug, sync::mpsc::Sender};
+use std::{borrow::Cow, fmt::Debug, sync::{mpsc::Sender, Arc}};
 
-use wgpu::{Adapter, Backends, Device, ShaderModuleDescriptor};
+use wgpu::{
+    Adapter, Backends, ComputePipeline, Device, Queue, ShaderModule, ShaderModuleDescriptor,
+    ShaderSource,
+};
 
 use crate::{app::SlaveMessage, helpers::markup::esc};
 
@@ -12,18 +15,24 @@ pub(crate) struct WgpuState {
     pub(crate) use_gpu: bool,
     /// Instance of WGPU, used for all other contexts.
     pub(crate) instance: wgpu::Instance,
-    /// Represents an open connection to a graphics device
-    pub(crate) device: Option<wgpu::Device>,
-    pub(crate) queue: Option<wgpu::Queue>,
-    pub(crate) cs_module: Option<wgpu::ShaderModule>,
-    pub(crate) compute_pipeline: Option<wgpu::ComputePipeline>,
+    /// Represents an open connection to a graphics device.
+    /// The GPU objects are shared by the copies made with `share`.
+    pub(crate) device: Option<Arc<Device>>,
+    pub(crate) queue: Option<Arc<Queue>>,
+    pub(crate) cs_module: Option<Arc<ShaderModule>>,
+    pub(crate) compute_pipeline: Option<Arc<ComputePipeline>>,
     /// A list of detected adapters, which are handles to physical devices.
     /// The list is created when the WgpuState is initialized, and aren't updated thereafter.
-    detected_adapters: Vec<Adapter>,
+    detected_adapters: Arc<Vec<Adapter>>,
     /// Index in detected_adapters of the preferred adapter. Guaranteed to be always valid.
     preferred_adapter: usize,
     /// The shader name of the desired fractal.
     frac_name: String,
+    /// Whether or not to use the double-single variant of the shaders, for deeper zooms.
+    double_single: bool,
+    /// The generated shader source of the Formula pseudo-fractal,
+    /// updated by `RenderSettings` before the shader is loaded.
+    pub(crate) formula_shader: String,
     /// Whether or not to use timeout detection
     pub(crate) disable_timeout_detection: bool,
 }
@@ -62,7 +71,7 @@ impl WgpuState {
     /// List available WGPU adapters and put them in .detected_adapters.
     /// Is ran once on initialization. Returns a fixed error message if no adapter is detected
     fn detect_adapters(&mut self) -> Result<(), String> {
-        self.detected_adapters = self.instance.enumerate_adapters(Backends::all());
+        self.detected_adapters = Arc::new(self.instance.enumerate_adapters(Backends::all()));
         if self.detected_adapters.is_empty() {
             return Err("No GPU adapter has been detected on your system.".to_string());
         }
@@ -91,11 +100,27 @@ impl WgpuState {
     fn get_cs_descriptor(&self) -> Result<ShaderModuleDescriptor<'static>, String> {
         // Loads the shader from WGSL
 
+        if self.double_single {
+            if let Some(descriptor) = self.get_double_single_cs_descriptor() {
+                return Ok(descriptor);
+            }
+        }
+
         Ok(match self.frac_name.to_lowercase().as_ref() {
             // TODO: implement other fractal shaders
             "mandelbrot" => wgpu::include_wgsl!("../fractals/shaders/mandelbrot.wgsl"),
             "burningship" => wgpu::include_wgsl!("../fractals/shaders/burning_ship.wgsl"),
             "julia" => wgpu::include_wgsl!("../fractals/shaders/julia.wgsl"),
+            "tricorn" => wgpu::include_wgsl!("../fractals/shaders/tricorn.wgsl"),
+            "celtic" => wgpu::include_wgsl!("../fractals/shaders/celtic.wgsl"),
+            "buffalo" => wgpu::include_wgsl!("../fractals/shaders/buffalo.wgsl"),
+            "perpendicular" => wgpu::include_wgsl!("../fractals/shaders/perpendicular.wgsl"),
+            "heart" => wgpu::include_wgsl!("../fractals/shaders/heart.wgsl"),
+            "newton" => wgpu::include_wgsl!("../fractals/shaders/newton.wgsl"),
+            "formula" => ShaderModuleDescriptor {
+                label: Some("formula"),
+                source: ShaderSource::Wgsl(Cow::Owned(self.formula_shader.clone())),
+            },
             _ => {
                 return Err(format!(
                     "Fractal shader not yet implemented for: {}",
@@ -105,6 +130,37 @@ impl WgpuState {
         })
     }
 
+    /// Return the double-single CS descriptor associated with the current fractal name,
+    /// or `None` if the fractal only has a single precision shader.
+    fn get_double_single_cs_descriptor(&self) -> Option<ShaderModuleDescriptor<'static>> {
+        Some(match self.frac_name.to_lowercase().as_ref() {
+            "mandelbrot" => wgpu::include_wgsl!("../fractals/shaders/double_single/mandelbrot.wgsl"),
+            "burningship" => {
+                wgpu::include_wgsl!("../fractals/shaders/double_single/burning_ship.wgsl")
+            }
+            "julia" => wgpu::include_wgsl!("../fractals/shaders/double_single/julia.wgsl"),
+            "tricorn" => wgpu::include_wgsl!("../fractals/shaders/double_single/tricorn.wgsl"),
+            "celtic" => wgpu::include_wgsl!("../fractals/shaders/double_single/celtic.wgsl"),
+            "buffalo" => wgpu::include_wgsl!("../fractals/shaders/double_single/buffalo.wgsl"),
+            "perpendicular" => {
+                wgpu::include_wgsl!("../fractals/shaders/double_single/perpendicular.wgsl")
+            }
+            "heart" => wgpu::include_wgsl!("../fractals/shaders/double_single/heart.wgsl"),
+            "newton" => wgpu::include_wgsl!("../fractals/shaders/double_single/newton.wgsl"),
+            _ => return None,
+        })
+    }
+
+    /// Switch between the single precision and the double-single shaders,
+    /// and reload the shader module if needed.
+    pub(crate) fn set_double_single(&mut self, double_single: bool) -> Result<(), String> {
+        if self.double_single == double_single {
+            return Ok(());
+        }
+        self.double_single = double_single;
+        self.update_cs_module(None)
+    }
+
     /// Set the wanted shader module descriptor.
     pub(crate) fn set_cs(&mut self, frac_name: impl Into<String>) -> Result<(), String> {
         self.frac_name = frac_name.into();
@@ -133,7 +189,7 @@ impl WgpuState {
             .await
             .map_err(|err| format!("Could not request device: {}", esc(err)))?;
 
-        (self.device, self.queue) = (Some(device), Some(queue));
+        (self.device, self.queue) = (Some(Arc::new(device)), Some(Arc::new(queue)));
 
         self.update_cs_module(sender)
     }
@@ -141,7 +197,7 @@ impl WgpuState {
     /// After selecting an adapter and updating the device and queue, update the cs module
     fn update_cs_module(&mut self, sender: Option<&Sender<SlaveMessage>>) -> Result<(), String> {
         msg_send(sender, "Updating CS module...")?;
-        self.cs_module = Some(
+        self.cs_module = Some(Arc::new(
             self
             .device
             .as_ref()
@@ -149,7 +205,7 @@ impl WgpuState {
             .create_shader_module(
                 self.get_cs_descriptor()?
             ),
-        );
+        ));
         self.update_pipeline(sender)?;
         Ok(())
     }
@@ -157,20 +213,20 @@ impl WgpuState {
     /// The latest step of GPU initialization is the pipeline.
     fn update_pipeline(&mut self, sender: Option<&Sender<SlaveMessage>>) -> Result<(), String> {
         msg_send(sender, "Updating the GPU pipeline...")?;
-        self.compute_pipeline = Some(
+        self.compute_pipeline = Some(Arc::new(
             self.get_device()?
                 .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
                     label: None,
                     layout: None,
                     module: self
                         .cs_module
-                        .as_ref()
+                        .as_deref()
                         .ok_or("Cannot update the pipeline if no CS module is defined.")?,
                     entry_point: Some("main"),
                     compilation_options: Default::default(),
                     cache: None,
                 }),
-        );
+        ));
         msg_send(sender, "GPU pipeline update finished.")?;
         Ok(())
     }
@@ -186,10 +242,24 @@ impl WgpuState {
     /// Returns a reference to the currently initialized GPU device. Returns a fixed error message
     /// when the GPU device has not been initialized yet.
     pub(crate) fn get_device(&self) -> Result<&Device, String> {
-        self.device.as_ref().ok_or(
+        self.device.as_deref().ok_or(
             "ERROR: Tried to access the GPU device while it was not initialized.".to_string(),
         )
     }
+
+    /// Returns a copy of the state sharing the same GPU objects, so that
+    /// a render can run on another thread without initializing the GPU again.
+    pub(crate) fn share(&self) -> Self {
+        Self {
+            device: self.device.clone(),
+            queue: self.queue.clone(),
+            cs_module: self.cs_module.clone(),
+            compute_pipeline: self.compute_pipeline.clone(),
+            detected_adapters: self.detected_adapters.clone(),
+            frac_name: self.frac_name.clone(),
+            ..self.clone()
+        }
+    }
 }
 
 /// Do no keep WGPU data when copying the AppState.
@@ -199,6 +269,8 @@ impl Clone for WgpuState {
             use_gpu: self.use_gpu,
             preferred_adapter: self.preferred_adapter,
             disable_timeout_detection: self.disable_timeout_detection,
+            formula_shader: self.formula_shader.clone(),
+            double_single: self.double_single,
             ..Default::default()
         }
     }
@@ -213,6 +285,8 @@ impl Debug for WgpuState {
 
 #[cfg(test)]
 mod tests {
+    use crate::fractals::{Formula, FRACTALS};
+
     use super::*;
 
     #[test]
@@ -224,4 +298,42 @@ mod tests {
         // Cloning the WgpuState should keep the use_gpu property.
         assert!(st.clone().use_gpu);
     }
+
+    /// Parse and validate a WGSL shader with naga, as wgpu would.
+    fn validate_shader(descriptor: ShaderModuleDescriptor) -> Result<(), String> {
+        let ShaderSource::Wgsl(source) = descriptor.source else {
+            return Err("Not a WGSL shader".to_string());
+        };
+        let module =
+            naga::front::wgsl::parse_str(&source).map_err(|err| err.emit_to_string(&source))?;
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .map_err(|err| err.emit_to_string(&source))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_shaders_are_valid() {
+        let mut st = WgpuState {
+            formula_shader: "sin(z)^2.5 / conj(c) + exp(im(z)) - log(3i)"
+                .parse::<Formula>()
+                .unwrap()
+                .shader_source(),
+            ..Default::default()
+        };
+
+        for frac in FRACTALS {
+            for double_single in [false, true] {
+                st.frac_name = frac.name.to_string();
+                st.double_single = double_single;
+                let descriptor = st.get_cs_descriptor().unwrap();
+                if let Err(err) = validate_shader(descriptor) {
+                    panic!("Invalid shader for {} ({double_single}):\n{err}", frac.name);
+                }
+            }
+        }
+    }
 }
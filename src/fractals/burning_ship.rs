@@ -2,28 +2,44 @@
 
 use std::ops::{AddAssign, MulAssign};
 
-use rug::ops::{CompleteRound, PowAssign};
+use rug::ops::{CompleteRound, NegAssign};
 use rug::Complex;
 
-use crate::frac_logic::RenderSettings;
-use crate::fractals::Fractal;
+use crate::frac_logic::{void_diverg, Diverg, NativeSettings, PeriodicityCheck, RenderSettings};
+use crate::fractals::{
+    derive_exponent, distance_estimate, final_mag, native_distance_estimate, pow_exponent, Fractal,
+    NativeFractal,
+};
+use crate::helpers::{DoubleDouble, NativeComplex, Real};
 
 /// Implement the formula for the julia set,
-fn get_burning_ship(mut p: Complex, render_settings: &RenderSettings) -> i32 {
+fn get_burning_ship(mut p: Complex, render_settings: &RenderSettings) -> Diverg {
     // invert the x and y axis
     p.mul_assign(-1);
 
     // iteration counter
     let mut n: i32 = 0;
+    let mut trap = render_settings.trap_tracker();
+    let mut interior = render_settings.interior_tracker();
+    let mut cycle = render_settings
+        .use_interior_checks()
+        .then(PeriodicityCheck::new);
 
     let mut z = Complex::new(render_settings.prec);
-    while *z
-        .abs_ref()
-        .complete((render_settings.prec, render_settings.prec))
-        .real()
-        < 4
-        && n < render_settings.max_iter
-    {
+    // The derivative of the current term with respect to `p`, for distance estimation.
+    let mut dz = render_settings
+        .use_distance_estimation()
+        .then(|| Complex::new(render_settings.prec));
+    while !render_settings.escaped(&z) && n < render_settings.max_iter {
+        if let Some(dz) = &mut dz {
+            // Taking the absolute value of a part flips the sign of its derivative.
+            if z.real().is_sign_negative() {
+                dz.mut_real().neg_assign();
+            }
+            if z.imag().is_sign_negative() {
+                dz.mut_imag().neg_assign();
+            }
+        }
         z = Complex::with_val(
             render_settings.prec,
             (
@@ -31,28 +47,107 @@ fn get_burning_ship(mut p: Complex, render_settings: &RenderSettings) -> i32 {
                 &z.imag().abs_ref().complete(render_settings.prec),
             ),
         );
-        z.pow_assign(2);
+        if let Some(dz) = &mut dz {
+            derive_exponent(dz, &z, render_settings);
+            *dz += 1;
+        }
+        pow_exponent(&mut z, render_settings);
         z.add_assign(&p);
 
+        if let Some(trap) = &mut trap {
+            trap.track_rug(&z);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track_rug(&z);
+        }
         n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&z, n)) {
+            return Diverg::new(-1);
+        }
     }
 
     if n == render_settings.max_iter {
-        return -1;
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, final_mag(&z));
+    if let Some(dz) = &dz {
+        diverg.dist = distance_estimate(&z, dz);
+    }
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
+}
+
+/// Same as `get_burning_ship`, with a native floating point type.
+fn get_burning_ship_native<T: Real>(p: NativeComplex<T>, settings: &NativeSettings<T>) -> Diverg {
+    // invert the x and y axis
+    let p = NativeComplex::new(-p.re, -p.im);
+
+    let mut n: i32 = 0;
+    let mut trap = settings.trap_tracker;
+    let mut interior = settings.interior_tracker;
+    let mut cycle = settings.cycle_detection.then(PeriodicityCheck::new);
+    let mut z = NativeComplex::<T>::default();
+    let mut dz = settings
+        .distance_estimation
+        .then(NativeComplex::<T>::default);
+
+    while !settings.escaped(z) && n < settings.max_iter {
+        let folded = NativeComplex::new(z.re.abs(), z.im.abs());
+        if let Some(dz) = &mut dz {
+            // Taking the absolute value of a part flips the sign of its derivative.
+            let zero = T::default();
+            let dz_folded = NativeComplex::new(
+                if z.re < zero { -dz.re } else { dz.re },
+                if z.im < zero { -dz.im } else { dz.im },
+            );
+            *dz = settings.derive_exponent(dz_folded, folded) + NativeComplex::one();
+        }
+        z = settings.pow_exponent(folded) + p;
+        if let Some(trap) = &mut trap {
+            trap.track(z);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track(z);
+        }
+        n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&z, n)) {
+            return Diverg::new(-1);
+        }
     }
 
-    n
+    if n == settings.max_iter {
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, z.mag());
+    if let Some(dz) = dz {
+        diverg.dist = native_distance_estimate(z, dz);
+    }
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
 }
 
 pub(crate) const BURNING_SHIP: Fractal = Fractal {
     default_pos: (0.5, 0.5),
+    native: Some(NativeFractal {
+        f64: &get_burning_ship_native::<f64>,
+        double_double: &get_burning_ship_native::<DoubleDouble>,
+    }),
+    distance_estimation: true,
+    interior_coloring: true,
     get: &get_burning_ship,
     name: "BurningShip",
     details: concat!(
-        // U_{n+1} = (|\Re(U_n)| + i |\Im(U_n)|)^2 + P
-        "Formula: \n<acc Uₙ₊₁ = (|ℜ(Uₙ)| + i |ℑ(Uₙ)|)² + P>\n",
+        // U_{n+1} = (|\Re(U_n)| + i |\Im(U_n)|)^d + P
+        "Formula: \n<acc Uₙ₊₁ = (|ℜ(Uₙ)| + i |ℑ(Uₙ)|)ᵈ + P>\n",
         "Where <acc U₀=0>, ",
-        "and <acc P> is the complex number at the position of the pixel. ",
+        "<acc P> is the complex number at the position of the pixel, ",
+        "and <acc d> is the exponent, set to <acc 2> by default (see the <command exponent> command). ",
         "The vertical and horizontal axis are inverted (negatives on the top/right) in order for the render to look like a ship.",
     ),
 };
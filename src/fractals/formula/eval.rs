@@ -0,0 +1,60 @@
+//! Contains the evaluation of formulas with arbitrary precision, for the CPU mode.
+
+use rug::ops::PowAssign;
+use rug::{Assign, Complex};
+
+use super::parser::{Expr, Func};
+
+impl Expr {
+    /// Evaluates the expression with the given values of `z` and `c`.
+    pub(crate) fn eval(&self, z: &Complex, c: &Complex, prec: u32) -> Complex {
+        match self {
+            Expr::Num(n) => Complex::with_val(prec, *n),
+            Expr::I => Complex::with_val(prec, (0, 1)),
+            Expr::Z => z.clone(),
+            Expr::C => c.clone(),
+            Expr::Neg(a) => -a.eval(z, c, prec),
+            Expr::Add(a, b) => a.eval(z, c, prec) + b.eval(z, c, prec),
+            Expr::Sub(a, b) => a.eval(z, c, prec) - b.eval(z, c, prec),
+            Expr::Mul(a, b) => a.eval(z, c, prec) * b.eval(z, c, prec),
+            Expr::Div(a, b) => a.eval(z, c, prec) / b.eval(z, c, prec),
+            Expr::Pow(base, exp) => {
+                let mut base = base.eval(z, c, prec);
+                // Zero is left unchanged whatever the exponent, like in `pow_exponent`.
+                if base.is_zero() {
+                    return base;
+                }
+                match **exp {
+                    // Integer exponents are computed exactly (and much faster) by MPC.
+                    Expr::Num(n) if n.fract() == 0.0 && n.abs() <= i32::MAX as f64 => {
+                        base.pow_assign(n as i32)
+                    }
+                    _ => base.pow_assign(&exp.eval(z, c, prec)),
+                }
+                base
+            }
+            Expr::Call(func, arg) => {
+                let mut x = arg.eval(z, c, prec);
+                match func {
+                    Func::Sin => x.sin_mut(),
+                    Func::Cos => x.cos_mut(),
+                    Func::Tan => x.tan_mut(),
+                    Func::Sinh => x.sinh_mut(),
+                    Func::Cosh => x.cosh_mut(),
+                    Func::Tanh => x.tanh_mut(),
+                    Func::Exp => x.exp_mut(),
+                    Func::Log => x.ln_mut(),
+                    Func::Sqrt => x.sqrt_mut(),
+                    Func::Conj => x.conj_mut(),
+                    Func::Abs => x.abs_mut(),
+                    Func::Re => x.mut_imag().assign(0),
+                    Func::Im => {
+                        let imag = x.imag().clone();
+                        x.assign((imag, 0));
+                    }
+                }
+                x
+            }
+        }
+    }
+}
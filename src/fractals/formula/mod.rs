@@ -0,0 +1,144 @@
+//! Contains the user-defined formula pseudo-fractal.
+
+mod eval;
+mod parser;
+mod wgsl;
+
+use std::str::FromStr;
+
+use rug::Complex;
+
+use crate::frac_logic::{void_diverg, Diverg, PeriodicityCheck, RenderSettings};
+use crate::fractals::{final_mag, Fractal};
+
+pub(crate) use parser::FUNCTIONS;
+use parser::{parse, Expr};
+
+const DEFAULT_FORMULA: &str = "z^2 + c";
+
+/// Represents a user-defined formula, used to compute the next term of the sequence.
+#[derive(Clone, Debug)]
+pub(crate) struct Formula {
+    /// The formula, as written by the user.
+    pub(crate) source: String,
+    expr: Expr,
+}
+
+impl FromStr for Formula {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            source: s.trim().to_string(),
+            expr: parse(s)?,
+        })
+    }
+}
+
+impl Default for Formula {
+    fn default() -> Self {
+        DEFAULT_FORMULA.parse().unwrap()
+    }
+}
+
+impl Formula {
+    /// Returns the source of the GPU shader for this formula.
+    pub(crate) fn shader_source(&self) -> String {
+        wgsl::shader_source(&self.expr)
+    }
+}
+
+/// Iterate the user-defined formula until the point escapes.
+fn get_formula(p: Complex, render_settings: &RenderSettings) -> Diverg {
+    let expr = &render_settings.formula.expr;
+
+    // iteration counter
+    let mut n: i32 = 0;
+    let mut trap = render_settings.trap_tracker();
+    let mut interior = render_settings.interior_tracker();
+    let mut cycle = render_settings
+        .use_interior_checks()
+        .then(PeriodicityCheck::new);
+
+    let mut z = Complex::with_val(render_settings.prec, &render_settings.mandel_constant);
+    while !render_settings.escaped(&z) && n < render_settings.max_iter {
+        z = expr.eval(&z, &p, render_settings.prec);
+        if let Some(trap) = &mut trap {
+            trap.track_rug(&z);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track_rug(&z);
+        }
+        n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&z, n)) {
+            return Diverg::new(-1);
+        }
+    }
+
+    if n == render_settings.max_iter {
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, final_mag(&z));
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
+}
+
+pub(crate) const FORMULA: Fractal = Fractal {
+    default_pos: (0.0, 0.0),
+    native: None,
+    distance_estimation: false,
+    interior_coloring: true,
+    get: &get_formula,
+    name: "Formula",
+    details: concat!(
+        "Formula: \n<acc Uₙ₊₁ = f(Uₙ, P)>\n",
+        "Where <acc U₀> is the mandelbrot constant (<acc 0> by default), ",
+        "<acc P> is the complex number at the position of the pixel, ",
+        "and <acc f> is a user-defined formula, set to <acc z^2 + c> by default ",
+        "(see the <command formula> command).",
+    ),
+};
+
+#[cfg(test)]
+mod tests {
+    use rug::Complex;
+
+    use crate::frac_logic::RenderSettings;
+    use crate::fractals::MANDELBROT;
+
+    use super::*;
+
+    #[test]
+    fn test_formula_matches_mandelbrot() {
+        let rs = RenderSettings::default();
+        for point in [(0.0, 0.0), (0.3, 0.5), (-0.75, 0.1), (1.0, 1.0), (-1.8, 0.0)] {
+            let p = Complex::with_val(rs.prec, point);
+            assert_eq!(
+                get_formula(p.clone(), &rs),
+                (MANDELBROT.get)(p, &rs),
+                "at {point:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_formula_eval() {
+        let z = Complex::with_val(64, (1.2, -0.3));
+        let c = Complex::with_val(64, (2, 1));
+        let eval = |source: &str| {
+            let formula: Formula = source.parse().unwrap();
+            formula.expr.eval(&z, &c, 64)
+        };
+
+        // sin² + cos² = 1
+        let one = eval("sin(z)^2 + cos(z)^2");
+        assert!((one - Complex::with_val(64, 1)).abs().real().to_f64() < 1e-15);
+        // (z^3)^(1/3) = z for this value of z, and exp(log(c)) = c
+        let zero = eval("(z^3)^(1/3) - z + exp(log(c)) - c");
+        assert!(zero.abs().real().to_f64() < 1e-15);
+        assert_eq!(eval("re(c) + im(c)i"), c);
+        assert_eq!(eval("abs(3 + 4i) + conj(i)"), Complex::with_val(64, (5, -1)));
+    }
+}
@@ -0,0 +1,336 @@
+//! Contains the tokenizer and the recursive descent parser for user-defined formulas.
+
+use std::fmt::Display;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::helpers::markup::esc;
+
+/// The functions that can be used in a formula.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Log,
+    Sqrt,
+    Conj,
+    Abs,
+    Re,
+    Im,
+}
+
+/// The name of each function, as written in formulas.
+pub(crate) const FUNCTIONS: &[(&str, Func)] = &[
+    ("sin", Func::Sin),
+    ("cos", Func::Cos),
+    ("tan", Func::Tan),
+    ("sinh", Func::Sinh),
+    ("cosh", Func::Cosh),
+    ("tanh", Func::Tanh),
+    ("exp", Func::Exp),
+    ("log", Func::Log),
+    ("sqrt", Func::Sqrt),
+    ("conj", Func::Conj),
+    ("abs", Func::Abs),
+    ("re", Func::Re),
+    ("im", Func::Im),
+];
+
+/// The abstract syntax tree of a formula.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expr {
+    /// A real number literal.
+    Num(f64),
+    /// The imaginary unit.
+    I,
+    /// The current term of the sequence.
+    Z,
+    /// The complex number at the position of the pixel.
+    C,
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(Func, Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "{n}"),
+            Token::Ident(ident) => write!(f, "{ident}"),
+            Token::Op(op) => write!(f, "{op}"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => tokens.push(Token::Num(read_number(&mut chars)?)),
+            'a'..='z' | 'A'..='Z' => {
+                let mut ident = String::new();
+                while let Some(ch) = chars.next_if(|ch| ch.is_ascii_alphabetic()) {
+                    ident.push(ch.to_ascii_lowercase());
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                chars.next();
+                tokens.push(Token::Op(ch));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            _ => return Err(format!("Unexpected character: <acc {}>", esc(ch))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Reads a number like `2`, `0.5` or `1e-3`.
+fn read_number(chars: &mut Peekable<Chars>) -> Result<f64, String> {
+    let mut number = String::new();
+    while let Some(ch) = chars.next_if(|ch| ch.is_ascii_digit() || *ch == '.') {
+        number.push(ch);
+    }
+
+    // The exponent is only read if it is followed by digits.
+    let mut lookahead = chars.clone();
+    if lookahead.next_if(|ch| *ch == 'e' || *ch == 'E').is_some() {
+        let sign = lookahead.next_if(|ch| *ch == '+' || *ch == '-');
+        if lookahead.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            number.push('e');
+            number.extend(sign);
+            *chars = lookahead;
+            while let Some(ch) = chars.next_if(|ch| ch.is_ascii_digit()) {
+                number.push(ch);
+            }
+        }
+    }
+
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or(format!("Invalid number: <acc {}>", esc(number)))
+}
+
+/// Parses a formula, using the following grammar:
+/// ```text
+/// expr    := term (('+' | '-') term)*
+/// term    := unary (('*' | '/')? unary)*
+/// unary   := ('-' | '+') unary | power
+/// power   := primary ('^' unary)?
+/// primary := number | 'i' | 'z' | 'c' | func '(' expr ')' | '(' expr ')'
+/// ```
+/// A missing operator between two factors, like in `2z` or `3i`, means a multiplication.
+pub(crate) fn parse(input: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    let expr = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected token: <acc {}>", esc(token)));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the given operator.
+    fn eat_op(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            if self.eat_op('+') {
+                expr = Expr::Add(Box::new(expr), Box::new(self.term()?));
+            } else if self.eat_op('-') {
+                expr = Expr::Sub(Box::new(expr), Box::new(self.term()?));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            if self.eat_op('*') {
+                expr = Expr::Mul(Box::new(expr), Box::new(self.unary()?));
+            } else if self.eat_op('/') {
+                expr = Expr::Div(Box::new(expr), Box::new(self.unary()?));
+            } else if matches!(
+                self.peek(),
+                Some(Token::Num(_) | Token::Ident(_) | Token::LParen)
+            ) {
+                // Implicit multiplication
+                expr = Expr::Mul(Box::new(expr), Box::new(self.power()?));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat_op('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.primary()?;
+        if self.eat_op('^') {
+            // The exponent is parsed with `unary` so that `z^-2` and `z^2^3` work as expected.
+            return Ok(Expr::Pow(Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "i" => Ok(Expr::I),
+                "z" => Ok(Expr::Z),
+                "c" => Ok(Expr::C),
+                name => {
+                    let func = FUNCTIONS
+                        .iter()
+                        .find(|(func_name, _)| *func_name == name)
+                        .map(|(_, func)| *func)
+                        .ok_or(format!("Unknown variable or function: <acc {}>", esc(name)))?;
+                    if self.next() != Some(Token::LParen) {
+                        return Err(format!(
+                            "Expected an opening parenthesis after <acc {}>",
+                            esc(name)
+                        ));
+                    }
+                    let arg = self.expr()?;
+                    self.closing_paren()?;
+                    Ok(Expr::Call(func, Box::new(arg)))
+                }
+            },
+            Some(Token::LParen) => {
+                let expr = self.expr()?;
+                self.closing_paren()?;
+                Ok(expr)
+            }
+            Some(token) => Err(format!("Unexpected token: <acc {}>", esc(token))),
+            None => Err("Unexpected end of formula.".to_string()),
+        }
+    }
+
+    fn closing_paren(&mut self) -> Result<(), String> {
+        if self.next() != Some(Token::RParen) {
+            return Err("Missing closing parenthesis.".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn b(expr: Expr) -> Box<Expr> {
+        Box::new(expr)
+    }
+
+    #[test]
+    fn test_parse_formula() {
+        // Operator precedence: z^2 + 2c
+        assert_eq!(
+            parse("z^2 + 2c").unwrap(),
+            Expr::Add(
+                b(Expr::Pow(b(Expr::Z), b(Expr::Num(2.0)))),
+                b(Expr::Mul(b(Expr::Num(2.0)), b(Expr::C)))
+            )
+        );
+        // Unary minus applies after the power, and the exponent can be negative.
+        assert_eq!(
+            parse("-z^-1e-1").unwrap(),
+            Expr::Neg(b(Expr::Pow(
+                b(Expr::Z),
+                b(Expr::Neg(b(Expr::Num(0.1))))
+            )))
+        );
+        // Subtraction is left associative.
+        assert_eq!(
+            parse("z - c - 1").unwrap(),
+            Expr::Sub(b(Expr::Sub(b(Expr::Z), b(Expr::C))), b(Expr::Num(1.0)))
+        );
+        assert_eq!(
+            parse("SIN(z)/3i").unwrap(),
+            Expr::Mul(
+                b(Expr::Div(b(Expr::Call(Func::Sin, b(Expr::Z))), b(Expr::Num(3.0)))),
+                b(Expr::I)
+            )
+        );
+
+        assert!(parse("").is_err());
+        assert!(parse("z^").is_err());
+        assert!(parse("(z + c").is_err());
+        assert!(parse("z + c)").is_err());
+        assert!(parse("foo(z)").is_err());
+        assert!(parse("sin z").is_err());
+        assert!(parse("z % 2").is_err());
+        assert!(parse("1..2").is_err());
+    }
+}
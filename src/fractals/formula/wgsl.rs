@@ -0,0 +1,153 @@
+//! Contains the translation of formulas to WGSL, for the GPU mode.
+
+use super::parser::{Expr, FUNCTIONS};
+
+/// The line that ends the code shared by all fractal shaders.
+const HEADER_END: &str =
+    "// ============= Everything above this line should be the same in all fractal shaders";
+
+/// The shader of the mandelbrot set, from which the shared code is taken.
+const MANDELBROT_SHADER: &str = include_str!("../shaders/mandelbrot.wgsl");
+
+/// The complex functions that can be used in the generated expressions,
+/// in addition to `cmul` and `cpow` from the shared code.
+const FORMULA_FUNCTIONS: &str = r#"
+fn cdiv(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return cmul(a, vec2<f32>(b.x, -b.y)) / dot(b, b);
+}
+
+fn cexp(z: vec2<f32>) -> vec2<f32> {
+    return exp(z.x) * vec2<f32>(cos(z.y), sin(z.y));
+}
+
+fn clog(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(log(length(z)), atan2(z.y, z.x));
+}
+
+// Raises a to the complex power b. Zero is left unchanged, like in cpow.
+fn cpow_complex(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    if a.x == 0f && a.y == 0f {
+        return a;
+    }
+    return cexp(cmul(b, clog(a)));
+}
+
+fn csqrt(z: vec2<f32>) -> vec2<f32> {
+    return cpow(z, 0.5f);
+}
+
+fn csin(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(sin(z.x) * cosh(z.y), cos(z.x) * sinh(z.y));
+}
+
+fn ccos(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(cos(z.x) * cosh(z.y), -sin(z.x) * sinh(z.y));
+}
+
+fn ctan(z: vec2<f32>) -> vec2<f32> {
+    return cdiv(csin(z), ccos(z));
+}
+
+fn csinh(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(sinh(z.x) * cos(z.y), cosh(z.x) * sin(z.y));
+}
+
+fn ccosh(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(cosh(z.x) * cos(z.y), sinh(z.x) * sin(z.y));
+}
+
+fn ctanh(z: vec2<f32>) -> vec2<f32> {
+    return cdiv(csinh(z), ccosh(z));
+}
+
+fn cconj(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(z.x, -z.y);
+}
+
+fn cabs(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(length(z), 0f);
+}
+
+fn cre(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(z.x, 0f);
+}
+
+fn cim(z: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(z.y, 0f);
+}
+"#;
+
+/// Formats a number as a WGSL `f32` literal.
+fn wgsl_float(n: f64) -> String {
+    format!("{:?}f", (n as f32).clamp(f32::MIN, f32::MAX))
+}
+
+impl Expr {
+    /// Translates the expression to WGSL, where complex numbers are `vec2<f32>`.
+    pub(crate) fn to_wgsl(&self) -> String {
+        match self {
+            Expr::Num(n) => format!("vec2<f32>({}, 0f)", wgsl_float(*n)),
+            Expr::I => "vec2<f32>(0f, 1f)".to_string(),
+            Expr::Z => "z".to_string(),
+            Expr::C => "point".to_string(),
+            Expr::Neg(a) => format!("(-{})", a.to_wgsl()),
+            Expr::Add(a, b) => format!("({} + {})", a.to_wgsl(), b.to_wgsl()),
+            Expr::Sub(a, b) => format!("({} - {})", a.to_wgsl(), b.to_wgsl()),
+            Expr::Mul(a, b) => format!("cmul({}, {})", a.to_wgsl(), b.to_wgsl()),
+            Expr::Div(a, b) => format!("cdiv({}, {})", a.to_wgsl(), b.to_wgsl()),
+            Expr::Pow(base, exp) => match **exp {
+                Expr::Num(n) => format!("cpow({}, {})", base.to_wgsl(), wgsl_float(n)),
+                _ => format!("cpow_complex({}, {})", base.to_wgsl(), exp.to_wgsl()),
+            },
+            Expr::Call(func, arg) => {
+                let (name, _) = FUNCTIONS.iter().find(|(_, f)| f == func).unwrap();
+                format!("c{}({})", name, arg.to_wgsl())
+            }
+        }
+    }
+}
+
+/// Returns the complete source of the shader iterating the given expression.
+pub(crate) fn shader_source(expr: &Expr) -> String {
+    let header = MANDELBROT_SHADER.split(HEADER_END).next().unwrap();
+    format!(
+        r#"{header}{HEADER_END}
+{FORMULA_FUNCTIONS}
+
+fn diverg(point: vec2<f32>) -> Diverg {{
+    var iter: i32 = 0i;
+    var z: vec2<f32> = vec2<f32>(params.mandel_constant_real, params.mandel_constant_imag);
+
+    // The minimum distance between the orbit and the orbit trap.
+    var trap = 1e38f;
+    // The quantities used to color the point if it does not escape.
+    var interior = new_interior();
+    // A previous term, compared with the next ones to stop the sequences that repeat themselves.
+    var saved = z;
+    while !escaped(z) && iter < params.max_iter {{
+        z = {};
+        if params.trap_shape >= 0i {{
+            trap = min(trap, trap_distance(z));
+        }}
+        if params.interior_mode > 0i {{
+            interior = interior_track(interior, z, iter + 1i);
+        }}
+        iter = iter + 1i;
+        if params.interior_mode == 0i {{
+            // A sequence that repeated itself will never escape (Brent's algorithm).
+            if all(z == saved) {{
+                iter = params.max_iter;
+            }} else if (iter & (iter - 1i)) == 0i {{
+                saved = z;
+            }}
+        }}
+    }}
+    if iter == params.max_iter {{
+        return Diverg(-1i, NO_BASIN, 0f, 0f, 0f, interior_value(interior));
+    }}
+    return Diverg(iter, NO_BASIN, length(z), 0f, trap, 0f);
+}}
+"#,
+        expr.to_wgsl()
+    )
+}
@@ -2,44 +2,114 @@
 
 use std::ops::AddAssign;
 
-use rug::ops::{CompleteRound, PowAssign};
 use rug::Complex;
 
-use crate::frac_logic::RenderSettings;
-use crate::fractals::Fractal;
+use crate::frac_logic::{void_diverg, Diverg, NativeSettings, PeriodicityCheck, RenderSettings};
+use crate::fractals::{
+    derive_exponent, distance_estimate, final_mag, native_distance_estimate, pow_exponent, Fractal,
+    NativeFractal,
+};
+use crate::helpers::{DoubleDouble, NativeComplex, Real};
 
 /// Implement the formula for the julia set,
-fn get_julia(mut p: Complex, render_settings: &RenderSettings) -> i32 {
+fn get_julia(mut p: Complex, render_settings: &RenderSettings) -> Diverg {
     // iteration counter
     let mut n: i32 = 0;
+    let mut trap = render_settings.trap_tracker();
+    let mut interior = render_settings.interior_tracker();
+    let mut cycle = render_settings
+        .use_interior_checks()
+        .then(PeriodicityCheck::new);
+    // The derivative of the current term with respect to the starting point.
+    let mut dz = render_settings
+        .use_distance_estimation()
+        .then(|| Complex::with_val(render_settings.prec, 1));
 
-    while *p
-        .abs_ref()
-        .complete((render_settings.prec, render_settings.prec))
-        .real()
-        < 4
-        && n < render_settings.max_iter
-    {
-        p.pow_assign(2);
+    while !render_settings.escaped(&p) && n < render_settings.max_iter {
+        if let Some(dz) = &mut dz {
+            derive_exponent(dz, &p, render_settings);
+        }
+        pow_exponent(&mut p, render_settings);
         p.add_assign(&render_settings.julia_constant);
+        if let Some(trap) = &mut trap {
+            trap.track_rug(&p);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track_rug(&p);
+        }
         n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&p, n)) {
+            return Diverg::new(-1);
+        }
     }
 
     if n == render_settings.max_iter {
-        return -1;
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, final_mag(&p));
+    if let Some(dz) = &dz {
+        diverg.dist = distance_estimate(&p, dz);
+    }
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
+}
+
+/// Same as `get_julia`, with a native floating point type.
+fn get_julia_native<T: Real>(mut p: NativeComplex<T>, settings: &NativeSettings<T>) -> Diverg {
+    let mut n: i32 = 0;
+    let mut trap = settings.trap_tracker;
+    let mut interior = settings.interior_tracker;
+    let mut cycle = settings.cycle_detection.then(PeriodicityCheck::new);
+    let mut dz = settings.distance_estimation.then(NativeComplex::<T>::one);
+
+    while !settings.escaped(p) && n < settings.max_iter {
+        if let Some(dz) = &mut dz {
+            *dz = settings.derive_exponent(*dz, p);
+        }
+        p = settings.pow_exponent(p) + settings.julia_constant;
+        if let Some(trap) = &mut trap {
+            trap.track(p);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track(p);
+        }
+        n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&p, n)) {
+            return Diverg::new(-1);
+        }
     }
 
-    n
+    if n == settings.max_iter {
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, p.mag());
+    if let Some(dz) = dz {
+        diverg.dist = native_distance_estimate(p, dz);
+    }
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
 }
 
 pub(crate) const JULIA: Fractal = Fractal {
     default_pos: (0.0, 0.0),
+    native: Some(NativeFractal {
+        f64: &get_julia_native::<f64>,
+        double_double: &get_julia_native::<DoubleDouble>,
+    }),
+    distance_estimation: true,
+    interior_coloring: true,
     get: &get_julia,
     name: "Julia",
     details: concat!(
-        "Formula: \n<acc Uₙ₊₁ = Uₙ² + C >\n",
-        // Todo: allow custom exp
+        "Formula: \n<acc Uₙ₊₁ = Uₙᵈ + C >\n",
         "Where <acc U₀> is the complex number at the position of the pixel, ",
-        "and <acc C> is a constant that can be modified.\n",
+        "<acc C> is a constant that can be modified, ",
+        "and <acc d> is the exponent, set to <acc 2> by default (see the <command exponent> command).\n",
     ),
 };
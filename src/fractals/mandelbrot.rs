@@ -1,40 +1,130 @@
 //! Contains the Mandelbrot set rendering information.
 
-use rug::ops::{CompleteRound, Pow};
 use rug::Complex;
 
-use crate::frac_logic::RenderSettings;
-use crate::fractals::Fractal;
+use crate::frac_logic::{void_diverg, Diverg, NativeSettings, PeriodicityCheck, RenderSettings};
+use crate::fractals::{
+    derive_exponent, distance_estimate, final_mag, native_distance_estimate, pow_exponent, Fractal,
+    NativeFractal,
+};
+use crate::helpers::{DoubleDouble, NativeComplex, Real};
+
+/// Returns true if `c` is in the main cardioid or in the period-2 bulb of the
+/// Mandelbrot set, which contain most of its points, and never escape.
+/// The points misclassified by the rounding are so close to the boundary
+/// that they would not escape before the maximum number of iterations either.
+pub(crate) fn in_main_components(c: NativeComplex<f64>) -> bool {
+    let q = (c.re - 0.25).powi(2) + c.im * c.im;
+    q * (q + c.re - 0.25) <= 0.25 * c.im * c.im || (c.re + 1.0).powi(2) + c.im * c.im <= 0.0625
+}
 
 /// Implement the formula for the mandelbrot set,
 /// takes a complex number which corresponds to a point in the canvas,
 /// and see if it diverges. It if does, return the number of iterations
 /// and if does not, return -1
-fn get_mandelbrot(p: Complex, render_settings: &RenderSettings) -> i32 {
+fn get_mandelbrot(p: Complex, render_settings: &RenderSettings) -> Diverg {
+    if render_settings.use_main_components_check()
+        && in_main_components(NativeComplex::new(p.real().to_f64(), p.imag().to_f64()))
+    {
+        return Diverg::new(-1);
+    }
+
     // iteration counter
     let mut n: i32 = 0;
+    let mut trap = render_settings.trap_tracker();
+    let mut interior = render_settings.interior_tracker();
+    let mut cycle = render_settings
+        .use_interior_checks()
+        .then(PeriodicityCheck::new);
     // Current term of the series
 
-    let mut z: Complex = render_settings.mandel_constant.clone();
+    let mut z = Complex::with_val(render_settings.prec, &render_settings.mandel_constant);
+    // The derivative of the current term with respect to `p`, for distance estimation.
+    let mut dz = render_settings
+        .use_distance_estimation()
+        .then(|| Complex::new(render_settings.prec));
 
-    // Compute the next term while z is not beyond 2
-    // from the origin and the maximum divergence is not passed
-    while *z
-        .abs_ref()
-        .complete((render_settings.prec, render_settings.prec))
-        .real()
-        < 2
-        && n < render_settings.max_iter
-    {
-        z = z.pow(2) + &p;
+    // Compute the next term while z has not escaped
+    // and the maximum divergence is not passed
+    while !render_settings.escaped(&z) && n < render_settings.max_iter {
+        if let Some(dz) = &mut dz {
+            derive_exponent(dz, &z, render_settings);
+            *dz += 1;
+        }
+        pow_exponent(&mut z, render_settings);
+        z += &p;
+        if let Some(trap) = &mut trap {
+            trap.track_rug(&z);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track_rug(&z);
+        }
         n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&z, n)) {
+            return Diverg::new(-1);
+        }
     }
 
     if n == render_settings.max_iter {
-        return -1;
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, final_mag(&z));
+    if let Some(dz) = &dz {
+        diverg.dist = distance_estimate(&z, dz);
     }
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
+}
 
-    n
+/// Same as `get_mandelbrot`, with a native floating point type.
+fn get_mandelbrot_native<T: Real>(p: NativeComplex<T>, settings: &NativeSettings<T>) -> Diverg {
+    if settings.main_components_check
+        && in_main_components(NativeComplex::new(p.re.to_f64(), p.im.to_f64()))
+    {
+        return Diverg::new(-1);
+    }
+
+    let mut n: i32 = 0;
+    let mut trap = settings.trap_tracker;
+    let mut interior = settings.interior_tracker;
+    let mut cycle = settings.cycle_detection.then(PeriodicityCheck::new);
+    let mut z = settings.mandel_constant;
+    let mut dz = settings
+        .distance_estimation
+        .then(NativeComplex::<T>::default);
+
+    while !settings.escaped(z) && n < settings.max_iter {
+        if let Some(dz) = &mut dz {
+            *dz = settings.derive_exponent(*dz, z) + NativeComplex::one();
+        }
+        z = settings.pow_exponent(z) + p;
+        if let Some(trap) = &mut trap {
+            trap.track(z);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track(z);
+        }
+        n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&z, n)) {
+            return Diverg::new(-1);
+        }
+    }
+
+    if n == settings.max_iter {
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, z.mag());
+    if let Some(dz) = dz {
+        diverg.dist = native_distance_estimate(z, dz);
+    }
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
 }
 
 pub(crate) const MANDELBROT: Fractal = Fractal {
@@ -42,10 +132,16 @@ pub(crate) const MANDELBROT: Fractal = Fractal {
     name: "Mandelbrot",
     details: concat!(
         "Default Formula: \n<acc Uₙ₊₁ = Uₙ²+P >\n",
-        // Todo: allow custom exp
-        "General case: \n<acc Uₙ₊₁ = Uₙ²+P >\n",
+        "General case: \n<acc Uₙ₊₁ = Uₙᵈ+P >\n",
         "Where <acc P> is the complex number at the position of the pixel, ",
-        "and <acc U₀> is a constant that can be modified, set to <acc 0+0j> by default.\n",
+        "<acc U₀> is a constant that can be modified, set to <acc 0+0j> by default, ",
+        "and <acc d> is the exponent, set to <acc 2> by default (see the <command exponent> command).\n",
     ),
     default_pos: (-0.5, 0.0),
+    native: Some(NativeFractal {
+        f64: &get_mandelbrot_native::<f64>,
+        double_double: &get_mandelbrot_native::<DoubleDouble>,
+    }),
+    distance_estimation: true,
+    interior_coloring: true,
 };
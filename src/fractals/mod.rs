@@ -1,24 +1,50 @@
 //! Contains the algorithms for each fractal
 
-use rug::Complex;
+use rug::ops::PowAssign;
+use rug::{Complex, Float};
 
+mod buffalo;
 mod burning_ship;
+mod celtic;
+mod formula;
+mod heart;
 mod julia;
 mod mandelbrot;
+mod newton;
+mod perpendicular;
+mod tricorn;
 
+pub(crate) use buffalo::BUFFALO;
 pub(crate) use burning_ship::BURNING_SHIP;
+pub(crate) use celtic::CELTIC;
+pub(crate) use formula::{Formula, FORMULA, FUNCTIONS as FORMULA_FUNCTIONS};
+pub(crate) use heart::HEART;
 pub(crate) use julia::JULIA;
-pub(crate) use mandelbrot::MANDELBROT;
+pub(crate) use mandelbrot::{in_main_components, MANDELBROT};
+pub(crate) use newton::NEWTON;
+pub(crate) use perpendicular::PERPENDICULAR;
+pub(crate) use tricorn::TRICORN;
 
-pub(crate) type FractalClos = &'static dyn Fn(Complex, &RenderSettings) -> i32;
+pub(crate) type FractalClos = &'static dyn Fn(Complex, &RenderSettings) -> Diverg;
+pub(crate) type NativeClos<T> =
+    &'static (dyn Fn(NativeComplex<T>, &NativeSettings<T>) -> Diverg + Sync);
 
-use crate::frac_logic::RenderSettings;
+use crate::frac_logic::{Diverg, NativeSettings, RenderSettings};
+use crate::helpers::{DoubleDouble, NativeComplex, Real};
+
+/// The implementations of a fractal with native floating point types, which are
+/// used instead of `rug` when the configured precision is low enough.
+pub(crate) struct NativeFractal {
+    pub(crate) f64: NativeClos<f64>,
+    pub(crate) double_double: NativeClos<DoubleDouble>,
+}
 
 /// Represents a fractal type.
 pub(crate) struct Fractal {
     /// A closure that takes in `RenderSettings`, a complex and
     /// returns the number of iterations before it diverged,
-    /// and `-1` if it reached the maximum number of iterations.
+    /// and `-1` if it reached the maximum number of iterations,
+    /// alongside the attractor it converged to, if any.
     pub(crate) get: FractalClos,
     /// The fractal display name.
     pub(crate) name: &'static str,
@@ -27,6 +53,60 @@ pub(crate) struct Fractal {
     pub(crate) details: &'static str,
     /// The default position of the canvas when first rendering the fractal.
     pub(crate) default_pos: (f64, f64),
+    /// The optional implementations of the fractal with native types.
+    pub(crate) native: Option<NativeFractal>,
+    /// Whether the fractal computes the distance estimation (on the CPU and the GPU).
+    pub(crate) distance_estimation: bool,
+    /// Whether the fractal tracks the orbits used by the interior coloring modes.
+    pub(crate) interior_coloring: bool,
+}
+
+/// Raises `z` to the exponent configured in the render settings.
+/// Zero is left unchanged whatever the exponent, so that negative
+/// exponents do not send the first iterations to infinity.
+pub(crate) fn pow_exponent(z: &mut Complex, render_settings: &RenderSettings) {
+    if z.is_zero() {
+        return;
+    }
+    // Integer exponents are computed exactly (and much faster) by MPC.
+    if render_settings.exponent.fract() == 0.0 {
+        z.pow_assign(render_settings.exponent as i32);
+    } else {
+        z.pow_assign(render_settings.exponent);
+    }
+}
+
+/// Returns the modulus of the last term of a sequence, used for smooth coloring.
+pub(crate) fn final_mag(z: &Complex) -> f32 {
+    Complex::with_val(53, z.abs_ref()).real().to_f32()
+}
+
+/// Multiplies the derivative `dz` of a sequence by the derivative of `zᵈ`,
+/// which must be done before `z` is raised to the configured exponent.
+pub(crate) fn derive_exponent(dz: &mut Complex, z: &Complex, render_settings: &RenderSettings) {
+    let mut factor = z.clone();
+    if render_settings.exponent.fract() == 0.0 {
+        factor.pow_assign(render_settings.exponent as i32 - 1);
+    } else {
+        factor.pow_assign(render_settings.exponent - 1.0);
+    }
+    *dz *= factor;
+    *dz *= render_settings.exponent;
+}
+
+/// Returns the estimated distance between a point that escaped and the set, in the
+/// complex plane, from the last term of its sequence and the derivative of this term.
+/// https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set#Distance_estimates
+pub(crate) fn distance_estimate(z: &Complex, dz: &Complex) -> f32 {
+    let mag = Float::with_val(53, z.abs_ref());
+    let ln = mag.clone().ln();
+    (mag * ln / Float::with_val(53, dz.abs_ref())).to_f32()
+}
+
+/// Same as `distance_estimate`, with a native floating point type.
+pub(crate) fn native_distance_estimate<T: Real>(z: NativeComplex<T>, dz: NativeComplex<T>) -> f32 {
+    let mag = z.norm_sqr().to_f64().sqrt();
+    (mag * mag.ln() / dz.norm_sqr().to_f64().sqrt()) as f32
 }
 
 /// Returns the index of a fractal which name matches, or `None`.
@@ -36,4 +116,15 @@ pub(crate) fn get_frac_index_by_name(name: &str) -> Option<usize> {
         .position(|f| f.name.to_lowercase().starts_with(&name.to_lowercase()))
 }
 
-pub(crate) const FRACTALS: &[Fractal] = &[MANDELBROT, BURNING_SHIP, JULIA];
+pub(crate) const FRACTALS: &[Fractal] = &[
+    MANDELBROT,
+    BURNING_SHIP,
+    JULIA,
+    TRICORN,
+    CELTIC,
+    BUFFALO,
+    PERPENDICULAR,
+    HEART,
+    NEWTON,
+    FORMULA,
+];
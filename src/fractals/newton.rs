@@ -0,0 +1,76 @@
+//! Contains the Newton fractal rendering information.
+
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+use rug::{Assign, Complex};
+
+use crate::frac_logic::{Diverg, RenderSettings};
+use crate::fractals::Fractal;
+
+/// The distance to a root under which a point is considered to have converged to it,
+/// relative to the modulus of the root when it is above 1, since the single precision
+/// shaders can not get closer than their precision to the large roots.
+const NEWTON_TOLERANCE: f64 = 1e-6;
+
+/// Apply Newton's method to the configured polynomial, starting from the given point,
+/// and return the index of the root it converged to.
+fn get_newton(mut z: Complex, render_settings: &RenderSettings) -> Diverg {
+    let poly = &render_settings.newton_poly;
+
+    // iteration counter
+    let mut n: i32 = 0;
+
+    let mut f = Complex::new(render_settings.prec);
+    let mut df = Complex::new(render_settings.prec);
+    while n < render_settings.max_iter {
+        // Check if we converged to one of the roots.
+        let (real, imag) = (z.real().to_f64(), z.imag().to_f64());
+        if let Some(basin) = poly.roots.iter().position(|root| {
+            let (root_real, root_imag) = (root.real().to_f64(), root.imag().to_f64());
+            (real - root_real).hypot(imag - root_imag)
+                < NEWTON_TOLERANCE * root_real.hypot(root_imag).max(1.0)
+        }) {
+            return Diverg::with_basin(n, basin as i32);
+        }
+
+        // Evaluate the polynomial and its derivative with Horner's method.
+        f.assign(0);
+        df.assign(0);
+        for coef in &poly.coefs {
+            df.mul_assign(&z);
+            df.add_assign(&f);
+            f.mul_assign(&z);
+            f.add_assign(coef);
+        }
+
+        // The tangent is horizontal, Newton's method cannot continue.
+        if df.is_zero() {
+            break;
+        }
+
+        f.div_assign(&df);
+        z.sub_assign(&f);
+
+        n += 1;
+    }
+
+    Diverg::new(-1)
+}
+
+pub(crate) const NEWTON: Fractal = Fractal {
+    default_pos: (0.0, 0.0),
+    native: None,
+    distance_estimation: false,
+    interior_coloring: false,
+    get: &get_newton,
+    name: "Newton",
+    details: concat!(
+        // U_{n+1} = U_n - \frac{p(U_n)}{p'(U_n)}
+        "Formula: \n<acc Uₙ₊₁ = Uₙ - p(Uₙ) / p'(Uₙ)>\n",
+        "Where <acc U₀=P> is the complex number at the position of the pixel, ",
+        "and <acc p> is a polynomial, set to <acc z³ - 1> by default (see the <command newton> command). ",
+        "Each point is colored depending on the root of <acc p> it converges to, ",
+        "and shaded by the number of iterations it took. ",
+        "Points that do not converge are filled like the inside of escape-time fractals.",
+    ),
+};
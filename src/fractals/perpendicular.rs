@@ -0,0 +1,69 @@
+//! Contains the Perpendicular Burning Ship rendering information.
+
+use std::ops::{AddAssign, MulAssign};
+
+use rug::Complex;
+
+use crate::frac_logic::{void_diverg, Diverg, PeriodicityCheck, RenderSettings};
+use crate::fractals::{final_mag, pow_exponent, Fractal};
+
+/// Implement the formula for the perpendicular burning ship, which takes the
+/// absolute value of the imaginary part only, and negates it, before raising to the exponent.
+fn get_perpendicular(mut p: Complex, render_settings: &RenderSettings) -> Diverg {
+    // invert the x and y axis, like the burning ship
+    p.mul_assign(-1);
+
+    // iteration counter
+    let mut n: i32 = 0;
+    let mut trap = render_settings.trap_tracker();
+    let mut interior = render_settings.interior_tracker();
+    let mut cycle = render_settings
+        .use_interior_checks()
+        .then(PeriodicityCheck::new);
+
+    let mut z = Complex::new(render_settings.prec);
+    while !render_settings.escaped(&z) && n < render_settings.max_iter {
+        z.mut_imag().abs_mut();
+        z.conj_mut();
+        pow_exponent(&mut z, render_settings);
+        z.add_assign(&p);
+
+        if let Some(trap) = &mut trap {
+            trap.track_rug(&z);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track_rug(&z);
+        }
+        n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&z, n)) {
+            return Diverg::new(-1);
+        }
+    }
+
+    if n == render_settings.max_iter {
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, final_mag(&z));
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
+}
+
+pub(crate) const PERPENDICULAR: Fractal = Fractal {
+    default_pos: (0.5, 0.0),
+    native: None,
+    distance_estimation: false,
+    interior_coloring: true,
+    get: &get_perpendicular,
+    name: "Perpendicular",
+    details: concat!(
+        // U_{n+1} = (\Re(U_n) - i |\Im(U_n)|)^d + P
+        "Formula: \n<acc Uₙ₊₁ = (ℜ(Uₙ) - i |ℑ(Uₙ)|)ᵈ + P>\n",
+        "Where <acc U₀=0>, ",
+        "<acc P> is the complex number at the position of the pixel, ",
+        "and <acc d> is the exponent, set to <acc 2> by default (see the <command exponent> command). ",
+        "Like the Burning Ship, the vertical and horizontal axis are inverted (negatives on the top/right).",
+    ),
+};
@@ -11,11 +11,55 @@ struct Params {
     julia_constant_imag: f32,
     mandel_constant_real: f32,
     mandel_constant_imag: f32,
+    exponent: f32,
+    newton_degree: i32,
+    // The low parts of the position and the cell size, stored as double-single
+    // numbers (see the double-single shaders).
+    pos_real_lo: f32,
+    pos_imag_lo: f32,
+    cell_size_lo: f32,
+    // Always 1, it hides the value of some expressions from the shader compiler,
+    // so that it cannot simplify the double-single arithmetic away.
+    one: f32,
+    // 1 if the distance to the set must be estimated, for the fractals that support it.
+    distance_estimation: i32,
+    // The shape of the orbit trap (see `trap_distance`), or -1 if the orbit trap coloring is not used.
+    trap_shape: i32,
+    trap_center_real: f32,
+    trap_center_imag: f32,
+    trap_size: f32,
+    // The rotation that aligns the line and the cross of the trap with the axes.
+    trap_cos: f32,
+    trap_sin: f32,
+    // The interior coloring mode (see `interior_value`), or 0 if the interior is filled.
+    interior_mode: i32,
+    // The norm compared with the bailout (see `escaped`).
+    escape_norm: i32,
+    // Complex numbers are stored in the first two components, because
+    // the elements of uniform arrays must be aligned to 16 bytes.
+    newton_coefs: array<vec4<f32>, 17>,
+    newton_roots: array<vec4<f32>, 16>,
+}
+
+// The value of `basin` when the fractal does not converge to an attractor.
+const NO_BASIN: i32 = -1i;
+
+struct Diverg {
+    iter: i32,
+    basin: i32,
+    // The modulus of the last term of the sequence, used for smooth coloring.
+    mag: f32,
+    // The estimated distance to the set, in cells.
+    dist: f32,
+    // The minimum distance between the orbit and the orbit trap.
+    trap: f32,
+    // The value used to color the point if it did not escape.
+    interior: f32,
 }
 
 
 
-@group(0) @binding(0) var<storage, read_write> output_buf: array<i32>; 
+@group(0) @binding(0) var<storage, read_write> output_buf: array<Diverg>; 
 @group(0) @binding(1) var<uniform> params: Params; 
 
 fn coords_to_c(x_: u32, y_: u32) -> vec2<f32> {
@@ -37,25 +81,201 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let point = coords_to_c(x, y);
     let index = y * u32(params.width_px) + x;
 
-    output_buf[index] = iterations(point);
+    output_buf[index] = diverg(point);
 }
 
+fn cmul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+// Raises z to the power n. Zero is left unchanged whatever the exponent,
+// the same way as on the CPU.
+fn cpow(z: vec2<f32>, n: f32) -> vec2<f32> {
+    if z.x == 0f && z.y == 0f {
+        return z;
+    }
+    // Integer exponents use exponentiation by squaring, which is more precise.
+    if fract(n) == 0f {
+        var result = vec2<f32>(1f, 0f);
+        var base = z;
+        var e = i32(abs(n));
+        while e > 0 {
+            if (e & 1) == 1 {
+                result = cmul(result, base);
+            }
+            base = cmul(base, base);
+            e = e >> 1u;
+        }
+        if n < 0f {
+            return vec2<f32>(result.x, -result.y) / dot(result, result);
+        }
+        return result;
+    }
+    let r = pow(length(z), n);
+    let theta = atan2(z.y, z.x) * n;
+    return r * vec2<f32>(cos(theta), sin(theta));
+}
+
+// Multiplies the derivative dz of a sequence by the derivative of z^n,
+// for distance estimation.
+fn dpow(dz: vec2<f32>, z: vec2<f32>, n: f32) -> vec2<f32> {
+    return n * cmul(cpow(z, n - 1f), dz);
+}
+
+// Returns the estimated distance between a point that escaped and the set, in cells,
+// from the last term of its sequence and the derivative of this term.
+fn distance_estimate(z: vec2<f32>, dz: vec2<f32>) -> f32 {
+    let mag = length(z);
+    return mag * log(mag) / length(dz) / params.cell_size;
+}
+
+// Returns the distance between a term of a sequence and the orbit trap, the same way as the CPU.
+fn trap_distance(z: vec2<f32>) -> f32 {
+    let offset = z - vec2<f32>(params.trap_center_real, params.trap_center_imag);
+    let rotated = cmul(offset, vec2<f32>(params.trap_cos, params.trap_sin));
+    switch params.trap_shape {
+        case 0i: {
+            return length(offset);
+        }
+        case 1i: {
+            return abs(rotated.y);
+        }
+        case 3i: {
+            return abs(length(offset) - params.trap_size);
+        }
+        // The cross and the Pickover stalks.
+        default: {
+            return min(abs(rotated.x), abs(rotated.y));
+        }
+    }
+}
+
+
+// Returns true if a term has escaped, with the selected norm (see `EscapeNorm`).
+fn escaped(z: vec2<f32>) -> bool {
+    switch params.escape_norm {
+        case 1i: {
+            return max(abs(z.x), abs(z.y)) >= params.bailout;
+        }
+        case 2i: {
+            return abs(z.x) >= params.bailout;
+        }
+        case 3i: {
+            return abs(z.y) >= params.bailout;
+        }
+        case 4i: {
+            return abs(z.x) + abs(z.y) >= params.bailout;
+        }
+        default: {
+            return dot(z, z) >= params.bailout * params.bailout;
+        }
+    }
+}
+
+// The quantities of an orbit used to color the point if it does not escape,
+// tracked the same way as the CPU.
+struct Interior {
+    last: vec2<f32>,
+    // The smallest squared modulus of the terms so far, and the index of that term.
+    min_norm: f32,
+    domain: i32,
+    // A previous term, compared with the next ones to detect a cycle. It is
+    // replaced each time the index reaches a power of two, like in Brent's algorithm.
+    reference: vec2<f32>,
+    reference_iter: i32,
+    period: i32,
+}
+
+// Two terms closer than this distance are considered equal by the cycle detection.
+const PERIOD_EPSILON: f32 = 1e-5f;
+
+fn new_interior() -> Interior {
+    return Interior(vec2<f32>(0f, 0f), 1e38f, 0i, vec2<f32>(1e30f, 1e30f), 0i, 0i);
+}
+
+// Updates the interior quantities with the term of the given index.
+fn interior_track(state: Interior, z: vec2<f32>, iter: i32) -> Interior {
+    var s = state;
+    s.last = z;
+    let norm = dot(z, z);
+    if norm < s.min_norm {
+        s.min_norm = norm;
+        s.domain = iter;
+    }
+    if s.period == 0i && length(z - s.reference) < PERIOD_EPSILON {
+        s.period = iter - s.reference_iter;
+    }
+    if (iter & (iter - 1i)) == 0i {
+        s.reference = z;
+        s.reference_iter = iter;
+    }
+    return s;
+}
+
+// Returns the quantity of the interior coloring mode, in the order of `InteriorMode`.
+fn interior_value(s: Interior) -> f32 {
+    switch params.interior_mode {
+        case 1i: {
+            return length(s.last);
+        }
+        case 2i: {
+            return atan2(s.last.y, s.last.x);
+        }
+        case 3i: {
+            return f32(s.period);
+        }
+        case 4i: {
+            return f32(s.domain);
+        }
+        default: {
+            return 0f;
+        }
+    }
+}
 
 // ============= Everything above this line should be the same in all fractal shaders
 
-fn iterations(point: vec2<f32>) -> i32 {
+fn diverg(point: vec2<f32>) -> Diverg {
     var iter: i32 = 0i;
     var z: vec2<f32> = vec2<f32>(0f, 0f);
+    // The derivative of the current term with respect to the point, for distance estimation.
+    var dz: vec2<f32> = vec2<f32>(0f, 0f);
 
-    while length(z) < params.bailout && iter < params.max_iter {
-        z = vec2<f32>(
-            pow(z.x, 2f) - pow(z.y, 2f) - point.x,
-            2f * abs(z.x) * abs(z.y) - point.y
-        );
+    // The minimum distance between the orbit and the orbit trap.
+    var trap = 1e38f;
+    // The quantities used to color the point if it does not escape.
+    var interior = new_interior();
+    // A previous term, compared with the next ones to stop the sequences that repeat themselves.
+    var saved = z;
+    while !escaped(z) && iter < params.max_iter {
+        if params.distance_estimation != 0i {
+            // Taking the absolute value of a part flips the sign of its derivative.
+            dz = dpow(select(dz, -dz, z < vec2<f32>(0f)), abs(z), params.exponent)
+                - vec2<f32>(1f, 0f);
+        }
+        z = cpow(abs(z), params.exponent) - point;
+        if params.trap_shape >= 0i {
+            trap = min(trap, trap_distance(z));
+        }
+        if params.interior_mode > 0i {
+            interior = interior_track(interior, z, iter + 1i);
+        }
         iter = iter + 1i;
+        if params.interior_mode == 0i {
+            // A sequence that repeated itself will never escape (Brent's algorithm).
+            if all(z == saved) {
+                iter = params.max_iter;
+            } else if (iter & (iter - 1i)) == 0i {
+                saved = z;
+            }
+        }
     }
     if iter == params.max_iter {
-        return -1i;
+        return Diverg(-1i, NO_BASIN, 0f, 0f, 0f, interior_value(interior));
+    }
+    var dist = 0f;
+    if params.distance_estimation != 0i {
+        dist = distance_estimate(z, dz);
     }
-    return iter;
+    return Diverg(iter, NO_BASIN, length(z), dist, trap, 0f);
 }
@@ -0,0 +1,62 @@
+//! Contains the Tricorn (Mandelbar) set rendering information.
+
+use rug::Complex;
+
+use crate::frac_logic::{void_diverg, Diverg, PeriodicityCheck, RenderSettings};
+use crate::fractals::{final_mag, pow_exponent, Fractal};
+
+/// Implement the formula for the tricorn set, which is the same as the
+/// mandelbrot set except that the complex conjugate of each term is used.
+fn get_tricorn(p: Complex, render_settings: &RenderSettings) -> Diverg {
+    // iteration counter
+    let mut n: i32 = 0;
+    let mut trap = render_settings.trap_tracker();
+    let mut interior = render_settings.interior_tracker();
+    let mut cycle = render_settings
+        .use_interior_checks()
+        .then(PeriodicityCheck::new);
+
+    let mut z = Complex::new(render_settings.prec);
+    while !render_settings.escaped(&z) && n < render_settings.max_iter {
+        z.conj_mut();
+        pow_exponent(&mut z, render_settings);
+        z += &p;
+        if let Some(trap) = &mut trap {
+            trap.track_rug(&z);
+        }
+        if let Some(interior) = &mut interior {
+            interior.track_rug(&z);
+        }
+        n += 1;
+        if cycle.as_mut().is_some_and(|cycle| cycle.repeats(&z, n)) {
+            return Diverg::new(-1);
+        }
+    }
+
+    if n == render_settings.max_iter {
+        return void_diverg(&interior);
+    }
+
+    let mut diverg = Diverg::with_mag(n, final_mag(&z));
+    if let Some(trap) = &trap {
+        diverg.trap = trap.min_distance();
+    }
+    diverg
+}
+
+pub(crate) const TRICORN: Fractal = Fractal {
+    default_pos: (-0.3, 0.0),
+    native: None,
+    distance_estimation: false,
+    interior_coloring: true,
+    get: &get_tricorn,
+    name: "Tricorn",
+    details: concat!(
+        // U_{n+1} = \overline{U_n}^d + P
+        "Formula: \n<acc Uₙ₊₁ = (Ūₙ)ᵈ + P>\n",
+        "Where <acc U₀=0>, <acc Ūₙ> is the complex conjugate of <acc Uₙ>, ",
+        "<acc P> is the complex number at the position of the pixel, ",
+        "and <acc d> is the exponent, set to <acc 2> by default (see the <command exponent> command). ",
+        "Also known as the Mandelbar set.",
+    ),
+};
@@ -16,6 +16,8 @@ pub(crate) struct Chunks {
     pub(crate) canvas: Rect,
     /// The area of the log panel, including the borders.
     pub(crate) log_panel: Rect,
+    /// The area of the job panel, displayed over the canvas when it is visible.
+    pub(crate) job_panel: Rect,
     /// The area of the command input.
     pub(crate) input: Rect,
     /// The area of the footer, which corresponds to the
@@ -59,6 +61,7 @@ impl Chunks {
             return Self {
                 footer,
                 canvas: body,
+                job_panel: Self::job_panel(body, app),
                 log_panel: Rect::default(),
                 input: Rect::default(),
             };
@@ -84,10 +87,20 @@ impl Chunks {
         Self {
             footer,
             canvas,
+            job_panel: Self::job_panel(canvas, app),
             log_panel,
             input,
         }
     }
+
+    /// Returns the area of the job panel in the canvas, or an empty area if it is hidden.
+    fn job_panel(canvas: Rect, app: &App) -> Rect {
+        if app.app_state.show_job_panel {
+            canvas.inner(Margin::new(2, 1))
+        } else {
+            Rect::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// How the iteration counts of the divergent points are turned into colors.
+#[derive(PartialEq, EnumIter, Debug, Display, Clone, Copy, Deserialize, Serialize)]
+pub(crate) enum ColoringMode {
+    /// Use the integer iteration counts, which shows distinct color bands.
+    Banded,
+    /// Use continuous iteration counts, computed from the final modulus of each point.
+    Smooth,
+    /// Use the estimated distance to the set, which shows the thin filaments.
+    /// Only available for some fractals, the others use smooth coloring.
+    Distance,
+    /// Use the minimum distance between the orbit of each point and the orbit trap.
+    OrbitTrap,
+}
+
+// Todo: find a way to make this a constant
+pub(crate) fn coloring_modes() -> Vec<ColoringMode> {
+    ColoringMode::iter().collect()
+}
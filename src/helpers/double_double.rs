@@ -0,0 +1,151 @@
+//! Contains the `DoubleDouble` type, which stores a real number as the
+//! unevaluated sum of two `f64`, for about twice their precision.
+//! https://en.wikipedia.org/wiki/Quadruple-precision_floating-point_format#Double-double_arithmetic
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use rug::Float;
+
+use super::Real;
+
+/// A real number equal to `hi + lo`, where `|lo|` is at most half an ulp of `hi`.
+/// Since `hi` is compared first, the derived ordering is correct.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub(crate) struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+/// Returns `a + b` and its rounding error, assuming that `|a| >= |b|`.
+fn quick_two_sum(a: f64, b: f64) -> DoubleDouble {
+    let hi = a + b;
+    DoubleDouble { hi, lo: b - (hi - a) }
+}
+
+/// Returns `a + b` and its rounding error.
+fn two_sum(a: f64, b: f64) -> DoubleDouble {
+    let hi = a + b;
+    let b_virtual = hi - a;
+    DoubleDouble {
+        hi,
+        lo: (a - (hi - b_virtual)) + (b - b_virtual),
+    }
+}
+
+/// Returns `a * b` and its rounding error.
+fn two_prod(a: f64, b: f64) -> DoubleDouble {
+    let hi = a * b;
+    DoubleDouble {
+        hi,
+        lo: a.mul_add(b, -hi),
+    }
+}
+
+impl Real for DoubleDouble {
+    const PREC: u32 = 2 * f64::MANTISSA_DIGITS;
+
+    fn from_f64(value: f64) -> Self {
+        Self { hi: value, lo: 0.0 }
+    }
+    fn from_float(value: &Float) -> Self {
+        let hi = value.to_f64();
+        if !hi.is_finite() {
+            return Self::from_f64(hi);
+        }
+        let lo = Float::with_val(value.prec(), value - hi).to_f64();
+        quick_two_sum(hi, lo)
+    }
+    fn to_f64(self) -> f64 {
+        self.hi
+    }
+    fn abs(self) -> Self {
+        if self.hi < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        let hi = two_sum(self.hi, rhs.hi);
+        let lo = two_sum(self.lo, rhs.lo);
+        let sum = quick_two_sum(hi.hi, hi.lo + lo.hi);
+        quick_two_sum(sum.hi, sum.lo + lo.lo)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let prod = two_prod(self.hi, rhs.hi);
+        quick_two_sum(
+            prod.hi,
+            prod.lo + (self.hi * rhs.lo + self.lo * rhs.hi),
+        )
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        // Long division, each quotient digit being computed with an `f64` division.
+        let q1 = self.hi / rhs.hi;
+        let rem = self - rhs * Self::from_f64(q1);
+        let q2 = rem.hi / rhs.hi;
+        let rem = rem - rhs * Self::from_f64(q2);
+        let q3 = rem.hi / rhs.hi;
+        let q = quick_two_sum(q1, q2);
+        q + Self::from_f64(q3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREC: u32 = 256;
+
+    /// Returns the error of a double-double relatively to the exact value.
+    fn rel_error(value: DoubleDouble, exact: &Float) -> f64 {
+        let value = Float::with_val(PREC, value.hi) + value.lo;
+        Float::with_val(PREC, (value - exact) / exact).to_f64().abs()
+    }
+
+    #[test]
+    fn test_double_double_arithmetic() {
+        let a_exact = Float::with_val(PREC, 2).sqrt();
+        let b_exact = Float::with_val(PREC, 3).ln();
+        let a = DoubleDouble::from_float(&a_exact);
+        let b = DoubleDouble::from_float(&b_exact);
+
+        let tolerance = 1e-30;
+        assert!(rel_error(a, &a_exact) < tolerance);
+        assert!(rel_error(a + b, &Float::with_val(PREC, &a_exact + &b_exact)) < tolerance);
+        assert!(rel_error(a - b, &Float::with_val(PREC, &a_exact - &b_exact)) < tolerance);
+        assert!(rel_error(a * b, &Float::with_val(PREC, &a_exact * &b_exact)) < tolerance);
+        assert!(rel_error(a / b, &Float::with_val(PREC, &a_exact / &b_exact)) < tolerance);
+
+        assert!(-a < b && b < a);
+        assert_eq!((-a).abs(), a);
+    }
+}
@@ -7,4 +7,5 @@ pub(crate) enum Focus {
     Canvas,
     LogPanel,
     Input,
+    JobPanel,
 }
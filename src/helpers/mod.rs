@@ -1,8 +1,13 @@
 //! Contains useful structs and data structures used across all the codebase.
 
 mod chunks;
+mod coloring_modes;
+mod double_double;
 mod focus;
 mod increment_wrap;
+mod native_complex;
+pub(crate) mod polynomial;
+mod real;
 mod saved_state;
 mod vec2;
 mod void_fills;
@@ -10,8 +15,13 @@ mod zoom_direction;
 
 pub(crate) mod markup;
 pub(crate) use chunks::Chunks;
+pub(crate) use coloring_modes::{coloring_modes, ColoringMode};
+pub(crate) use double_double::DoubleDouble;
 pub(crate) use focus::Focus;
 pub(crate) use increment_wrap::{decrement_wrap, increment_wrap};
+pub(crate) use native_complex::NativeComplex;
+pub(crate) use polynomial::Polynomial;
+pub(crate) use real::Real;
 pub(crate) use saved_state::SavedState;
 pub(crate) use vec2::Vec2;
 pub(crate) use void_fills::{void_fills, VoidFill};
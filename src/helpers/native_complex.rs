@@ -0,0 +1,95 @@
+//! Contains the `NativeComplex` struct, a complex number made of native real numbers.
+
+use std::ops::{Add, Mul, Sub};
+
+use rug::Complex;
+
+use super::Real;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct NativeComplex<T> {
+    pub(crate) re: T,
+    pub(crate) im: T,
+}
+
+impl<T: Real> NativeComplex<T> {
+    pub(crate) fn new(re: T, im: T) -> Self {
+        Self { re, im }
+    }
+
+    pub(crate) fn one() -> Self {
+        Self::new(T::from_f64(1.0), T::default())
+    }
+
+    /// Converts an arbitrary precision complex, rounding each part to the nearest value.
+    pub(crate) fn from_rug(c: &Complex) -> Self {
+        Self::new(T::from_float(c.real()), T::from_float(c.imag()))
+    }
+
+    pub(crate) fn is_zero(self) -> bool {
+        self.re == T::default() && self.im == T::default()
+    }
+
+    /// Returns the square of the modulus, which is faster to compute than the modulus.
+    pub(crate) fn norm_sqr(self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// Returns the modulus as an `f32`, used for smooth coloring.
+    pub(crate) fn mag(self) -> f32 {
+        self.norm_sqr().to_f64().sqrt() as f32
+    }
+
+    pub(crate) fn scale(self, factor: T) -> Self {
+        Self::new(self.re * factor, self.im * factor)
+    }
+
+    /// Raises the number to an integer power, using exponentiation by squaring.
+    pub(crate) fn powi(self, exp: i32) -> Self {
+        let mut result = Self::one();
+        let mut base = self;
+        let mut e = exp.unsigned_abs();
+        while e > 0 {
+            if e & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            e >>= 1;
+        }
+        if exp < 0 {
+            let norm = result.norm_sqr();
+            return Self::new(result.re / norm, -result.im / norm);
+        }
+        result
+    }
+}
+
+impl NativeComplex<f64> {
+    pub(crate) fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+impl<T: Real> Add for NativeComplex<T> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl<T: Real> Sub for NativeComplex<T> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl<T: Real> Mul for NativeComplex<T> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
@@ -0,0 +1,252 @@
+//! Contains the `Polynomial` struct, used by the Newton fractal.
+
+use rug::{Complex, Float};
+
+use super::markup::esc;
+
+/// The maximum degree of a polynomial, limited by the size of the GPU parameter arrays.
+pub(crate) const MAX_POLY_DEGREE: usize = 16;
+/// The precision used to store the coefficients and the roots.
+const POLY_PREC: u32 = 128;
+/// The maximum number of Durand-Kerner iterations when searching for the roots.
+const DK_MAX_ITER: usize = 1000;
+/// The Durand-Kerner iterations stop when no root estimate moves more than this distance.
+const DK_TOLERANCE: f64 = 1e-30;
+
+/// Represents a polynomial with complex coefficients, alongside its roots.
+#[derive(Clone, Debug)]
+pub(crate) struct Polynomial {
+    /// The coefficients of the monic polynomial, from the highest to the lowest degree.
+    pub(crate) coefs: Vec<Complex>,
+    /// The roots of the polynomial, their order defines the index of the convergence basins.
+    pub(crate) roots: Vec<Complex>,
+}
+
+/// `z³ - 1`, the classic Newton fractal.
+impl Default for Polynomial {
+    fn default() -> Self {
+        Self::from_coefs(
+            [1, 0, 0, -1]
+                .iter()
+                .map(|c| Complex::with_val(POLY_PREC, *c))
+                .collect(),
+        )
+        .unwrap()
+    }
+}
+
+impl Polynomial {
+    /// Create a monic polynomial from its roots.
+    pub(crate) fn from_roots(roots: Vec<Complex>) -> Result<Self, String> {
+        check_degree(roots.len())?;
+
+        // Expand the product of the `(z - root)` factors.
+        let mut coefs = vec![Complex::with_val(POLY_PREC, 1)];
+        for root in &roots {
+            let mut next = coefs.clone();
+            next.push(Complex::new(POLY_PREC));
+            for (i, coef) in coefs.iter().enumerate() {
+                next[i + 1] -= Complex::with_val(POLY_PREC, coef * root);
+            }
+            coefs = next;
+        }
+
+        Ok(Self { coefs, roots })
+    }
+
+    /// Create a polynomial from its coefficients (from the highest to the lowest degree),
+    /// and find its roots with the Durand-Kerner method.
+    pub(crate) fn from_coefs(coefs: Vec<Complex>) -> Result<Self, String> {
+        check_degree(coefs.len().saturating_sub(1))?;
+        if coefs[0].is_zero() {
+            return Err("The leading coefficient must not be zero.".to_string());
+        }
+
+        // Newton's method is not affected by the scale of the polynomial, so
+        // we can work with the monic polynomial.
+        let leading = coefs[0].clone();
+        let coefs: Vec<Complex> = coefs
+            .into_iter()
+            .map(|c| Complex::with_val(POLY_PREC, c / &leading))
+            .collect();
+        let roots = durand_kerner(&coefs);
+
+        Ok(Self { coefs, roots })
+    }
+
+    /// Returns the degree of the polynomial.
+    pub(crate) fn degree(&self) -> usize {
+        self.roots.len()
+    }
+}
+
+fn check_degree(degree: usize) -> Result<(), String> {
+    if !(1..=MAX_POLY_DEGREE).contains(&degree) {
+        return Err(format!(
+            "The degree of the polynomial must be between 1 and {MAX_POLY_DEGREE}."
+        ));
+    }
+    Ok(())
+}
+
+/// Evaluates the polynomial with the given coefficients at `z`, using Horner's method.
+fn horner(coefs: &[Complex], z: &Complex) -> Complex {
+    let mut result = Complex::new(POLY_PREC);
+    for coef in coefs {
+        result *= z;
+        result += coef;
+    }
+    result
+}
+
+/// Find all the roots of the given monic polynomial simultaneously.
+/// https://en.wikipedia.org/wiki/Durand%E2%80%93Kerner_method
+fn durand_kerner(coefs: &[Complex]) -> Vec<Complex> {
+    let degree = coefs.len() - 1;
+
+    // The initial estimates are the powers of a number which is
+    // neither real nor a root of unity.
+    let seed = Complex::with_val(POLY_PREC, (0.4, 0.9));
+    let mut roots = Vec::with_capacity(degree);
+    let mut estimate = Complex::with_val(POLY_PREC, 1);
+    for _ in 0..degree {
+        roots.push(estimate.clone());
+        estimate *= &seed;
+    }
+
+    for _ in 0..DK_MAX_ITER {
+        let mut max_delta: f64 = 0.0;
+        for i in 0..degree {
+            let mut denom = Complex::with_val(POLY_PREC, 1);
+            for j in (0..degree).filter(|j| *j != i) {
+                denom *= Complex::with_val(POLY_PREC, &roots[i] - &roots[j]);
+            }
+            let delta = horner(coefs, &roots[i]) / &denom;
+            max_delta = max_delta.max(delta.real().to_f64().hypot(delta.imag().to_f64()));
+            roots[i] -= &delta;
+        }
+        if max_delta < DK_TOLERANCE {
+            break;
+        }
+    }
+
+    roots
+}
+
+/// Parse a complex number written like `1`, `2.5i`, `-i` or `1e-3-2i`.
+pub(crate) fn parse_complex(input: &str) -> Result<Complex, String> {
+    let err = || format!("<acc {}> is not a valid complex number.", esc(input));
+    let parse_float = |s: &str| -> Result<Float, String> {
+        let parsed = Float::with_val(POLY_PREC, Float::parse(s).map_err(|_| err())?);
+        if !parsed.is_finite() {
+            return Err(err());
+        }
+        Ok(parsed)
+    };
+
+    let Some(without_i) = input.strip_suffix('i') else {
+        return Ok(Complex::with_val(POLY_PREC, (parse_float(input)?, 0)));
+    };
+
+    // The imaginary part begins at the last sign, unless it is
+    // the first character or part of an exponent.
+    let split = without_i
+        .char_indices()
+        .rev()
+        .find(|(i, c)| {
+            (*c == '+' || *c == '-') && *i > 0 && !without_i[..*i].ends_with(['e', 'E'])
+        })
+        .map_or(0, |(i, _)| i);
+
+    let (real, imag) = without_i.split_at(split);
+    let imag = match imag {
+        "" | "+" => "1",
+        "-" => "-1",
+        imag => imag,
+    };
+    let real = if real.is_empty() {
+        Float::new(POLY_PREC)
+    } else {
+        parse_float(real)?
+    };
+
+    Ok(Complex::with_val(POLY_PREC, (real, parse_float(imag)?)))
+}
+
+/// Parse a comma separated list of complex numbers.
+pub(crate) fn parse_complex_list(input: &str) -> Result<Vec<Complex>, String> {
+    input.split(',').map(parse_complex).collect()
+}
+
+/// Format a complex number so that it can be parsed back with `parse_complex`.
+pub(crate) fn format_complex(c: &Complex) -> String {
+    let real = c.real().to_f64();
+    let imag = c.imag().to_f64();
+    // Parts that are negligible compared to the other one are
+    // leftovers of the root finding algorithm.
+    let negligible = real.hypot(imag) * 1e-12;
+
+    match (real.abs() <= negligible, imag.abs() <= negligible) {
+        (_, true) => format!("{real}"),
+        (true, false) => format!("{imag}i"),
+        (false, false) => format!("{real}{imag:+}i"),
+    }
+}
+
+/// Format a list of complex numbers so that it can be parsed back with `parse_complex_list`.
+pub(crate) fn format_complex_list(values: &[Complex]) -> String {
+    values
+        .iter()
+        .map(format_complex)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(real: f64, imag: f64) -> Complex {
+        Complex::with_val(POLY_PREC, (real, imag))
+    }
+
+    #[test]
+    fn test_parse_complex() {
+        assert_eq!(parse_complex("2").unwrap(), c(2.0, 0.0));
+        assert_eq!(parse_complex("-i").unwrap(), c(0.0, -1.0));
+        assert_eq!(parse_complex("2.5i").unwrap(), c(0.0, 2.5));
+        assert_eq!(parse_complex("1-i").unwrap(), c(1.0, -1.0));
+        assert_eq!(parse_complex("5e-1+2e1i").unwrap(), c(0.5, 20.0));
+        assert!(parse_complex("").is_err());
+        assert!(parse_complex("1+2j").is_err());
+        assert!(parse_complex("inf").is_err());
+
+        // Formatting and parsing back should give the same list.
+        let list = vec![c(1.0, 0.0), c(-0.5, 0.25), c(0.0, -3.0)];
+        assert_eq!(
+            parse_complex_list(&format_complex_list(&list)).unwrap(),
+            list
+        );
+    }
+
+    #[test]
+    fn test_polynomial_roots() {
+        // z² + 1 has roots i and -i
+        let poly = Polynomial::from_coefs(vec![c(2.0, 0.0), c(0.0, 0.0), c(2.0, 0.0)]).unwrap();
+        assert_eq!(poly.degree(), 2);
+        for root in [c(0.0, 1.0), c(0.0, -1.0)] {
+            assert!(poly
+                .roots
+                .iter()
+                .any(|r| Complex::with_val(53, r - &root).abs().real().to_f64() < 1e-20));
+        }
+
+        // (z - 1)(z + 2) = z² + z - 2
+        let poly = Polynomial::from_roots(vec![c(1.0, 0.0), c(-2.0, 0.0)]).unwrap();
+        assert_eq!(poly.coefs, vec![c(1.0, 0.0), c(1.0, 0.0), c(-2.0, 0.0)]);
+
+        // Constant and zero-leading polynomials are rejected.
+        assert!(Polynomial::from_coefs(vec![c(1.0, 0.0)]).is_err());
+        assert!(Polynomial::from_coefs(vec![c(0.0, 0.0), c(1.0, 0.0)]).is_err());
+    }
+}
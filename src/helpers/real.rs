@@ -0,0 +1,50 @@
+//! Contains the `Real` trait, implemented by the native floating point types
+//! that can replace `rug` when the precision is low enough.
+
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use rug::Float;
+
+/// A native real number type, such as `f64` or `DoubleDouble`.
+pub(crate) trait Real:
+    Copy
+    + Debug
+    + Default
+    + PartialOrd
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// The number of bits of the mantissa.
+    const PREC: u32;
+
+    fn from_f64(value: f64) -> Self;
+    /// Converts an arbitrary precision number, rounding it to the nearest value.
+    fn from_float(value: &Float) -> Self;
+    /// Converts the number to the nearest `f64`.
+    fn to_f64(self) -> f64;
+    fn abs(self) -> Self;
+}
+
+impl Real for f64 {
+    const PREC: u32 = f64::MANTISSA_DIGITS;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+    fn from_float(value: &Float) -> Self {
+        value.to_f64()
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
@@ -1,8 +1,11 @@
-use crate::{frac_logic::RenderSettings, VERSION};
+use crate::{
+    frac_logic::{DensityMode, EscapeNorm, InteriorMode, RenderSettings, TrapShape},
+    VERSION,
+};
 use serde::{Deserialize, Serialize};
 use std::{fs::File, io::Write, str::FromStr};
 
-use super::{markup::esc, void_fills, VoidFill};
+use super::{markup::esc, polynomial::format_complex_list, void_fills, ColoringMode, VoidFill};
 
 /// Describes the state data that can be saved to a rsf file.
 #[derive(Serialize, Deserialize)]
@@ -15,15 +18,33 @@ pub(crate) struct SavedState {
     pub(crate) precision: Option<u32>,
     pub(crate) max_iter: Option<i32>,
     pub(crate) void_fill: Option<VoidFill>,
+    pub(crate) coloring_mode: Option<ColoringMode>,
+    pub(crate) distance_threshold: Option<f64>,
+    pub(crate) trap_shape: Option<TrapShape>,
+    pub(crate) trap_center: Option<String>,
+    pub(crate) trap_size: Option<f64>,
+    pub(crate) trap_angle: Option<f64>,
+    pub(crate) density_mode: Option<DensityMode>,
+    pub(crate) samples_per_pixel: Option<u32>,
+    pub(crate) nebula_limits: Option<[i32; 3]>,
+    pub(crate) interior_mode: Option<InteriorMode>,
+    pub(crate) interior_palette_name: Option<String>,
     pub(crate) julia_constant: Option<String>,
     pub(crate) mandel_constant: Option<String>,
     pub(crate) bailout: Option<f32>,
+    pub(crate) escape_norm: Option<EscapeNorm>,
+    pub(crate) exponent: Option<f64>,
+    pub(crate) newton_roots: Option<String>,
+    pub(crate) formula: Option<String>,
     pub(crate) smoothness: Option<i32>,
     pub(crate) hsl_mode: Option<bool>,
     pub(crate) hsl_saturation: Option<i32>,
     pub(crate) hsl_lum: Option<i32>,
     pub(crate) hsl_hue_offset: Option<i32>,
     pub(crate) hsl_smoothness: Option<i32>,
+    /// The supersampling factor of a capture, only saved in the state files of the captures.
+    pub(crate) supersampling: Option<i32>,
+    pub(crate) supersampling_jitter: Option<bool>,
     pub(crate) version: Option<String>,
 }
 
@@ -38,15 +59,32 @@ impl From<&RenderSettings> for SavedState {
             max_iter: Some(rs.max_iter),
             precision: Some(rs.prec),
             void_fill: Some(void_fills()[rs.void_fill_index].clone()),
+            coloring_mode: Some(rs.coloring_mode()),
+            distance_threshold: Some(rs.distance_threshold),
+            trap_shape: Some(rs.orbit_trap.shape),
+            trap_center: Some(rs.orbit_trap.format_center()),
+            trap_size: Some(rs.orbit_trap.size),
+            trap_angle: Some(rs.orbit_trap.angle),
+            density_mode: Some(rs.buddhabrot.mode),
+            samples_per_pixel: Some(rs.buddhabrot.samples_per_pixel),
+            nebula_limits: Some(rs.buddhabrot.nebula_limits),
+            interior_mode: Some(rs.interior.mode),
+            interior_palette_name: Some(rs.get_interior_palette().name.to_string()),
             julia_constant: Some(rs.julia_constant.to_string()),
             mandel_constant: Some(rs.mandel_constant.to_string()),
             bailout: Some(rs.bailout),
+            escape_norm: Some(rs.escape_norm),
+            exponent: Some(rs.exponent),
+            newton_roots: Some(format_complex_list(&rs.newton_poly.roots)),
+            formula: Some(rs.formula.source.clone()),
             smoothness: Some(rs.smoothness),
             hsl_lum: Some(rs.hsl_settings.lum),
             hsl_mode: Some(rs.hsl_settings.enabled),
             hsl_saturation: Some(rs.hsl_settings.saturation),
             hsl_hue_offset: Some(rs.hsl_settings.hue_offset),
             hsl_smoothness: Some(rs.hsl_settings.smoothness),
+            supersampling: None,
+            supersampling_jitter: None,
             version: Some(VERSION.to_string()),
         }
     }
@@ -63,8 +101,14 @@ impl FromStr for SavedState {
 impl RenderSettings {
     /// Saves the app state to an rsf file with the provided filename (extension included).
     pub(crate) fn save(&self, filename: &str) -> Result<(), String> {
-        let saved_state = SavedState::from(self);
-        let str = toml::to_string_pretty(&saved_state)
+        SavedState::from(self).write(filename)
+    }
+}
+
+impl SavedState {
+    /// Writes the state to an rsf file with the provided filename (extension included).
+    pub(crate) fn write(&self, filename: &str) -> Result<(), String> {
+        let str = toml::to_string_pretty(self)
             .map_err(|err| format!("Could not save the current state: {}", esc(err)))?;
 
         let mut file = File::create(filename)